@@ -0,0 +1,429 @@
+//! Admin and ops CLI for `mojo_contract`.
+
+mod rpc;
+
+use std::path::PathBuf;
+
+use anchor_lang::{prelude::Pubkey, solana_program::instruction::Instruction};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use mojo_sdk::{args, instructions, pda, state, PairKeys, PlatformState};
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+use crate::rpc::RpcClient;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// JSON-RPC endpoint
+    #[arg(
+        long,
+        short = 'u',
+        env = "MOJO_RPC_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// Keypair of the signer, the platform admin for admin commands
+    #[arg(long, short = 'k', env = "MOJO_KEYPAIR")]
+    keypair: Option<PathBuf>,
+
+    /// Print the signed transaction and its simulation instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Platform configuration
+    #[command(subcommand)]
+    Platform(PlatformCommand),
+    /// Pairs
+    #[command(subcommand)]
+    Pair(PairCommand),
+}
+
+#[derive(Subcommand)]
+enum PlatformCommand {
+    /// Print the platform state
+    Show,
+    /// Create the platform state with the signer as admin
+    Init {
+        /// MOJO mint
+        #[arg(long)]
+        base_mint: Pubkey,
+        /// Protocol fee in basis points
+        #[arg(long)]
+        fee_rate: u16,
+    },
+    /// Stop trading and liquidity changes
+    Pause,
+    /// Resume trading and liquidity changes
+    Unpause,
+    /// Announce a protocol fee change, applied after the fee change delay
+    ProposeFeeChange {
+        /// Protocol fee in basis points
+        #[arg(long)]
+        fee_rate: u16,
+    },
+    /// Apply the pending fee change once its delay has passed
+    ExecuteFeeChange,
+    /// Drop the pending fee change
+    CancelFeeChange,
+    /// Change the notice later fee changes give
+    UpdateFeeChangeDelay {
+        #[arg(long)]
+        seconds: i64,
+    },
+    /// Change the default minimum time between swaps by one user on a pair
+    UpdateSwapCooldown {
+        #[arg(long)]
+        seconds: i64,
+    },
+    /// Change the minimum time between price samples
+    UpdateObservationInterval {
+        #[arg(long)]
+        seconds: i64,
+    },
+    /// Withdraw collected protocol fees from the fee collector
+    WithdrawFees {
+        /// Token account to receive them
+        #[arg(long)]
+        destination: Pubkey,
+        #[arg(long)]
+        amount: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum PairCommand {
+    /// Print a pair's state, its price accumulators and its observations
+    Show(PairAddress),
+    /// Create the pair of the MOJO mint and `paired_mint`
+    Create {
+        /// MOJO mint
+        #[arg(long)]
+        base_mint: Pubkey,
+        #[arg(long)]
+        paired_mint: Pubkey,
+        /// LP fee in basis points
+        #[arg(long)]
+        lp_fee_rate: u16,
+    },
+    /// Override the platform swap cooldown for one pair
+    UpdateSwapCooldown {
+        #[command(flatten)]
+        pair: PairAddress,
+        /// Seconds between swaps; leave out to use the platform default
+        #[arg(long)]
+        seconds: Option<i64>,
+    },
+}
+
+/// A pair, by address or by its mints.
+#[derive(Args)]
+struct PairAddress {
+    #[arg(long, conflicts_with_all = ["base_mint", "paired_mint"])]
+    pair: Option<Pubkey>,
+    #[arg(long, requires = "paired_mint")]
+    base_mint: Option<Pubkey>,
+    #[arg(long, requires = "base_mint")]
+    paired_mint: Option<Pubkey>,
+}
+
+impl PairAddress {
+    fn resolve(&self) -> Result<Pubkey> {
+        match (self.pair, self.base_mint, self.paired_mint) {
+            (Some(pair), _, _) => Ok(pair),
+            (None, Some(base), Some(paired)) => Ok(pda::pair(&base, &paired)),
+            _ => bail!("pass --pair, or --base-mint and --paired-mint"),
+        }
+    }
+}
+
+struct Session {
+    rpc: RpcClient,
+    keypair: Option<PathBuf>,
+    dry_run: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let ctx = Session {
+        rpc: RpcClient::new(cli.url),
+        keypair: cli.keypair,
+        dry_run: cli.dry_run,
+    };
+
+    match cli.command {
+        Command::Platform(command) => platform(&ctx, command),
+        Command::Pair(command) => pair(&ctx, command),
+    }
+}
+
+fn platform(ctx: &Session, command: PlatformCommand) -> Result<()> {
+    let instruction = match command {
+        PlatformCommand::Show => return show_platform(ctx),
+        PlatformCommand::Init {
+            base_mint,
+            fee_rate,
+        } => instructions::initialize_platform(
+            &ctx.signer()?.pubkey(),
+            &base_mint,
+            &ctx.token_program(&base_mint)?,
+            args::InitializePlatform {
+                protocol_fee_rate: fee_rate,
+            },
+        ),
+        PlatformCommand::Pause => instructions::pause_platform(
+            &ctx.signer()?.pubkey(),
+            args::PausePlatform { pause: true },
+        ),
+        PlatformCommand::Unpause => instructions::pause_platform(
+            &ctx.signer()?.pubkey(),
+            args::PausePlatform { pause: false },
+        ),
+        PlatformCommand::ProposeFeeChange { fee_rate } => instructions::propose_fee_change(
+            &ctx.signer()?.pubkey(),
+            args::ProposeFeeChange {
+                new_fee_rate: fee_rate,
+            },
+        ),
+        PlatformCommand::ExecuteFeeChange => {
+            instructions::execute_fee_change(&ctx.signer()?.pubkey())
+        }
+        PlatformCommand::CancelFeeChange => {
+            instructions::cancel_fee_change(&ctx.signer()?.pubkey())
+        }
+        PlatformCommand::UpdateFeeChangeDelay { seconds } => instructions::update_fee_change_delay(
+            &ctx.signer()?.pubkey(),
+            args::UpdateFeeChangeDelay {
+                delay_seconds: seconds,
+            },
+        ),
+        PlatformCommand::UpdateSwapCooldown { seconds } => instructions::update_swap_cooldown(
+            &ctx.signer()?.pubkey(),
+            args::UpdateSwapCooldown {
+                cooldown_seconds: seconds,
+            },
+        ),
+        PlatformCommand::UpdateObservationInterval { seconds } => {
+            instructions::update_observation_interval(
+                &ctx.signer()?.pubkey(),
+                args::UpdateObservationInterval {
+                    interval_seconds: seconds,
+                },
+            )
+        }
+        PlatformCommand::WithdrawFees {
+            destination,
+            amount,
+        } => {
+            let platform = ctx.platform_state()?;
+            let mint = platform.base_token_mint;
+            instructions::withdraw_platform_fees(
+                &ctx.signer()?.pubkey(),
+                &platform.fee_collector,
+                &mint,
+                &destination,
+                &ctx.token_program(&mint)?,
+                args::WithdrawPlatformFees { amount },
+            )
+        }
+    };
+    ctx.submit(instruction)
+}
+
+fn pair(ctx: &Session, command: PairCommand) -> Result<()> {
+    let instruction = match command {
+        PairCommand::Show(address) => return show_pair(ctx, &address.resolve()?),
+        PairCommand::Create {
+            base_mint,
+            paired_mint,
+            lp_fee_rate,
+        } => {
+            let keys = PairKeys::new(
+                base_mint,
+                paired_mint,
+                ctx.token_program(&base_mint)?,
+                ctx.token_program(&paired_mint)?,
+            );
+            println!("pair: {}", keys.pair);
+            instructions::create_pair(
+                &keys,
+                &ctx.signer()?.pubkey(),
+                args::CreatePair { lp_fee_rate },
+            )
+        }
+        PairCommand::UpdateSwapCooldown { pair, seconds } => {
+            instructions::update_pair_swap_cooldown(
+                &pair.resolve()?,
+                &ctx.signer()?.pubkey(),
+                args::UpdatePairSwapCooldown {
+                    cooldown_seconds: seconds,
+                },
+            )
+        }
+    };
+    ctx.submit(instruction)
+}
+
+fn show_platform(ctx: &Session) -> Result<()> {
+    let platform = ctx.platform_state()?;
+
+    println!("address: {}", pda::platform_state());
+    println!("admin: {}", platform.admin);
+    println!("base_token_mint: {}", platform.base_token_mint);
+    println!("fee_collector: {}", platform.fee_collector);
+    println!("is_paused: {}", platform.is_paused);
+    println!("protocol_fee_rate: {} bps", platform.protocol_fee_rate);
+    println!("swap_cooldown_seconds: {}", platform.swap_cooldown_seconds);
+    println!(
+        "observation_interval_seconds: {}",
+        platform.observation_interval_seconds
+    );
+    println!(
+        "fee_change_delay_seconds: {}",
+        platform.fee_change_delay_seconds
+    );
+
+    let address = pda::pending_fee_change();
+    match ctx.rpc.account(&address)? {
+        Some(account) => {
+            let pending = state::pending_fee_change(&account.data)
+                .map_err(|e| anyhow!("{address} is not a pending fee change: {e}"))?;
+            println!(
+                "pending_fee_change: {} bps, effective at {}",
+                pending.new_fee_rate, pending.effective_at
+            );
+        }
+        None => println!("pending_fee_change: none"),
+    }
+    Ok(())
+}
+
+fn show_pair(ctx: &Session, address: &Pubkey) -> Result<()> {
+    let account = ctx
+        .rpc
+        .account(address)?
+        .with_context(|| format!("no pair at {address}"))?;
+    let pair = state::pair(&account.data).map_err(|e| anyhow!("{address} is not a pair: {e}"))?;
+
+    println!("address: {address}");
+    println!("base_token_mint: {}", pair.base_token_mint);
+    println!("paired_token_mint: {}", pair.paired_token_mint);
+    println!("lp_mint: {}", pair.lp_mint);
+    println!("base_vault: {}", pair.base_vault);
+    println!("paired_vault: {}", pair.paired_vault);
+    println!("base_reserve: {}", pair.base_reserve);
+    println!("paired_reserve: {}", pair.paired_reserve);
+    println!("total_liquidity: {}", pair.total_liquidity);
+    println!("lp_fee_rate: {} bps", pair.lp_fee_rate);
+    match pair.swap_cooldown_seconds {
+        Some(seconds) => println!("swap_cooldown_seconds: {seconds}"),
+        None => println!("swap_cooldown_seconds: platform default"),
+    }
+    println!("last_swap_time: {}", pair.last_swap_time);
+    println!(
+        "base_price_cumulative: {} (Q64.64)",
+        pair.base_price_cumulative
+    );
+    println!(
+        "paired_price_cumulative: {} (Q64.64)",
+        pair.paired_price_cumulative
+    );
+
+    let address = pda::observations(address);
+    println!("observations: {address}");
+    let Some(account) = ctx.rpc.account(&address)? else {
+        println!("observation_count: none");
+        return Ok(());
+    };
+    let buffer = state::observations(&account.data)
+        .map_err(|e| anyhow!("{address} is not an observations account: {e}"))?;
+    println!(
+        "observation_count: {} of {}",
+        buffer.header.count, buffer.header.capacity
+    );
+    if let Some(latest) = buffer.samples.get(buffer.header.index as usize) {
+        println!(
+            "latest_observation: at {}, base {}, paired {}",
+            latest.timestamp, latest.base_price_cumulative, latest.paired_price_cumulative
+        );
+    }
+    Ok(())
+}
+
+impl Session {
+    fn signer(&self) -> Result<Keypair> {
+        let path = match &self.keypair {
+            Some(path) => path.clone(),
+            None => PathBuf::from(std::env::var("HOME").context("HOME is not set")?)
+                .join(".config/solana/id.json"),
+        };
+        read_keypair_file(&path).map_err(|e| anyhow!("cannot read {}: {e}", path.display()))
+    }
+
+    /// Token program owning `mint`.
+    fn token_program(&self, mint: &Pubkey) -> Result<Pubkey> {
+        Ok(self
+            .rpc
+            .account(mint)?
+            .with_context(|| format!("no mint at {mint}"))?
+            .owner)
+    }
+
+    /// The platform state account.
+    fn platform_state(&self) -> Result<PlatformState> {
+        let address = pda::platform_state();
+        let account = self
+            .rpc
+            .account(&address)?
+            .context("platform is not initialized")?;
+        state::platform_state(&account.data)
+            .map_err(|e| anyhow!("{address} is not the platform state: {e}"))
+    }
+
+    /// Signs `instruction` into a transaction paid by the signer, then sends
+    /// it or, with `--dry-run`, prints and simulates it.
+    fn submit(&self, instruction: Instruction) -> Result<()> {
+        let signer = self.signer()?;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&signer.pubkey()),
+            &[&signer],
+            self.rpc.latest_blockhash()?,
+        );
+
+        if self.dry_run {
+            println!("transaction: {}", rpc::encode(&transaction)?);
+            let simulation = self.rpc.simulate(&transaction)?;
+            println!("simulation: {}", serde_json::to_string_pretty(&simulation)?);
+            if !simulation["err"].is_null() {
+                bail!("simulation failed");
+            }
+            return Ok(());
+        }
+
+        let signature = self.rpc.send_and_confirm(&transaction)?;
+        println!("signature: {signature}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn cli_is_well_formed() {
+        Cli::command().debug_assert();
+    }
+}
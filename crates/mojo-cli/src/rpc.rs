@@ -0,0 +1,133 @@
+//! The few JSON-RPC methods the CLI needs, over blocking HTTP.
+
+use std::{
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anchor_lang::{prelude::Pubkey, solana_program::hash::Hash};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_transaction::Transaction;
+
+const COMMITMENT: &str = "confirmed";
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct RpcClient {
+    url: String,
+}
+
+/// An account's owner and data.
+pub struct Account {
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+impl RpcClient {
+    pub fn new(url: String) -> Self {
+        Self { url }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let mut response: Value = ureq::post(&self.url)
+            .send_json(request)
+            .with_context(|| format!("{method} request to {} failed", self.url))?
+            .into_json()?;
+
+        if let Some(error) = response.get("error") {
+            bail!("{method} failed: {error}");
+        }
+        Ok(response["result"].take())
+    }
+
+    pub fn latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([{ "commitment": COMMITMENT }]))?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .context("getLatestBlockhash returned no blockhash")?;
+        Hash::from_str(blockhash).map_err(|e| anyhow!("invalid blockhash {blockhash}: {e}"))
+    }
+
+    pub fn account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": COMMITMENT }]),
+        )?;
+        let value = &result["value"];
+        if value.is_null() {
+            return Ok(None);
+        }
+
+        let owner = value["owner"]
+            .as_str()
+            .context("account has no owner")?
+            .parse()?;
+        let data = value["data"][0]
+            .as_str()
+            .context("account has no base64 data")?;
+        Ok(Some(Account {
+            owner,
+            data: STANDARD.decode(data)?,
+        }))
+    }
+
+    /// Simulates `transaction` against the latest state. Signatures are not
+    /// checked and the blockhash is replaced, so it works for any signer.
+    pub fn simulate(&self, transaction: &Transaction) -> Result<Value> {
+        let mut result = self.call(
+            "simulateTransaction",
+            json!([
+                encode(transaction)?,
+                {
+                    "encoding": "base64",
+                    "commitment": COMMITMENT,
+                    "sigVerify": false,
+                    "replaceRecentBlockhash": true,
+                },
+            ]),
+        )?;
+        Ok(result["value"].take())
+    }
+
+    /// Sends `transaction` and waits until it is confirmed.
+    pub fn send_and_confirm(&self, transaction: &Transaction) -> Result<String> {
+        let signature = self.call(
+            "sendTransaction",
+            json!([
+                encode(transaction)?,
+                { "encoding": "base64", "preflightCommitment": COMMITMENT },
+            ]),
+        )?;
+        let signature = signature
+            .as_str()
+            .context("sendTransaction returned no signature")?
+            .to_string();
+
+        let started = Instant::now();
+        while started.elapsed() < CONFIRM_TIMEOUT {
+            let result = self.call("getSignatureStatuses", json!([[signature]]))?;
+            let status = &result["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    bail!("transaction {signature} failed: {}", status["err"]);
+                }
+                if matches!(
+                    status["confirmationStatus"].as_str(),
+                    Some("confirmed" | "finalized")
+                ) {
+                    return Ok(signature);
+                }
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+        bail!("transaction {signature} was not confirmed within {CONFIRM_TIMEOUT:?}")
+    }
+}
+
+/// Wire format of `transaction`, base64 encoded.
+pub fn encode(transaction: &Transaction) -> Result<String> {
+    Ok(STANDARD.encode(bincode::serialize(transaction)?))
+}
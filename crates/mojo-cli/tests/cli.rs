@@ -0,0 +1,341 @@
+//! Runs the `mojo-cli` binary against the in-process SVM of `mojo-tests`.
+//! [`Rpc`] answers the JSON-RPC methods the CLI calls from an [`Env`], over a
+//! local HTTP socket, so the commands go through the same client code as
+//! against a validator.
+
+use std::{
+    collections::HashSet,
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+    thread,
+    time::Duration,
+};
+
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use mojo_sdk::pda;
+use mojo_tests::{Env, MintExtensions};
+use serde_json::{json, Value};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+/// A JSON-RPC endpoint backed by `env`, and the admin keypair file the CLI
+/// signs with.
+struct Rpc {
+    env: Env,
+    listener: TcpListener,
+    keypair: PathBuf,
+    /// Signatures of the transactions sent through the endpoint
+    confirmed: HashSet<String>,
+}
+
+impl Rpc {
+    fn new(env: Env) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+
+        let keypair = std::env::temp_dir().join(format!(
+            "mojo-cli-{}-{}.json",
+            std::process::id(),
+            listener.local_addr().unwrap().port()
+        ));
+        let bytes = env.admin.to_bytes().to_vec();
+        std::fs::write(&keypair, serde_json::to_string(&bytes).unwrap()).unwrap();
+
+        Self {
+            env,
+            listener,
+            keypair,
+            confirmed: HashSet::new(),
+        }
+    }
+
+    /// Runs the CLI with `args` as the admin, answering its requests until
+    /// it exits.
+    async fn run(&mut self, args: &[&str]) -> Output {
+        let url = format!("http://{}", self.listener.local_addr().unwrap());
+        let mut child = Command::new(env!("CARGO_BIN_EXE_mojo-cli"))
+            .arg("--url")
+            .arg(url)
+            .arg("--keypair")
+            .arg(&self.keypair)
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => self.respond(stream).await,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    if child.try_wait().unwrap().is_some() {
+                        break;
+                    }
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Err(error) => panic!("accept failed: {error}"),
+            }
+        }
+        child.wait_with_output().unwrap()
+    }
+
+    /// [`Rpc::run`] for a command that must succeed. Returns its output.
+    async fn cli(&mut self, args: &[&str]) -> String {
+        let output = self.run(args).await;
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            output.status.success(),
+            "mojo-cli {args:?} failed:\n{stdout}{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        stdout
+    }
+
+    /// Answers one HTTP request carrying a JSON-RPC call.
+    async fn respond(&mut self, stream: TcpStream) {
+        stream.set_nonblocking(false).unwrap();
+        let mut reader = BufReader::new(&stream);
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        let request: Value = serde_json::from_slice(&body).unwrap();
+
+        let response = match self
+            .call(request["method"].as_str().unwrap(), &request["params"])
+            .await
+        {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+            Err(message) => json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": { "code": -32002, "message": message },
+            }),
+        };
+        let response = response.to_string();
+        write!(
+            &stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{response}",
+            response.len()
+        )
+        .unwrap();
+    }
+
+    async fn call(&mut self, method: &str, params: &Value) -> Result<Value, String> {
+        let banks = &mut self.env.context.banks_client;
+        match method {
+            "getLatestBlockhash" => {
+                let blockhash = banks.get_latest_blockhash().await.unwrap();
+                Ok(json!({ "value": { "blockhash": blockhash.to_string() } }))
+            }
+            "getAccountInfo" => {
+                let address = params[0].as_str().unwrap().parse().unwrap();
+                let value = banks.get_account(address).await.unwrap().map(|account| {
+                    json!({
+                        "owner": account.owner.to_string(),
+                        "lamports": account.lamports,
+                        "data": [STANDARD.encode(&account.data), "base64"],
+                    })
+                });
+                Ok(json!({ "value": value }))
+            }
+            "simulateTransaction" => {
+                let simulation = banks
+                    .simulate_transaction(transaction(params))
+                    .await
+                    .unwrap();
+                let details = simulation.simulation_details.unwrap();
+                let err = simulation
+                    .result
+                    .unwrap()
+                    .err()
+                    .map(|error| error.to_string());
+                Ok(json!({
+                    "value": {
+                        "err": err,
+                        "logs": details.logs,
+                        "unitsConsumed": details.units_consumed,
+                    },
+                }))
+            }
+            // Sent transactions are processed at once, and failures are
+            // reported as preflight errors
+            "sendTransaction" => {
+                let transaction = transaction(params);
+                let signature = transaction.signatures[0].to_string();
+                banks
+                    .process_transaction(transaction)
+                    .await
+                    .map_err(|error| format!("Transaction simulation failed: {error}"))?;
+                self.confirmed.insert(signature.clone());
+                Ok(json!(signature))
+            }
+            "getSignatureStatuses" => {
+                let statuses: Vec<Value> = params[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|signature| {
+                        if self.confirmed.contains(signature.as_str().unwrap()) {
+                            json!({ "err": null, "confirmationStatus": "confirmed" })
+                        } else {
+                            Value::Null
+                        }
+                    })
+                    .collect();
+                Ok(json!({ "value": statuses }))
+            }
+            _ => Err(format!("unexpected method {method}")),
+        }
+    }
+}
+
+impl Drop for Rpc {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.keypair);
+    }
+}
+
+/// The transaction in the first parameter, base64 encoded.
+fn transaction(params: &Value) -> Transaction {
+    let data = STANDARD.decode(params[0].as_str().unwrap()).unwrap();
+    bincode::deserialize(&data).unwrap()
+}
+
+#[tokio::test]
+async fn administers_the_platform() {
+    let mut rpc = Rpc::new(Env::new().await);
+    let admin = rpc.env.admin();
+    let base_mint = rpc.env.base_mint.to_string();
+
+    rpc.cli(&[
+        "platform",
+        "init",
+        "--base-mint",
+        &base_mint,
+        "--fee-rate",
+        "30",
+    ])
+    .await;
+    let platform = rpc.env.platform().await;
+    assert_eq!(platform.admin, admin.pubkey());
+    assert_eq!(platform.protocol_fee_rate, 30);
+
+    let paired_mint = rpc
+        .env
+        .create_mint(&TOKEN_PROGRAM_ID, MintExtensions::default())
+        .await;
+    let keys = rpc.env.pair_keys(&paired_mint, &TOKEN_PROGRAM_ID);
+    let output = rpc
+        .cli(&[
+            "pair",
+            "create",
+            "--base-mint",
+            &base_mint,
+            "--paired-mint",
+            &paired_mint.to_string(),
+            "--lp-fee-rate",
+            "25",
+        ])
+        .await;
+    assert!(output.contains(&format!("pair: {}", keys.pair)));
+    assert_eq!(rpc.env.pair(&keys).await.lp_fee_rate, 25);
+
+    // A trade some time after the deposit leaves protocol fees to withdraw
+    // and moves the price accumulators
+    rpc.env
+        .fund(&keys, &admin.pubkey(), 2_000_000, 2_000_000)
+        .await;
+    rpc.env
+        .add_liquidity(&keys, &admin, 1_000_000, 1_000_000)
+        .await
+        .unwrap();
+    rpc.env.warp(60).await;
+    rpc.env.swap(&keys, &admin, 100_000, true).await.unwrap();
+    let fee_collector = rpc.env.fee_collector();
+    let fees = rpc.env.balance(&fee_collector).await;
+    assert!(fees > 0);
+
+    // A dry run prints the simulation and changes nothing
+    let output = rpc
+        .cli(&[
+            "--dry-run",
+            "platform",
+            "propose-fee-change",
+            "--fee-rate",
+            "50",
+        ])
+        .await;
+    assert!(output.contains("transaction: "));
+    assert!(output.contains("simulation: "));
+    let pending_fee_change = pda::pending_fee_change();
+    assert!(rpc.env.account_data(&pending_fee_change).await.is_none());
+
+    rpc.cli(&["platform", "propose-fee-change", "--fee-rate", "50"])
+        .await;
+    assert!(rpc.cli(&["platform", "show"]).await.contains(&format!(
+        "pending_fee_change: 50 bps, effective at {}",
+        rpc.env.clock().await.unix_timestamp + platform.fee_change_delay_seconds
+    )));
+
+    // The fee change delay has not passed, so the simulation fails
+    let output = rpc
+        .run(&["--dry-run", "platform", "execute-fee-change"])
+        .await;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("simulation failed"));
+
+    let treasury = keys.user_base_ata(&admin.pubkey());
+    let before = rpc.env.balance(&treasury).await;
+    rpc.cli(&[
+        "platform",
+        "withdraw-fees",
+        "--destination",
+        &treasury.to_string(),
+        "--amount",
+        &fees.to_string(),
+    ])
+    .await;
+    assert_eq!(rpc.env.balance(&treasury).await - before, fees);
+    assert_eq!(rpc.env.balance(&fee_collector).await, 0);
+
+    let output = rpc
+        .cli(&["pair", "show", "--pair", &keys.pair.to_string()])
+        .await;
+    let pair = rpc.env.pair(&keys).await;
+    let observations = rpc.env.observations(&keys).await;
+    assert!(pair.base_price_cumulative > 0);
+    for line in [
+        format!(
+            "base_price_cumulative: {} (Q64.64)",
+            pair.base_price_cumulative
+        ),
+        format!(
+            "paired_price_cumulative: {} (Q64.64)",
+            pair.paired_price_cumulative
+        ),
+        format!("observations: {}", keys.observations),
+        format!(
+            "observation_count: {} of {}",
+            observations.header.count, observations.header.capacity
+        ),
+    ] {
+        assert!(output.contains(&line), "no {line:?} in\n{output}");
+    }
+}
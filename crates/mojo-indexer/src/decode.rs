@@ -0,0 +1,157 @@
+//! Event decoding.
+//!
+//! Without the `event-cpi` feature the program logs each event as a
+//! `Program data: <base64>` line. With it, each event is the data of a
+//! self-CPI, prefixed with `EVENT_IX_TAG_LE`. Both carry the event's
+//! discriminator followed by its borsh encoding.
+
+use anchor_lang::{event::EVENT_IX_TAG_LE, prelude::Pubkey, AnchorDeserialize, Discriminator};
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use mojo_contract::{
+    FeeChangeCancelled, FeeChangeProposed, FeeRateUpdated, FeesWithdrawn, LiquidityAdded,
+    LiquidityRemoved, PairCreated, PlatformInitialized, PlatformPauseChanged, Swapped,
+};
+
+use crate::transaction::FetchedTransaction;
+
+/// Every event `mojo_contract` emits.
+pub enum MojoEvent {
+    PlatformInitialized(PlatformInitialized),
+    PlatformPauseChanged(PlatformPauseChanged),
+    FeeChangeProposed(FeeChangeProposed),
+    FeeChangeCancelled(FeeChangeCancelled),
+    FeeRateUpdated(FeeRateUpdated),
+    FeesWithdrawn(FeesWithdrawn),
+    PairCreated(PairCreated),
+    LiquidityAdded(LiquidityAdded),
+    LiquidityRemoved(LiquidityRemoved),
+    Swapped(Swapped),
+}
+
+macro_rules! decode_as {
+    ($data:expr, $($event:ident),+ $(,)?) => {{
+        let data: &[u8] = $data;
+        $(
+            if let Some(body) = data.strip_prefix($event::DISCRIMINATOR) {
+                let event = $event::try_from_slice(body)
+                    .with_context(|| format!("malformed {} event", stringify!($event)))?;
+                return Ok(Some(MojoEvent::$event(event)));
+            }
+        )+
+        Ok(None)
+    }};
+}
+
+/// Decodes one event from its discriminator and borsh encoding. Returns
+/// `None` for a discriminator this build does not know, such as an event a
+/// newer program added.
+pub fn decode_event(data: &[u8]) -> Result<Option<MojoEvent>> {
+    decode_as!(
+        data,
+        PlatformInitialized,
+        PlatformPauseChanged,
+        FeeChangeProposed,
+        FeeChangeCancelled,
+        FeeRateUpdated,
+        FeesWithdrawn,
+        PairCreated,
+        LiquidityAdded,
+        LiquidityRemoved,
+        Swapped,
+    )
+}
+
+/// Events `program_id` emitted in `tx`, in emission order, with `None` for
+/// unknown ones so the others keep their positions. Failed transactions
+/// have none, since their state changes were rolled back.
+///
+/// Event CPIs are read when there are any. Only otherwise are the logs
+/// read, and only then do truncated logs make the result unreliable.
+pub fn transaction_events(
+    tx: &FetchedTransaction,
+    program_id: &Pubkey,
+) -> Result<Vec<Option<MojoEvent>>> {
+    let Some(meta) = tx.meta.as_ref().filter(|_| !tx.failed()) else {
+        return Ok(Vec::new());
+    };
+
+    let events = cpi_events(tx, program_id)?;
+    if !events.is_empty() {
+        return Ok(events);
+    }
+    match &meta.log_messages {
+        Some(logs) => log_events(logs, program_id),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Events sent through `event-cpi` self-CPIs of `program_id`.
+fn cpi_events(tx: &FetchedTransaction, program_id: &Pubkey) -> Result<Vec<Option<MojoEvent>>> {
+    let Some(inner) = tx.meta.as_ref().and_then(|m| m.inner_instructions.as_ref()) else {
+        return Ok(Vec::new());
+    };
+
+    let account_keys = tx.account_keys()?;
+    let mut events = Vec::new();
+    for instruction in inner.iter().flat_map(|i| &i.instructions) {
+        let program = account_keys
+            .get(instruction.program_id_index as usize)
+            .context("inner instruction program index out of range")?;
+        if program != program_id {
+            continue;
+        }
+
+        let data = bs58::decode(&instruction.data)
+            .into_vec()
+            .context("inner instruction data is not base58")?;
+        // Only event CPIs carry the tag; other self-CPIs are not events
+        if let Some(event) = data.strip_prefix(EVENT_IX_TAG_LE) {
+            events.push(decode_event(event)?);
+        }
+    }
+    Ok(events)
+}
+
+/// Events in `Program data:` lines written while `program_id` is the
+/// innermost running program.
+fn log_events(logs: &[String], program_id: &Pubkey) -> Result<Vec<Option<MojoEvent>>> {
+    let program_id = program_id.to_string();
+    let mut call_stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        if line == "Log truncated" {
+            bail!("transaction logs were truncated, so events may be missing");
+        }
+        if let Some(data) = line.strip_prefix("Program data: ") {
+            if call_stack.last() == Some(&program_id.as_str()) {
+                let data = STANDARD
+                    .decode(data)
+                    .context("program data is not base64")?;
+                events.push(decode_event(&data)?);
+            }
+            continue;
+        }
+
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        let mut words = rest.split_whitespace();
+        let (Some(program), Some(status)) = (words.next(), words.next()) else {
+            continue;
+        };
+        // `Program log:`, `Program return:` and the like are not frames
+        if program.ends_with(':') {
+            continue;
+        }
+        match status {
+            "invoke" => call_stack.push(program),
+            "success" | "failed:" => {
+                call_stack.pop();
+            }
+            _ => {}
+        }
+    }
+    Ok(events)
+}
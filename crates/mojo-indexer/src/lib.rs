@@ -0,0 +1,65 @@
+//! Indexer for `mojo_contract` events.
+//!
+//! Reads transactions fetched with `getTransaction` (`"encoding": "json"`),
+//! decodes the events the program emitted, whether logged or sent through
+//! `event-cpi` self-CPIs, and writes them to SQLite as rows of `pairs`,
+//! `swaps`, `liquidity_changes` and `admin_actions`.
+
+pub mod decode;
+pub mod store;
+pub mod transaction;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+
+use crate::{
+    decode::transaction_events,
+    store::{insert_event, EventSource, Store},
+    transaction::FetchedTransaction,
+};
+
+/// Counts from one indexing run.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct IndexSummary {
+    pub transactions: usize,
+    pub events: usize,
+    /// Events not already in the database
+    pub inserted: usize,
+    /// Events with a discriminator this build does not know, left out
+    pub unknown: usize,
+}
+
+/// Indexes the events `program_id` emitted in `transactions`, all in one
+/// database transaction.
+pub fn index_transactions(
+    store: &mut Store,
+    transactions: &[FetchedTransaction],
+    program_id: &Pubkey,
+) -> Result<IndexSummary> {
+    store.transaction(|db| {
+        let mut summary = IndexSummary::default();
+        for tx in transactions {
+            let signature = tx.signature()?;
+            let events = transaction_events(tx, program_id)
+                .with_context(|| format!("cannot decode events of {signature}"))?;
+
+            for (event_index, event) in events.iter().enumerate() {
+                let Some(event) = event else {
+                    summary.unknown += 1;
+                    continue;
+                };
+                let source = EventSource {
+                    signature,
+                    event_index,
+                    slot: tx.slot,
+                };
+                if insert_event(db, &source, event)? {
+                    summary.inserted += 1;
+                }
+            }
+            summary.transactions += 1;
+            summary.events += events.len();
+        }
+        Ok(summary)
+    })
+}
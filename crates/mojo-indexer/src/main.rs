@@ -0,0 +1,46 @@
+use std::{fs, path::PathBuf};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+use clap::Parser;
+use mojo_indexer::{index_transactions, store::Store, transaction::read_transactions};
+
+/// Decodes mojo_contract events from fetched transactions into SQLite.
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    /// SQLite database to write, created if missing
+    #[arg(long, default_value = "mojo.sqlite")]
+    db: String,
+
+    /// Program to index, if not the one mojo_contract is built with
+    #[arg(long, default_value_t = mojo_contract::ID)]
+    program_id: Pubkey,
+
+    /// JSON files, each an array of getTransaction results
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let mut store = Store::open(&args.db)?;
+
+    for path in &args.files {
+        let json =
+            fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        let transactions = read_transactions(&json)?;
+        let summary = index_transactions(&mut store, &transactions, &args.program_id)
+            .with_context(|| format!("cannot index {}", path.display()))?;
+
+        println!(
+            "{}: {} transactions, {} events, {} new, {} unknown",
+            path.display(),
+            summary.transactions,
+            summary.events,
+            summary.inserted,
+            summary.unknown,
+        );
+    }
+    Ok(())
+}
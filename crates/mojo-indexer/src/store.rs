@@ -0,0 +1,414 @@
+//! SQLite storage.
+//!
+//! Every row is keyed by the transaction signature and the event's position
+//! within it, so indexing the same transaction twice is a no-op. Token
+//! amounts are stored as INTEGER; an amount above `i64::MAX` is an error
+//! rather than a silently wrong row.
+//!
+//! The schema version is kept in `PRAGMA user_version`. Databases written
+//! before it was, which read as version 0, are migrated when opened.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, Transaction};
+
+use crate::decode::MojoEvent;
+
+/// Tables of a new database, besides `admin_actions`.
+const SCHEMA: &str = "
+CREATE TABLE pairs (
+    pair              TEXT PRIMARY KEY,
+    creator           TEXT NOT NULL,
+    base_token_mint   TEXT NOT NULL,
+    paired_token_mint TEXT NOT NULL,
+    lp_mint           TEXT NOT NULL,
+    lp_fee_rate       INTEGER NOT NULL,
+    created_at        INTEGER NOT NULL,
+    signature         TEXT NOT NULL,
+    slot              INTEGER NOT NULL
+);
+
+CREATE TABLE swaps (
+    signature      TEXT NOT NULL,
+    event_index    INTEGER NOT NULL,
+    slot           INTEGER NOT NULL,
+    pair           TEXT NOT NULL,
+    user           TEXT NOT NULL,
+    is_base_input  INTEGER NOT NULL,
+    amount_in      INTEGER NOT NULL,
+    amount_out     INTEGER NOT NULL,
+    protocol_fee   INTEGER NOT NULL,
+    base_reserve   INTEGER NOT NULL,
+    paired_reserve INTEGER NOT NULL,
+    timestamp      INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX swaps_pair ON swaps (pair, timestamp);
+
+-- kind is 'add' or 'remove'; lp_amount is minted or burned accordingly
+CREATE TABLE liquidity_changes (
+    signature       TEXT NOT NULL,
+    event_index     INTEGER NOT NULL,
+    slot            INTEGER NOT NULL,
+    pair            TEXT NOT NULL,
+    user            TEXT NOT NULL,
+    kind            TEXT NOT NULL CHECK (kind IN ('add', 'remove')),
+    base_amount     INTEGER NOT NULL,
+    paired_amount   INTEGER NOT NULL,
+    lp_amount       INTEGER NOT NULL,
+    base_reserve    INTEGER NOT NULL,
+    paired_reserve  INTEGER NOT NULL,
+    total_liquidity INTEGER NOT NULL,
+    timestamp       INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX liquidity_changes_pair ON liquidity_changes (pair, timestamp);
+";
+
+const ADMIN_ACTIONS: &str = "
+-- Columns an action does not use are NULL
+CREATE TABLE admin_actions (
+    signature     TEXT NOT NULL,
+    event_index   INTEGER NOT NULL,
+    slot          INTEGER NOT NULL,
+    action        TEXT NOT NULL CHECK (
+        action IN (
+            'initialize_platform', 'pause_platform', 'propose_fee_change', 'execute_fee_change',
+            'cancel_fee_change', 'withdraw_fees'
+        )
+    ),
+    admin         TEXT NOT NULL,
+    paused        INTEGER,
+    old_fee_rate  INTEGER,
+    new_fee_rate  INTEGER,
+    token_mint    TEXT,
+    account       TEXT,
+    amount        INTEGER,
+    effective_at  INTEGER,
+    timestamp     INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+";
+
+/// Each step upgrades the schema from the version it is at to the next.
+const MIGRATIONS: &[fn(&Transaction) -> Result<()>] = &[migrate_admin_actions];
+
+/// Version `user_version` records once every migration has run.
+const SCHEMA_VERSION: usize = MIGRATIONS.len();
+
+pub struct Store {
+    conn: Connection,
+}
+
+/// Where an event came from.
+pub struct EventSource<'a> {
+    pub signature: &'a str,
+    pub event_index: usize,
+    pub slot: u64,
+}
+
+impl Store {
+    pub fn open(path: &str) -> Result<Self> {
+        Self::init(Connection::open(path).with_context(|| format!("cannot open {path}"))?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            bail!(
+                "database schema version {version} is newer than this indexer's {SCHEMA_VERSION}"
+            );
+        }
+
+        let db = conn.transaction()?;
+        let empty: bool = db.query_row(
+            "SELECT COUNT(*) = 0 FROM sqlite_master WHERE type = 'table'",
+            [],
+            |row| row.get(0),
+        )?;
+        if empty {
+            db.execute_batch(SCHEMA)?;
+            db.execute_batch(ADMIN_ACTIONS)?;
+        } else {
+            for migrate in &MIGRATIONS[version..] {
+                migrate(&db)?;
+            }
+        }
+        db.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        db.commit()?;
+        Ok(Self { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Runs `f` in a database transaction, committed only if it succeeds.
+    pub fn transaction<T>(&mut self, f: impl FnOnce(&Transaction) -> Result<T>) -> Result<T> {
+        let tx = self.conn.transaction()?;
+        let value = f(&tx)?;
+        tx.commit()?;
+        Ok(value)
+    }
+}
+
+/// Version 0 to 1. Unversioned `admin_actions` tables predate the fee change
+/// timelock or only partly follow it: they may lack `effective_at`, and their
+/// `action` check has no `execute_fee_change`, which was `update_fee_rate`.
+/// SQLite cannot alter a check, so the table is rebuilt.
+fn migrate_admin_actions(db: &Transaction) -> Result<()> {
+    let has_effective_at: bool = db.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('admin_actions') WHERE name = 'effective_at'",
+        [],
+        |row| row.get(0),
+    )?;
+    let effective_at = if has_effective_at {
+        "effective_at"
+    } else {
+        "NULL"
+    };
+
+    db.execute_batch("ALTER TABLE admin_actions RENAME TO admin_actions_unversioned")?;
+    db.execute_batch(ADMIN_ACTIONS)?;
+    db.execute_batch(&format!(
+        "INSERT INTO admin_actions
+         SELECT signature, event_index, slot,
+                CASE action WHEN 'update_fee_rate' THEN 'execute_fee_change' ELSE action END,
+                admin, paused, old_fee_rate, new_fee_rate, token_mint, account, amount,
+                {effective_at}, timestamp
+         FROM admin_actions_unversioned;
+         DROP TABLE admin_actions_unversioned;"
+    ))?;
+    Ok(())
+}
+
+/// Writes the row for one event. Returns whether it was new.
+pub fn insert_event(db: &Transaction, source: &EventSource, event: &MojoEvent) -> Result<bool> {
+    let EventSource {
+        signature,
+        event_index,
+        slot,
+    } = *source;
+
+    let inserted = match event {
+        MojoEvent::PairCreated(e) => db.execute(
+            "INSERT OR IGNORE INTO pairs VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                e.pair.to_string(),
+                e.creator.to_string(),
+                e.base_token_mint.to_string(),
+                e.paired_token_mint.to_string(),
+                e.lp_mint.to_string(),
+                e.lp_fee_rate,
+                e.timestamp,
+                signature,
+                slot,
+            ],
+        )?,
+        MojoEvent::Swapped(e) => db.execute(
+            "INSERT OR IGNORE INTO swaps
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                signature,
+                event_index,
+                slot,
+                e.pair.to_string(),
+                e.user.to_string(),
+                e.is_base_input,
+                e.amount_in,
+                e.amount_out,
+                e.protocol_fee,
+                e.base_reserve,
+                e.paired_reserve,
+                e.timestamp,
+            ],
+        )?,
+        MojoEvent::LiquidityAdded(e) => insert_liquidity_change(
+            db,
+            source,
+            LiquidityChange {
+                pair: e.pair.to_string(),
+                user: e.user.to_string(),
+                kind: "add",
+                base_amount: e.base_amount,
+                paired_amount: e.paired_amount,
+                lp_amount: e.lp_minted,
+                base_reserve: e.base_reserve,
+                paired_reserve: e.paired_reserve,
+                total_liquidity: e.total_liquidity,
+                timestamp: e.timestamp,
+            },
+        )?,
+        MojoEvent::LiquidityRemoved(e) => insert_liquidity_change(
+            db,
+            source,
+            LiquidityChange {
+                pair: e.pair.to_string(),
+                user: e.user.to_string(),
+                kind: "remove",
+                base_amount: e.base_amount,
+                paired_amount: e.paired_amount,
+                lp_amount: e.lp_burned,
+                base_reserve: e.base_reserve,
+                paired_reserve: e.paired_reserve,
+                total_liquidity: e.total_liquidity,
+                timestamp: e.timestamp,
+            },
+        )?,
+        MojoEvent::PlatformInitialized(e) => insert_admin_action(
+            db,
+            source,
+            AdminAction {
+                action: "initialize_platform",
+                admin: e.admin.to_string(),
+                new_fee_rate: Some(e.protocol_fee_rate),
+                token_mint: Some(e.base_token_mint.to_string()),
+                account: Some(e.fee_collector.to_string()),
+                timestamp: e.timestamp,
+                ..AdminAction::default()
+            },
+        )?,
+        MojoEvent::PlatformPauseChanged(e) => insert_admin_action(
+            db,
+            source,
+            AdminAction {
+                action: "pause_platform",
+                admin: e.admin.to_string(),
+                paused: Some(e.paused),
+                timestamp: e.timestamp,
+                ..AdminAction::default()
+            },
+        )?,
+        MojoEvent::FeeChangeProposed(e) => insert_admin_action(
+            db,
+            source,
+            AdminAction {
+                action: "propose_fee_change",
+                admin: e.admin.to_string(),
+                old_fee_rate: Some(e.current_fee_rate),
+                new_fee_rate: Some(e.new_fee_rate),
+                effective_at: Some(e.effective_at),
+                timestamp: e.timestamp,
+                ..AdminAction::default()
+            },
+        )?,
+        MojoEvent::FeeChangeCancelled(e) => insert_admin_action(
+            db,
+            source,
+            AdminAction {
+                action: "cancel_fee_change",
+                admin: e.admin.to_string(),
+                new_fee_rate: Some(e.new_fee_rate),
+                timestamp: e.timestamp,
+                ..AdminAction::default()
+            },
+        )?,
+        MojoEvent::FeeRateUpdated(e) => insert_admin_action(
+            db,
+            source,
+            AdminAction {
+                action: "execute_fee_change",
+                admin: e.admin.to_string(),
+                old_fee_rate: Some(e.old_fee_rate),
+                new_fee_rate: Some(e.new_fee_rate),
+                timestamp: e.timestamp,
+                ..AdminAction::default()
+            },
+        )?,
+        MojoEvent::FeesWithdrawn(e) => insert_admin_action(
+            db,
+            source,
+            AdminAction {
+                action: "withdraw_fees",
+                admin: e.admin.to_string(),
+                token_mint: Some(e.token_mint.to_string()),
+                account: Some(e.destination.to_string()),
+                amount: Some(e.amount),
+                timestamp: e.timestamp,
+                ..AdminAction::default()
+            },
+        )?,
+    };
+    Ok(inserted > 0)
+}
+
+struct LiquidityChange {
+    pair: String,
+    user: String,
+    kind: &'static str,
+    base_amount: u64,
+    paired_amount: u64,
+    lp_amount: u64,
+    base_reserve: u64,
+    paired_reserve: u64,
+    total_liquidity: u64,
+    timestamp: i64,
+}
+
+fn insert_liquidity_change(
+    db: &Transaction,
+    source: &EventSource,
+    row: LiquidityChange,
+) -> Result<usize> {
+    Ok(db.execute(
+        "INSERT OR IGNORE INTO liquidity_changes
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            source.signature,
+            source.event_index,
+            source.slot,
+            row.pair,
+            row.user,
+            row.kind,
+            row.base_amount,
+            row.paired_amount,
+            row.lp_amount,
+            row.base_reserve,
+            row.paired_reserve,
+            row.total_liquidity,
+            row.timestamp,
+        ],
+    )?)
+}
+
+/// `initialize_platform` records the protocol fee rate as `new_fee_rate`,
+/// the base mint as `token_mint` and the fee collector as `account`.
+/// `withdraw_fees` records the destination as `account`.
+#[derive(Default)]
+struct AdminAction {
+    action: &'static str,
+    admin: String,
+    paused: Option<bool>,
+    old_fee_rate: Option<u16>,
+    new_fee_rate: Option<u16>,
+    token_mint: Option<String>,
+    account: Option<String>,
+    amount: Option<u64>,
+    effective_at: Option<i64>,
+    timestamp: i64,
+}
+
+fn insert_admin_action(db: &Transaction, source: &EventSource, row: AdminAction) -> Result<usize> {
+    Ok(db.execute(
+        "INSERT OR IGNORE INTO admin_actions
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            source.signature,
+            source.event_index,
+            source.slot,
+            row.action,
+            row.admin,
+            row.paused,
+            row.old_fee_rate,
+            row.new_fee_rate,
+            row.token_mint,
+            row.account,
+            row.amount,
+            row.effective_at,
+            row.timestamp,
+        ],
+    )?)
+}
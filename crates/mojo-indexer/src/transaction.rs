@@ -0,0 +1,102 @@
+//! Fetched transactions, as returned by the `getTransaction` RPC method with
+//! `"encoding": "json"`. Only the fields the indexer reads are modelled.
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchedTransaction {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub transaction: Transaction,
+    pub meta: Option<Meta>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Transaction {
+    pub signatures: Vec<String>,
+    pub message: Message,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub account_keys: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meta {
+    pub err: Option<serde_json::Value>,
+    #[serde(default)]
+    pub log_messages: Option<Vec<String>>,
+    #[serde(default)]
+    pub inner_instructions: Option<Vec<InnerInstructions>>,
+    #[serde(default)]
+    pub loaded_addresses: Option<LoadedAddresses>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InnerInstructions {
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompiledInstruction {
+    pub program_id_index: u8,
+    /// Base58 encoded instruction data
+    pub data: String,
+}
+
+/// Accounts loaded from lookup tables by versioned transactions. They follow
+/// the static account keys, writable ones first.
+#[derive(Debug, Default, Deserialize)]
+pub struct LoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl FetchedTransaction {
+    pub fn signature(&self) -> Result<&str> {
+        self.transaction
+            .signatures
+            .first()
+            .map(String::as_str)
+            .context("transaction has no signatures")
+    }
+
+    /// Whether the transaction failed, in which case its logs describe state
+    /// changes that were rolled back.
+    pub fn failed(&self) -> bool {
+        self.meta.as_ref().is_none_or(|meta| meta.err.is_some())
+    }
+
+    /// Static account keys followed by any loaded from lookup tables, in the
+    /// order `program_id_index` refers to them.
+    pub fn account_keys(&self) -> Result<Vec<Pubkey>> {
+        let loaded = self
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.loaded_addresses.as_ref());
+
+        self.transaction
+            .message
+            .account_keys
+            .iter()
+            .chain(loaded.into_iter().flat_map(|l| &l.writable))
+            .chain(loaded.into_iter().flat_map(|l| &l.readonly))
+            .map(|key| {
+                key.parse()
+                    .with_context(|| format!("invalid account key {key}"))
+            })
+            .collect()
+    }
+}
+
+/// Reads a JSON array of fetched transactions.
+pub fn read_transactions(json: &str) -> Result<Vec<FetchedTransaction>> {
+    serde_json::from_str(json).context("expected a JSON array of getTransaction results")
+}
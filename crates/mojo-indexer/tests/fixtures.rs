@@ -0,0 +1,338 @@
+//! Indexes recorded transactions. `transactions.json` is recorded by
+//! `crates/mojo-tests/tests/indexer_fixtures.rs` from a program built with
+//! `event-cpi`, and covers every event, a two-hop route and a failed swap.
+//! The logged form of the same events is derived from it with [`as_logged`].
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use mojo_contract::DEFAULT_FEE_CHANGE_DELAY;
+use mojo_indexer::{
+    index_transactions,
+    store::Store,
+    transaction::{read_transactions, CompiledInstruction, FetchedTransaction},
+    IndexSummary,
+};
+
+const TRANSACTIONS: &str = include_str!("fixtures/transactions.json");
+
+const SUMMARY: IndexSummary = IndexSummary {
+    transactions: 14,
+    events: 14,
+    inserted: 14,
+    unknown: 0,
+};
+
+fn index(transactions: &[FetchedTransaction]) -> (Store, IndexSummary) {
+    let mut store = Store::open_in_memory().unwrap();
+    let summary = index_transactions(&mut store, transactions, &mojo_contract::ID).unwrap();
+    (store, summary)
+}
+
+fn indexed_store() -> Store {
+    let (store, summary) = index(&read_transactions(TRANSACTIONS).unwrap());
+    assert_eq!(summary, SUMMARY);
+    store
+}
+
+fn event_data(instruction: &CompiledInstruction) -> Option<Vec<u8>> {
+    let data = bs58::decode(&instruction.data).into_vec().unwrap();
+    data.strip_prefix(EVENT_IX_TAG_LE).map(<[u8]>::to_vec)
+}
+
+/// `tx` as the program without `event-cpi` logs it: each event CPI becomes
+/// a `Program data:` line written while the program runs.
+fn as_logged(mut tx: FetchedTransaction) -> FetchedTransaction {
+    let program_id = mojo_contract::ID.to_string();
+    let Some(meta) = tx.meta.as_mut() else {
+        return tx;
+    };
+
+    let mut logs = vec![format!("Program {program_id} invoke [1]")];
+    for instruction in meta.inner_instructions.take().into_iter().flatten() {
+        logs.extend(
+            instruction
+                .instructions
+                .iter()
+                .filter_map(event_data)
+                .map(|event| format!("Program data: {}", STANDARD.encode(event))),
+        );
+    }
+    logs.push(format!("Program {program_id} success"));
+    meta.log_messages = Some(logs);
+    tx
+}
+
+fn logged_transactions() -> Vec<FetchedTransaction> {
+    read_transactions(TRANSACTIONS)
+        .unwrap()
+        .into_iter()
+        .map(as_logged)
+        .collect()
+}
+
+fn logs(tx: &mut FetchedTransaction) -> &mut Vec<String> {
+    tx.meta.as_mut().unwrap().log_messages.as_mut().unwrap()
+}
+
+fn count(store: &Store, table: &str) -> i64 {
+    store
+        .connection()
+        .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+}
+
+fn liquidity_changes(store: &Store) -> Vec<(String, u64, u64, u64)> {
+    store
+        .connection()
+        .prepare(
+            "SELECT kind, base_amount, paired_amount, lp_amount
+             FROM liquidity_changes ORDER BY kind, pair",
+        )
+        .unwrap()
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn admin(store: &Store) -> String {
+    store
+        .connection()
+        .query_row(
+            "SELECT admin FROM admin_actions WHERE action = 'initialize_platform'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap()
+}
+
+#[test]
+fn indexes_every_event_kind() {
+    let store = indexed_store();
+
+    assert_eq!(count(&store, "pairs"), 2);
+    assert_eq!(count(&store, "swaps"), 2);
+    assert_eq!(count(&store, "liquidity_changes"), 3);
+    assert_eq!(count(&store, "admin_actions"), 7);
+
+    let creators: Vec<(String, u16)> = store
+        .connection()
+        .prepare("SELECT creator, lp_fee_rate FROM pairs")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(creators, vec![(admin(&store), 25); 2]);
+}
+
+#[test]
+fn keeps_both_hops_of_a_route_in_order() {
+    let store = indexed_store();
+
+    // The paired token is sold for MOJO on the first pair, then the MOJO
+    // buys the second pair's paired token
+    let hops: Vec<(i64, bool, u64, u64)> = store
+        .connection()
+        .prepare(
+            "SELECT event_index, is_base_input, amount_in, amount_out
+             FROM swaps ORDER BY event_index",
+        )
+        .unwrap()
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        hops,
+        vec![(0, false, 10_000, 4_948), (1, true, 4_948, 9_795)]
+    );
+}
+
+#[test]
+fn reads_events_from_event_cpi() {
+    let store = indexed_store();
+
+    assert_eq!(
+        liquidity_changes(&store),
+        vec![
+            ("add".to_string(), 1_000_000, 2_000_000, 1_413_213),
+            ("add".to_string(), 1_000_000, 2_000_000, 1_413_213),
+            ("remove".to_string(), 497_166, 1_004_288, 706_606),
+        ]
+    );
+}
+
+#[test]
+fn reads_the_same_events_from_logs() {
+    let (store, summary) = index(&logged_transactions());
+
+    assert_eq!(summary, SUMMARY);
+    assert_eq!(
+        liquidity_changes(&store),
+        liquidity_changes(&indexed_store())
+    );
+}
+
+#[test]
+fn normalizes_admin_actions() {
+    let store = indexed_store();
+    let admin = admin(&store);
+
+    let (withdrawer, token_mint, amount): (String, String, u64) = store
+        .connection()
+        .query_row(
+            "SELECT admin, token_mint, amount FROM admin_actions
+             WHERE action = 'withdraw_fees'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    let (base_mint, protocol_fees): (String, u64) = store
+        .connection()
+        .query_row(
+            "SELECT (SELECT base_token_mint FROM pairs LIMIT 1),
+                    (SELECT SUM(protocol_fee) FROM swaps)",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(
+        (withdrawer, token_mint, amount),
+        (admin.clone(), base_mint, protocol_fees)
+    );
+
+    let fee_change: (String, Option<u16>, Option<u16>, Option<bool>) = store
+        .connection()
+        .query_row(
+            "SELECT admin, old_fee_rate, new_fee_rate, paused FROM admin_actions
+             WHERE action = 'execute_fee_change'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .unwrap();
+    assert_eq!(fee_change, (admin, Some(30), Some(50), None));
+
+    let proposal: (Option<u16>, Option<u16>, Option<i64>) = store
+        .connection()
+        .query_row(
+            "SELECT old_fee_rate, new_fee_rate, effective_at - timestamp FROM admin_actions
+             WHERE action = 'propose_fee_change' AND new_fee_rate = 50",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(
+        proposal,
+        (Some(30), Some(50), Some(DEFAULT_FEE_CHANGE_DELAY))
+    );
+
+    let cancellation: (Option<u16>, Option<u16>) = store
+        .connection()
+        .query_row(
+            "SELECT old_fee_rate, new_fee_rate FROM admin_actions
+             WHERE action = 'cancel_fee_change'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(cancellation, (None, Some(75)));
+}
+
+#[test]
+fn reindexing_is_a_no_op() {
+    let mut store = indexed_store();
+    let transactions = read_transactions(TRANSACTIONS).unwrap();
+
+    let summary = index_transactions(&mut store, &transactions, &mojo_contract::ID).unwrap();
+    assert_eq!(summary.inserted, 0);
+    assert_eq!(count(&store, "swaps"), 2);
+}
+
+#[test]
+fn skips_unknown_events() {
+    let mut transactions = read_transactions(TRANSACTIONS).unwrap();
+    for tx in &mut transactions {
+        let program_id_index = tx
+            .account_keys()
+            .unwrap()
+            .iter()
+            .position(|key| *key == mojo_contract::ID)
+            .unwrap() as u8;
+        let unknown = [EVENT_IX_TAG_LE, &[0xff; 8], &[1, 2, 3]].concat();
+        let inner = tx
+            .meta
+            .as_mut()
+            .unwrap()
+            .inner_instructions
+            .as_mut()
+            .unwrap();
+        if let Some(last) = inner.last_mut() {
+            last.instructions.push(CompiledInstruction {
+                program_id_index,
+                data: bs58::encode(unknown).into_string(),
+            });
+        }
+    }
+
+    let (store, summary) = index(&transactions);
+    assert_eq!(
+        summary,
+        IndexSummary {
+            events: 27,
+            unknown: 13,
+            ..SUMMARY
+        }
+    );
+    assert_eq!(count(&store, "swaps"), 2);
+}
+
+#[test]
+fn ignores_data_logged_by_other_programs() {
+    let mut transactions = logged_transactions();
+    let foreign = anchor_lang::prelude::Pubkey::new_unique();
+    for tx in transactions.iter_mut().filter(|tx| !tx.failed()) {
+        let logs = logs(tx);
+        let data = logs[1].clone();
+        logs.splice(
+            1..1,
+            [
+                format!("Program {foreign} invoke [2]"),
+                data,
+                format!("Program {foreign} success"),
+            ],
+        );
+    }
+
+    assert_eq!(index(&transactions).1, SUMMARY);
+}
+
+#[test]
+fn ignores_truncated_logs_with_event_cpi() {
+    let mut transactions = read_transactions(TRANSACTIONS).unwrap();
+    for tx in &mut transactions {
+        let logs = logs(tx);
+        logs.truncate(3);
+        logs.push("Log truncated".to_string());
+    }
+
+    assert_eq!(index(&transactions).1, SUMMARY);
+}
+
+#[test]
+fn rejects_truncated_logs_without_event_cpi() {
+    let mut transactions = logged_transactions();
+    transactions.truncate(1);
+    let logs = logs(&mut transactions[0]);
+    logs.truncate(1);
+    logs.push("Log truncated".to_string());
+
+    let mut store = Store::open_in_memory().unwrap();
+    assert!(index_transactions(&mut store, &transactions, &mojo_contract::ID).is_err());
+}
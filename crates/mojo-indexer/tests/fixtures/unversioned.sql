@@ -0,0 +1,74 @@
+-- The schema mojo-indexer created before it was versioned, with one row
+-- of each admin action it knew.
+CREATE TABLE IF NOT EXISTS pairs (
+    pair              TEXT PRIMARY KEY,
+    creator           TEXT NOT NULL,
+    base_token_mint   TEXT NOT NULL,
+    paired_token_mint TEXT NOT NULL,
+    lp_mint           TEXT NOT NULL,
+    lp_fee_rate       INTEGER NOT NULL,
+    created_at        INTEGER NOT NULL,
+    signature         TEXT NOT NULL,
+    slot              INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS swaps (
+    signature      TEXT NOT NULL,
+    event_index    INTEGER NOT NULL,
+    slot           INTEGER NOT NULL,
+    pair           TEXT NOT NULL,
+    user           TEXT NOT NULL,
+    is_base_input  INTEGER NOT NULL,
+    amount_in      INTEGER NOT NULL,
+    amount_out     INTEGER NOT NULL,
+    protocol_fee   INTEGER NOT NULL,
+    base_reserve   INTEGER NOT NULL,
+    paired_reserve INTEGER NOT NULL,
+    timestamp      INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS swaps_pair ON swaps (pair, timestamp);
+
+-- kind is 'add' or 'remove'; lp_amount is minted or burned accordingly
+CREATE TABLE IF NOT EXISTS liquidity_changes (
+    signature       TEXT NOT NULL,
+    event_index     INTEGER NOT NULL,
+    slot            INTEGER NOT NULL,
+    pair            TEXT NOT NULL,
+    user            TEXT NOT NULL,
+    kind            TEXT NOT NULL CHECK (kind IN ('add', 'remove')),
+    base_amount     INTEGER NOT NULL,
+    paired_amount   INTEGER NOT NULL,
+    lp_amount       INTEGER NOT NULL,
+    base_reserve    INTEGER NOT NULL,
+    paired_reserve  INTEGER NOT NULL,
+    total_liquidity INTEGER NOT NULL,
+    timestamp       INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS liquidity_changes_pair ON liquidity_changes (pair, timestamp);
+
+-- Columns an action does not use are NULL
+CREATE TABLE IF NOT EXISTS admin_actions (
+    signature     TEXT NOT NULL,
+    event_index   INTEGER NOT NULL,
+    slot          INTEGER NOT NULL,
+    action        TEXT NOT NULL CHECK (
+        action IN ('initialize_platform', 'pause_platform', 'update_fee_rate', 'withdraw_fees')
+    ),
+    admin         TEXT NOT NULL,
+    paused        INTEGER,
+    old_fee_rate  INTEGER,
+    new_fee_rate  INTEGER,
+    token_mint    TEXT,
+    account       TEXT,
+    amount        INTEGER,
+    timestamp     INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+INSERT INTO admin_actions VALUES
+    ('sig1', 0, 100, 'initialize_platform', 'admin', NULL, NULL, 30, 'mint', 'collector', NULL, 1),
+    ('sig2', 0, 101, 'pause_platform', 'admin', 1, NULL, NULL, NULL, NULL, NULL, 2),
+    ('sig3', 0, 102, 'update_fee_rate', 'admin', NULL, 30, 50, NULL, NULL, NULL, 3),
+    ('sig4', 0, 103, 'withdraw_fees', 'admin', NULL, NULL, NULL, 'mint', 'treasury', 59, 4);
@@ -0,0 +1,90 @@
+//! Opening databases written by earlier versions of the indexer.
+//! `unversioned.sql` is the schema from before `user_version` was kept.
+
+use std::path::PathBuf;
+
+use mojo_indexer::{index_transactions, store::Store, transaction::read_transactions};
+use rusqlite::Connection;
+
+const UNVERSIONED: &str = include_str!("fixtures/unversioned.sql");
+const TRANSACTIONS: &str = include_str!("fixtures/transactions.json");
+
+/// A database file holding `sql`, removed when dropped.
+struct Database(PathBuf);
+
+impl Database {
+    fn new(name: &str, sql: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("mojo-indexer-{}-{name}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Connection::open(&path).unwrap().execute_batch(sql).unwrap();
+        Self(path)
+    }
+
+    fn open(&self) -> Store {
+        Store::open(self.0.to_str().unwrap()).unwrap()
+    }
+}
+
+impl Drop for Database {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn user_version(store: &Store) -> i64 {
+    store
+        .connection()
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn migrates_unversioned_databases() {
+    let database = Database::new("unversioned", UNVERSIONED);
+    let mut store = database.open();
+    assert_eq!(user_version(&store), 1);
+
+    let actions: Vec<(String, Option<u16>, Option<i64>)> = store
+        .connection()
+        .prepare("SELECT action, new_fee_rate, effective_at FROM admin_actions ORDER BY slot")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![
+            ("initialize_platform".to_string(), Some(30), None),
+            ("pause_platform".to_string(), None, None),
+            ("execute_fee_change".to_string(), Some(50), None),
+            ("withdraw_fees".to_string(), None, None),
+        ]
+    );
+
+    // The timelock's actions fit the rebuilt table
+    let transactions = read_transactions(TRANSACTIONS).unwrap();
+    let summary = index_transactions(&mut store, &transactions, &mojo_contract::ID).unwrap();
+    assert_eq!(summary.inserted, summary.events);
+}
+
+#[test]
+fn migrates_once() {
+    let database = Database::new("reopened", UNVERSIONED);
+    drop(database.open());
+
+    let store = database.open();
+    assert_eq!(user_version(&store), 1);
+    let rows: i64 = store
+        .connection()
+        .query_row("SELECT COUNT(*) FROM admin_actions", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(rows, 4);
+}
+
+#[test]
+fn rejects_newer_databases() {
+    let database = Database::new("newer", "PRAGMA user_version = 2;");
+    assert!(Store::open(database.0.to_str().unwrap()).is_err());
+}
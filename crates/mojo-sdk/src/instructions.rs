@@ -0,0 +1,499 @@
+//! Instruction builders, one per handler in the program.
+//!
+//! Arguments are the program's own instruction data types, so builders stay
+//! in step with the handler signatures. Mints with a Token-2022 transfer
+//! hook need the hook's extra accounts appended to `Instruction::accounts`.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, system_program, sysvar},
+    InstructionData, ToAccountMetas,
+};
+use anchor_spl::associated_token;
+use mojo_contract::{accounts, instruction, ID};
+
+use crate::pda::{self, PairKeys};
+
+/// `accounts::$name` plus, for programs built with `event-cpi`, the
+/// accounts its events are sent through.
+macro_rules! emitting {
+    ($name:ident { $($fields:tt)* }) => {
+        accounts::$name {
+            $($fields)*
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(),
+            #[cfg(feature = "event-cpi")]
+            program: ID,
+        }
+    };
+}
+
+fn build(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+pub fn initialize_platform(
+    admin: &Pubkey,
+    base_token_mint: &Pubkey,
+    token_program: &Pubkey,
+    args: instruction::InitializePlatform,
+) -> Instruction {
+    let platform_state = pda::platform_state();
+    build(
+        emitting!(InitializePlatform {
+            admin: *admin,
+            base_token_mint: *base_token_mint,
+            platform_state,
+            platform_treasury: pda::associated_token_account(
+                &platform_state,
+                base_token_mint,
+                token_program,
+            ),
+            system_program: system_program::ID,
+            associated_token_program: associated_token::ID,
+            token_program: *token_program,
+        }),
+        args,
+    )
+}
+
+pub fn pause_platform(admin: &Pubkey, args: instruction::PausePlatform) -> Instruction {
+    build(
+        emitting!(PausePlatform {
+            platform: pda::platform_state(),
+            admin: *admin,
+            system_program: system_program::ID,
+        }),
+        args,
+    )
+}
+
+pub fn resume_plaform(admin: &Pubkey) -> Instruction {
+    build(
+        accounts::ResumePlatform {
+            admin: *admin,
+            platform_state: pda::platform_state(),
+            system_program: system_program::ID,
+        },
+        instruction::ResumePlaform {},
+    )
+}
+
+/// Announces a protocol fee rate, replacing any pending change.
+pub fn propose_fee_change(admin: &Pubkey, args: instruction::ProposeFeeChange) -> Instruction {
+    build(
+        emitting!(ProposeFeeChange {
+            platform_state: pda::platform_state(),
+            pending_fee_change: pda::pending_fee_change(),
+            admin: *admin,
+            system_program: system_program::ID,
+        }),
+        args,
+    )
+}
+
+/// Applies the pending fee change once its delay has passed.
+pub fn execute_fee_change(admin: &Pubkey) -> Instruction {
+    build(
+        emitting!(ExecuteFeeChange {
+            platform_state: pda::platform_state(),
+            pending_fee_change: pda::pending_fee_change(),
+            admin: *admin,
+        }),
+        instruction::ExecuteFeeChange {},
+    )
+}
+
+pub fn cancel_fee_change(admin: &Pubkey) -> Instruction {
+    build(
+        emitting!(CancelFeeChange {
+            platform_state: pda::platform_state(),
+            pending_fee_change: pda::pending_fee_change(),
+            admin: *admin,
+        }),
+        instruction::CancelFeeChange {},
+    )
+}
+
+pub fn update_fee_change_delay(
+    admin: &Pubkey,
+    args: instruction::UpdateFeeChangeDelay,
+) -> Instruction {
+    build(
+        accounts::UpdateFeeChangeDelay {
+            platform_state: pda::platform_state(),
+            admin: *admin,
+        },
+        args,
+    )
+}
+
+pub fn update_swap_cooldown(admin: &Pubkey, args: instruction::UpdateSwapCooldown) -> Instruction {
+    build(
+        accounts::UpdateSwapCooldown {
+            platform_state: pda::platform_state(),
+            admin: *admin,
+        },
+        args,
+    )
+}
+
+pub fn update_pair_swap_cooldown(
+    pair: &Pubkey,
+    admin: &Pubkey,
+    args: instruction::UpdatePairSwapCooldown,
+) -> Instruction {
+    build(
+        accounts::UpdatePairSwapCooldown {
+            platform_state: pda::platform_state(),
+            pair: *pair,
+            admin: *admin,
+        },
+        args,
+    )
+}
+
+pub fn update_observation_interval(
+    admin: &Pubkey,
+    args: instruction::UpdateObservationInterval,
+) -> Instruction {
+    build(
+        accounts::UpdateObservationInterval {
+            platform_state: pda::platform_state(),
+            admin: *admin,
+        },
+        args,
+    )
+}
+
+/// Withdraws collected protocol fees to `fee_destination`. `fee_collector` is
+/// `PlatformState::fee_collector` and `token_mint` its mint.
+pub fn withdraw_platform_fees(
+    admin: &Pubkey,
+    fee_collector: &Pubkey,
+    token_mint: &Pubkey,
+    fee_destination: &Pubkey,
+    token_program: &Pubkey,
+    args: instruction::WithdrawPlatformFees,
+) -> Instruction {
+    build(
+        emitting!(WithdrawPlatformFees {
+            admin: *admin,
+            platform_state: pda::platform_state(),
+            fee_collector: *fee_collector,
+            token_mint: *token_mint,
+            fee_destination: *fee_destination,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        }),
+        args,
+    )
+}
+
+pub fn create_pair(
+    keys: &PairKeys,
+    creator: &Pubkey,
+    args: instruction::CreatePair,
+) -> Instruction {
+    build(
+        emitting!(CreatePair {
+            creator: *creator,
+            pair: keys.pair,
+            base_token_mint: keys.base_token_mint,
+            paired_token_mint: keys.paired_token_mint,
+            lp_mint: keys.lp_mint,
+            base_vault: keys.base_vault,
+            paired_vault: keys.paired_vault,
+            observations: keys.observations,
+            platform_state: pda::platform_state(),
+            token_program: keys.token_program,
+            paired_token_program: keys.paired_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        }),
+        args,
+    )
+}
+
+pub fn grow_observations(
+    pair: &Pubkey,
+    payer: &Pubkey,
+    args: instruction::GrowObservations,
+) -> Instruction {
+    build(
+        accounts::GrowObservations {
+            payer: *payer,
+            pair: *pair,
+            observations: pda::observations(pair),
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+/// Read-only; simulate it and decode the return data as a `SwapQuote`.
+pub fn quote_swap(keys: &PairKeys, args: instruction::QuoteSwap) -> Instruction {
+    build(
+        accounts::QuoteSwap {
+            platform_state: pda::platform_state(),
+            pair: keys.pair,
+            base_token_mint: keys.base_token_mint,
+            paired_token_mint: keys.paired_token_mint,
+        },
+        args,
+    )
+}
+
+/// Read-only; simulate it and decode the return data as a `TwapResult`.
+pub fn get_twap(pair: &Pubkey, args: instruction::GetTwap) -> Instruction {
+    build(
+        accounts::GetTwap {
+            pair: *pair,
+            observations: pda::observations(pair),
+        },
+        args,
+    )
+}
+
+fn swap_accounts(keys: &PairKeys, user: &Pubkey, fee_collector: &Pubkey) -> accounts::Swap {
+    emitting!(Swap {
+        platform_state: pda::platform_state(),
+        user: *user,
+        pair: keys.pair,
+        base_token_mint: keys.base_token_mint,
+        paired_token_mint: keys.paired_token_mint,
+        base_vault: keys.base_vault,
+        paired_vault: keys.paired_vault,
+        user_base_ata: keys.user_base_ata(user),
+        user_paired_ata: keys.user_paired_ata(user),
+        fee_collector: *fee_collector,
+        trader_state: keys.trader_state(user),
+        observations: keys.observations,
+        native_sol_account: None,
+        token_program: keys.token_program,
+        paired_token_program: keys.paired_token_program,
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+    })
+}
+
+/// `fee_collector` is `PlatformState::fee_collector`.
+pub fn swap(
+    keys: &PairKeys,
+    user: &Pubkey,
+    fee_collector: &Pubkey,
+    args: instruction::Swap,
+) -> Instruction {
+    build(swap_accounts(keys, user, fee_collector), args)
+}
+
+pub fn swap_exact_out(
+    keys: &PairKeys,
+    user: &Pubkey,
+    fee_collector: &Pubkey,
+    args: instruction::SwapExactOut,
+) -> Instruction {
+    build(swap_accounts(keys, user, fee_collector), args)
+}
+
+/// `swap` for wrapped SOL pairs, paying in or out in native SOL.
+pub fn swap_sol(
+    keys: &PairKeys,
+    user: &Pubkey,
+    fee_collector: &Pubkey,
+    args: instruction::SwapSol,
+) -> Instruction {
+    let accounts = accounts::Swap {
+        native_sol_account: Some(pda::native_sol_account(user)),
+        ..swap_accounts(keys, user, fee_collector)
+    };
+    build(accounts, args)
+}
+
+/// Swaps `pair_in`'s paired token for `pair_out`'s through MOJO.
+pub fn swap_route(
+    pair_in: &PairKeys,
+    pair_out: &PairKeys,
+    user: &Pubkey,
+    fee_collector: &Pubkey,
+    args: instruction::SwapRoute,
+) -> Instruction {
+    build(
+        emitting!(SwapRoute {
+            platform_state: pda::platform_state(),
+            user: *user,
+            pair_in: pair_in.pair,
+            pair_out: pair_out.pair,
+            base_token_mint: pair_in.base_token_mint,
+            input_token_mint: pair_in.paired_token_mint,
+            output_token_mint: pair_out.paired_token_mint,
+            pair_in_base_vault: pair_in.base_vault,
+            pair_in_paired_vault: pair_in.paired_vault,
+            pair_out_base_vault: pair_out.base_vault,
+            pair_out_paired_vault: pair_out.paired_vault,
+            user_input_ata: pair_in.user_paired_ata(user),
+            user_output_ata: pair_out.user_paired_ata(user),
+            fee_collector: *fee_collector,
+            pair_in_trader_state: pair_in.trader_state(user),
+            pair_out_trader_state: pair_out.trader_state(user),
+            pair_in_observations: pair_in.observations,
+            pair_out_observations: pair_out.observations,
+            token_program: pair_in.token_program,
+            input_token_program: pair_in.paired_token_program,
+            output_token_program: pair_out.paired_token_program,
+            system_program: system_program::ID,
+        }),
+        args,
+    )
+}
+
+fn add_liquidity_accounts(keys: &PairKeys, user: &Pubkey) -> accounts::AddLiquidity {
+    emitting!(AddLiquidity {
+        user: *user,
+        pair: keys.pair,
+        base_token_mint: keys.base_token_mint,
+        paired_token_mint: keys.paired_token_mint,
+        base_vault: keys.base_vault,
+        user_base_ata: keys.user_base_ata(user),
+        paired_vault: keys.paired_vault,
+        user_paired_ata: keys.user_paired_ata(user),
+        lp_mint: keys.lp_mint,
+        user_lp_ata: keys.user_lp_ata(user),
+        native_sol_account: None,
+        token_program: keys.token_program,
+        paired_token_program: keys.paired_token_program,
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+    })
+}
+
+pub fn add_liquidity(
+    keys: &PairKeys,
+    user: &Pubkey,
+    args: instruction::AddLiquidity,
+) -> Instruction {
+    build(add_liquidity_accounts(keys, user), args)
+}
+
+/// `add_liquidity` for wrapped SOL pairs, depositing native SOL.
+pub fn add_liquidity_sol(
+    keys: &PairKeys,
+    user: &Pubkey,
+    args: instruction::AddLiquiditySol,
+) -> Instruction {
+    let accounts = accounts::AddLiquidity {
+        native_sol_account: Some(pda::native_sol_account(user)),
+        ..add_liquidity_accounts(keys, user)
+    };
+    build(accounts, args)
+}
+
+/// `fee_collector` is `PlatformState::fee_collector`.
+pub fn add_liquidity_single_sided(
+    keys: &PairKeys,
+    user: &Pubkey,
+    fee_collector: &Pubkey,
+    args: instruction::AddLiquiditySingleSided,
+) -> Instruction {
+    build(
+        emitting!(AddLiquiditySingleSided {
+            platform_state: pda::platform_state(),
+            user: *user,
+            pair: keys.pair,
+            base_token_mint: keys.base_token_mint,
+            paired_token_mint: keys.paired_token_mint,
+            base_vault: keys.base_vault,
+            paired_vault: keys.paired_vault,
+            user_base_ata: keys.user_base_ata(user),
+            user_paired_ata: keys.user_paired_ata(user),
+            lp_mint: keys.lp_mint,
+            user_lp_ata: keys.user_lp_ata(user),
+            fee_collector: *fee_collector,
+            trader_state: keys.trader_state(user),
+            observations: keys.observations,
+            token_program: keys.token_program,
+            paired_token_program: keys.paired_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }),
+        args,
+    )
+}
+
+fn remove_liquidity_accounts(keys: &PairKeys, user: &Pubkey) -> accounts::RemoveLiquidity {
+    emitting!(RemoveLiquidity {
+        user: *user,
+        pair: keys.pair,
+        base_token_mint: keys.base_token_mint,
+        paired_token_mint: keys.paired_token_mint,
+        base_vault: keys.base_vault,
+        user_base_ata: keys.user_base_ata(user),
+        paired_vault: keys.paired_vault,
+        user_paired_ata: keys.user_paired_ata(user),
+        lp_mint: keys.lp_mint,
+        user_lp_ata: keys.user_lp_ata(user),
+        native_sol_account: None,
+        token_program: keys.token_program,
+        paired_token_program: keys.paired_token_program,
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+    })
+}
+
+pub fn remove_liquidity(
+    keys: &PairKeys,
+    user: &Pubkey,
+    args: instruction::RemoveLiquidity,
+) -> Instruction {
+    build(remove_liquidity_accounts(keys, user), args)
+}
+
+/// `remove_liquidity` for wrapped SOL pairs, paying out native SOL.
+pub fn remove_liquidity_sol(
+    keys: &PairKeys,
+    user: &Pubkey,
+    args: instruction::RemoveLiquiditySol,
+) -> Instruction {
+    let accounts = accounts::RemoveLiquidity {
+        native_sol_account: Some(pda::native_sol_account(user)),
+        ..remove_liquidity_accounts(keys, user)
+    };
+    build(accounts, args)
+}
+
+/// `fee_collector` is `PlatformState::fee_collector`.
+pub fn remove_liquidity_single_sided(
+    keys: &PairKeys,
+    user: &Pubkey,
+    fee_collector: &Pubkey,
+    args: instruction::RemoveLiquiditySingleSided,
+) -> Instruction {
+    build(
+        emitting!(RemoveLiquiditySingleSided {
+            platform_state: pda::platform_state(),
+            user: *user,
+            pair: keys.pair,
+            base_token_mint: keys.base_token_mint,
+            paired_token_mint: keys.paired_token_mint,
+            base_vault: keys.base_vault,
+            paired_vault: keys.paired_vault,
+            user_base_ata: keys.user_base_ata(user),
+            user_paired_ata: keys.user_paired_ata(user),
+            lp_mint: keys.lp_mint,
+            user_lp_ata: keys.user_lp_ata(user),
+            fee_collector: *fee_collector,
+            trader_state: keys.trader_state(user),
+            observations: keys.observations,
+            token_program: keys.token_program,
+            paired_token_program: keys.paired_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }),
+        args,
+    )
+}
@@ -0,0 +1,17 @@
+//! Rust client for `mojo_contract`.
+//!
+//! - [`pda`]: program derived addresses, pair vaults and user token accounts
+//! - [`instructions`]: a builder for every program instruction
+//! - [`state`]: account decoding
+//! - [`quote`]: off-chain quotes computed by the program's own curve code
+
+pub mod instructions;
+pub mod pda;
+pub mod quote;
+pub mod state;
+
+pub use mojo_contract::{
+    self, instruction as args, Observation, Observations, Pair, PendingFeeChange, PlatformState,
+    PriceObservation, SwapQuote, TraderState, TwapResult, ID,
+};
+pub use pda::PairKeys;
@@ -0,0 +1,123 @@
+//! Program derived addresses and the token accounts the program expects.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use mojo_contract::ID;
+
+/// The singleton platform configuration.
+pub fn platform_state() -> Pubkey {
+    Pubkey::find_program_address(&[b"platform-state"], &ID).0
+}
+
+/// The pair of `base_token_mint` (MOJO) and `paired_token_mint`.
+pub fn pair(base_token_mint: &Pubkey, paired_token_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"pair",
+            base_token_mint.as_ref(),
+            paired_token_mint.as_ref(),
+        ],
+        &ID,
+    )
+    .0
+}
+
+/// LP token mint of `pair`.
+pub fn lp_mint(pair: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"lp_mint", pair.as_ref()], &ID).0
+}
+
+/// The protocol fee rate change waiting out its delay, if any.
+pub fn pending_fee_change() -> Pubkey {
+    Pubkey::find_program_address(&[b"pending-fee-change"], &ID).0
+}
+
+/// Price sample ring buffer of `pair`.
+pub fn observations(pair: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"observations", pair.as_ref()], &ID).0
+}
+
+/// Swap cooldown state of `user` on `pair`.
+pub fn trader_state(pair: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"trader", pair.as_ref(), user.as_ref()], &ID).0
+}
+
+/// Temporary WSOL account the `*_sol` instructions unwrap `user`'s SOL
+/// through. It only exists within those instructions.
+pub fn native_sol_account(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"native-sol", user.as_ref()], &ID).0
+}
+
+/// Signer of the self-CPIs events are sent through with `event-cpi`.
+pub fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &ID).0
+}
+
+/// Associated token account of `owner` for `mint`, owned by
+/// `token_program`. Pair vaults are the pair's own ATAs.
+pub fn associated_token_account(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+/// Every address an instruction on one pair involves, other than the
+/// user's. Vaults and the LP mint follow from the mints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PairKeys {
+    pub pair: Pubkey,
+    pub base_token_mint: Pubkey,
+    pub paired_token_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub paired_vault: Pubkey,
+    pub observations: Pubkey,
+    /// Token program of the base token (MOJO) and LP mint
+    pub token_program: Pubkey,
+    /// Token program of the paired token, SPL Token or Token-2022
+    pub paired_token_program: Pubkey,
+}
+
+impl PairKeys {
+    pub fn new(
+        base_token_mint: Pubkey,
+        paired_token_mint: Pubkey,
+        token_program: Pubkey,
+        paired_token_program: Pubkey,
+    ) -> Self {
+        let pair = pair(&base_token_mint, &paired_token_mint);
+        Self {
+            pair,
+            base_token_mint,
+            paired_token_mint,
+            lp_mint: lp_mint(&pair),
+            base_vault: associated_token_account(&pair, &base_token_mint, &token_program),
+            paired_vault: associated_token_account(
+                &pair,
+                &paired_token_mint,
+                &paired_token_program,
+            ),
+            observations: observations(&pair),
+            token_program,
+            paired_token_program,
+        }
+    }
+
+    /// The user's base token (MOJO) account.
+    pub fn user_base_ata(&self, user: &Pubkey) -> Pubkey {
+        associated_token_account(user, &self.base_token_mint, &self.token_program)
+    }
+
+    /// The user's paired token account. For wrapped SOL pairs this is the
+    /// account the `*_sol` instructions wrap into.
+    pub fn user_paired_ata(&self, user: &Pubkey) -> Pubkey {
+        associated_token_account(user, &self.paired_token_mint, &self.paired_token_program)
+    }
+
+    /// The user's LP token account.
+    pub fn user_lp_ata(&self, user: &Pubkey) -> Pubkey {
+        associated_token_account(user, &self.lp_mint, &self.token_program)
+    }
+
+    pub fn trader_state(&self, user: &Pubkey) -> Pubkey {
+        trader_state(&self.pair, user)
+    }
+}
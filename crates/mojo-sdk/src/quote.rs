@@ -0,0 +1,83 @@
+//! Off-chain swap quotes.
+//!
+//! These call the same `quote_exact_in` and `quote_exact_out` the program
+//! prices every swap with, so a quote matches execution against the same
+//! account state. Only the Token-2022 transfer fee lookup differs: the
+//! program reads the epoch from the clock sysvar, here it is passed in with
+//! each mint's [`transfer_fee_config`] as [`TransferFees`].
+//!
+//! [`twap`] and [`twap_between`] average prices from any snapshot of a
+//! pair's accumulators, where `get_twap` only takes stored observations.
+
+use anchor_lang::prelude::*;
+pub use mojo_contract::transfer_fee::{transfer_fee_config, TransferFees};
+use mojo_contract::{Pair, PlatformState, PriceObservation, SwapQuote, TwapResult};
+
+/// Prices a swap of exactly `amount_in` for mints without transfer fees.
+pub fn quote_exact_in(
+    pair: &Pair,
+    platform: &PlatformState,
+    amount_in: u64,
+    is_base_input: bool,
+) -> Result<SwapQuote> {
+    mojo_contract::quote_exact_in(pair, platform.protocol_fee_rate, amount_in, is_base_input)
+}
+
+/// Prices a swap delivering exactly `amount_out`, for mints without
+/// transfer fees.
+pub fn quote_exact_out(
+    pair: &Pair,
+    platform: &PlatformState,
+    amount_out: u64,
+    is_base_input: bool,
+) -> Result<SwapQuote> {
+    mojo_contract::quote_exact_out(pair, platform.protocol_fee_rate, amount_out, is_base_input)
+}
+
+/// Average prices of `pair` from `since` to `now`, computed like `get_twap`
+/// without an end index.
+pub fn twap(pair: &Pair, since: &PriceObservation, now: i64) -> Result<TwapResult> {
+    mojo_contract::twap_since(pair, since, now)
+}
+
+/// Average prices from `start` to `end`, computed like `get_twap` between
+/// two observations.
+pub fn twap_between(start: &PriceObservation, end: &PriceObservation) -> Result<TwapResult> {
+    mojo_contract::twap_between(start, end)
+}
+
+/// `quote_exact_in` as the program runs it for Token-2022 mints with
+/// transfer fees.
+pub fn quote_exact_in_after_transfer_fees(
+    pair: &Pair,
+    platform: &PlatformState,
+    amount_in: u64,
+    is_base_input: bool,
+    fees: &TransferFees,
+) -> Result<SwapQuote> {
+    mojo_contract::quote_exact_in_with_transfer_fees(
+        pair,
+        platform.protocol_fee_rate,
+        amount_in,
+        is_base_input,
+        fees,
+    )
+}
+
+/// `quote_exact_out` as the program runs it for Token-2022 mints with
+/// transfer fees.
+pub fn quote_exact_out_after_transfer_fees(
+    pair: &Pair,
+    platform: &PlatformState,
+    amount_out: u64,
+    is_base_input: bool,
+    fees: &TransferFees,
+) -> Result<SwapQuote> {
+    mojo_contract::quote_exact_out_with_transfer_fees(
+        pair,
+        platform.protocol_fee_rate,
+        amount_out,
+        is_base_input,
+        fees,
+    )
+}
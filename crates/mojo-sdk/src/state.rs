@@ -0,0 +1,49 @@
+//! Account decoding.
+
+use anchor_lang::{error::ErrorCode, AccountDeserialize, Discriminator, Result};
+use mojo_contract::{
+    Observation, Observations, Pair, PendingFeeChange, PlatformState, TraderState,
+};
+
+/// Decodes a `Pair` account, checking its discriminator.
+pub fn pair(data: &[u8]) -> Result<Pair> {
+    Pair::try_deserialize(&mut &data[..])
+}
+
+/// Decodes the `PlatformState` account, checking its discriminator.
+pub fn platform_state(data: &[u8]) -> Result<PlatformState> {
+    PlatformState::try_deserialize(&mut &data[..])
+}
+
+/// A decoded `Observations` account.
+#[derive(Clone, Debug)]
+pub struct ObservationBuffer {
+    pub header: Observations,
+    /// The samples written so far, indexed by slot
+    pub samples: Vec<Observation>,
+}
+
+/// Decodes an `Observations` account, checking its discriminator.
+pub fn observations(data: &[u8]) -> Result<ObservationBuffer> {
+    if data.len() < Observations::space(0) {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    if !data.starts_with(Observations::DISCRIMINATOR) {
+        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    let (header, slots) = Observations::read(data);
+    let samples = (0..header.count)
+        .map(|index| header.sample(slots, index))
+        .collect::<Result<_>>()?;
+    Ok(ObservationBuffer { header, samples })
+}
+
+/// Decodes a `TraderState` account, checking its discriminator.
+pub fn trader_state(data: &[u8]) -> Result<TraderState> {
+    TraderState::try_deserialize(&mut &data[..])
+}
+
+/// Decodes the `PendingFeeChange` account, checking its discriminator.
+pub fn pending_fee_change(data: &[u8]) -> Result<PendingFeeChange> {
+    PendingFeeChange::try_deserialize(&mut &data[..])
+}
@@ -0,0 +1,162 @@
+use anchor_lang::{prelude::Pubkey, AccountSerialize, Discriminator};
+use anchor_spl::{
+    token::ID as TOKEN_PROGRAM_ID,
+    token_2022::spl_token_2022::extension::transfer_fee::{TransferFee, TransferFeeConfig},
+};
+use mojo_sdk::{
+    args, instructions, pda,
+    quote::{self, TransferFees},
+    state, Pair, PairKeys, PlatformState,
+};
+
+fn pair_state() -> Pair {
+    Pair {
+        base_token_mint: Pubkey::new_unique(),
+        paired_token_mint: Pubkey::new_unique(),
+        lp_mint: Pubkey::new_unique(),
+        base_reserve: 5_000_000_000,
+        paired_reserve: 2_000_000_000,
+        total_liquidity: 3_162_277_660,
+        bump: 255,
+        last_swap_time: 0,
+        lp_fee_rate: 25,
+        base_vault: Pubkey::new_unique(),
+        paired_vault: Pubkey::new_unique(),
+        swap_cooldown_seconds: None,
+        base_price_cumulative: 0,
+        paired_price_cumulative: 0,
+    }
+}
+
+fn platform_state() -> PlatformState {
+    PlatformState {
+        base_token_mint: Pubkey::new_unique(),
+        admin: Pubkey::new_unique(),
+        fee_collector: Pubkey::new_unique(),
+        is_paused: false,
+        protocol_fee_rate: 30,
+        bump: 254,
+        swap_cooldown_seconds: 0,
+        observation_interval_seconds: 60,
+        fee_change_delay_seconds: 86_400,
+    }
+}
+
+#[test]
+fn pair_keys_follow_the_program_seeds() {
+    let base = Pubkey::new_unique();
+    let paired = Pubkey::new_unique();
+    let keys = PairKeys::new(base, paired, TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID);
+
+    let (pair, _) =
+        Pubkey::find_program_address(&[b"pair", base.as_ref(), paired.as_ref()], &mojo_sdk::ID);
+    assert_eq!(keys.pair, pair);
+    assert_ne!(keys.pair, pda::pair(&paired, &base));
+    assert_eq!(
+        keys.base_vault,
+        pda::associated_token_account(&pair, &base, &TOKEN_PROGRAM_ID)
+    );
+    assert_eq!(keys.lp_mint, pda::lp_mint(&pair));
+}
+
+#[test]
+fn builds_swap_with_the_program_discriminator_and_accounts() {
+    let keys = PairKeys::new(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        TOKEN_PROGRAM_ID,
+        TOKEN_PROGRAM_ID,
+    );
+    let user = Pubkey::new_unique();
+    let ix = instructions::swap(
+        &keys,
+        &user,
+        &Pubkey::new_unique(),
+        args::Swap {
+            input_amount: 1_000,
+            min_output_amount: 990,
+            is_base_input: true,
+            deadline: Some(1_700_000_000),
+        },
+    );
+
+    assert_eq!(ix.program_id, mojo_sdk::ID);
+    assert!(ix.data.starts_with(args::Swap::DISCRIMINATOR));
+    let signers: Vec<_> = ix.accounts.iter().filter(|a| a.is_signer).collect();
+    assert_eq!(signers.len(), 1);
+    assert_eq!(signers[0].pubkey, user);
+    assert!(ix
+        .accounts
+        .iter()
+        .any(|a| a.pubkey == keys.trader_state(&user) && a.is_writable));
+}
+
+#[test]
+fn decodes_accounts_and_rejects_the_wrong_type() {
+    let pair = pair_state();
+    let mut data = Vec::new();
+    pair.try_serialize(&mut data).unwrap();
+
+    let decoded = state::pair(&data).unwrap();
+    assert_eq!(decoded.base_reserve, pair.base_reserve);
+    assert_eq!(decoded.lp_fee_rate, pair.lp_fee_rate);
+    assert!(state::platform_state(&data).is_err());
+}
+
+#[test]
+fn quotes_match_the_program_curve() {
+    let pair = pair_state();
+    let platform = platform_state();
+
+    for is_base_input in [true, false] {
+        let quote = quote::quote_exact_in(&pair, &platform, 12_345_678, is_base_input).unwrap();
+        let on_chain =
+            mojo_sdk::mojo_contract::quote_exact_in(&pair, 30, 12_345_678, is_base_input).unwrap();
+        assert_eq!(quote, on_chain);
+
+        let without_fees = quote::quote_exact_in_after_transfer_fees(
+            &pair,
+            &platform,
+            12_345_678,
+            is_base_input,
+            &TransferFees::default(),
+        )
+        .unwrap();
+        assert_eq!(without_fees, on_chain);
+    }
+}
+
+#[test]
+fn quotes_transfer_fees_like_the_program() {
+    let pair = pair_state();
+    let platform = platform_state();
+    let fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: u64::MAX.into(),
+        transfer_fee_basis_points: 100.into(),
+    };
+    let config = TransferFeeConfig {
+        older_transfer_fee: fee,
+        newer_transfer_fee: fee,
+        ..TransferFeeConfig::default()
+    };
+    let fees = TransferFees {
+        input: Some(&config),
+        output: None,
+        epoch: 3,
+    };
+
+    let quote =
+        quote::quote_exact_in_after_transfer_fees(&pair, &platform, 100_000, false, &fees).unwrap();
+    let net = quote::quote_exact_in(&pair, &platform, 99_000, false).unwrap();
+    assert_eq!(quote.amount_in, 100_000);
+    assert_eq!(quote.amount_out, net.amount_out);
+
+    // Exact out grosses the input up so the curve still receives its share
+    let quote =
+        quote::quote_exact_out_after_transfer_fees(&pair, &platform, 50_000, false, &fees).unwrap();
+    let net = quote::quote_exact_out(&pair, &platform, 50_000, false).unwrap();
+    assert_eq!(quote.amount_received, 50_000);
+    assert!(quote.amount_in > net.amount_in);
+    assert!(quote.amount_in - quote.amount_in / 100 >= net.amount_in);
+}
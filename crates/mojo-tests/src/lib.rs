@@ -0,0 +1,661 @@
+//! In-process SVM harness for the `mojo_contract` integration tests.
+//!
+//! Programs are loaded from the `target/deploy` builds `anchor build`
+//! produces, so the suite runs under the same heap, compute, stack and
+//! realloc limits as a validator, without needing one. Setting
+//! `MOJO_TESTS_NATIVE=1` runs them natively instead, which skips those limits
+//! and only suits debugging. SPL Token, Token-2022 and the associated token
+//! program are the builds bundled with `solana-program-test`.
+//!
+//! With the `event-cpi` feature, instructions carry the accounts event CPIs
+//! need, so `mojo_contract.so` must be built with that feature as well.
+
+use std::path::PathBuf;
+
+use anchor_lang::{
+    prelude::{AccountInfo, Pubkey},
+    solana_program::{entrypoint::ProgramResult, instruction::Instruction},
+    AccountDeserialize, AnchorDeserialize,
+};
+use anchor_spl::{
+    associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+    token::ID as TOKEN_PROGRAM_ID,
+    token_2022::spl_token_2022::{
+        self,
+        extension::{transfer_fee, transfer_hook, ExtensionType, StateWithExtensions},
+        state::{Account as TokenAccount, Mint},
+    },
+};
+use mojo_sdk::{
+    args, instructions, pda,
+    state::{self, ObservationBuffer},
+    Pair, PairKeys, PlatformState,
+};
+use serde_json::{json, Value};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    bpf_loader,
+    clock::Clock,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{CompiledInstruction, InstructionError},
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction, system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+pub use mojo_contract::error::AmmError;
+
+/// Decimals of every mint the harness creates.
+pub const DECIMALS: u8 = 6;
+
+/// Lamports each wallet starts with.
+const WALLET_LAMPORTS: u64 = 100_000_000_000;
+
+/// Compute limit of every transaction, the most a transaction may request.
+const COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+// Anchor's `entry` wants the account slice to live as long as the accounts
+// themselves, which a native processor cannot promise. Leaking a copy per
+// instruction is fine for tests.
+fn mojo_contract_entry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    mojo_contract::entry(program_id, Box::leak(Box::new(accounts.to_vec())), data)
+}
+
+fn test_transfer_hook_entry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    test_transfer_hook::entry(program_id, Box::leak(Box::new(accounts.to_vec())), data)
+}
+
+fn deploy_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target/deploy")
+}
+
+/// Whether `MOJO_TESTS_NATIVE` asks for the programs to run natively.
+fn native() -> bool {
+    std::env::var_os("MOJO_TESTS_NATIVE").is_some_and(|value| value != "0")
+}
+
+/// Adds `name` from `target/deploy`, which must have been built.
+fn add_deployed_program(test: &mut ProgramTest, name: &str, program_id: Pubkey) {
+    let path = deploy_dir().join(format!("{name}.so"));
+    let elf = std::fs::read(&path).unwrap_or_else(|e| {
+        panic!(
+            "cannot read {}: {e}; run `anchor build` first, or set MOJO_TESTS_NATIVE=1 \
+             to run the programs natively without SBF limits",
+            path.display()
+        )
+    });
+    test.add_account(
+        program_id,
+        Account {
+            lamports: Rent::default().minimum_balance(elf.len()).max(1),
+            data: elf,
+            owner: bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+}
+
+fn program_test() -> ProgramTest {
+    let mut test = ProgramTest::default();
+    if native() {
+        test.prefer_bpf(false);
+        test.add_program(
+            "mojo_contract",
+            mojo_sdk::ID,
+            processor!(mojo_contract_entry),
+        );
+        test.add_program(
+            "test_transfer_hook",
+            test_transfer_hook::ID,
+            processor!(test_transfer_hook_entry),
+        );
+    } else {
+        add_deployed_program(&mut test, "mojo_contract", mojo_sdk::ID);
+        add_deployed_program(&mut test, "test_transfer_hook", test_transfer_hook::ID);
+    }
+    test
+}
+
+/// Token-2022 extensions for [`Env::create_mint`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MintExtensions {
+    /// Transfer fee in basis points, with no maximum
+    pub transfer_fee_bps: Option<u16>,
+    /// Program run on every transfer
+    pub transfer_hook: Option<Pubkey>,
+}
+
+/// A fresh cluster with the MOJO mint, whose mint authority is the admin.
+/// The admin mints every token and is the admin of the platform once it is
+/// initialized.
+pub struct Env {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,
+    /// MOJO, an SPL Token mint
+    pub base_mint: Pubkey,
+    transactions: u32,
+}
+
+impl Env {
+    pub async fn new() -> Self {
+        let context = program_test().start_with_context().await;
+        let mut env = Self {
+            context,
+            admin: Keypair::new(),
+            base_mint: Pubkey::default(),
+            transactions: 0,
+        };
+        env.fund_wallet(&env.admin.pubkey());
+        env.base_mint = env
+            .create_mint(&TOKEN_PROGRAM_ID, MintExtensions::default())
+            .await;
+        env
+    }
+
+    /// `new` with the platform initialized at `protocol_fee_rate`.
+    pub async fn with_platform(protocol_fee_rate: u16) -> Self {
+        let mut env = Self::new().await;
+        env.initialize_platform(protocol_fee_rate).await.unwrap();
+        env
+    }
+
+    /// A copy of the admin keypair, to sign alongside `&mut self` calls.
+    pub fn admin(&self) -> Keypair {
+        self.admin.insecure_clone()
+    }
+
+    /// A new wallet holding some SOL.
+    pub fn wallet(&mut self) -> Keypair {
+        let wallet = Keypair::new();
+        self.fund_wallet(&wallet.pubkey());
+        wallet
+    }
+
+    fn fund_wallet(&mut self, address: &Pubkey) {
+        self.context.set_account(
+            address,
+            &AccountSharedData::new(WALLET_LAMPORTS, 0, &system_program::ID),
+        );
+    }
+
+    async fn transaction(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Transaction {
+        // A distinct compute limit per transaction keeps a repeated
+        // instruction from being rejected as already processed
+        self.transactions += 1;
+        let mut all = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            COMPUTE_UNIT_LIMIT - self.transactions,
+        )];
+        all.extend_from_slice(instructions);
+
+        let blockhash = self
+            .context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        let mut keypairs = vec![&self.context.payer];
+        keypairs.extend_from_slice(signers);
+        Transaction::new_signed_with_payer(
+            &all,
+            Some(&self.context.payer.pubkey()),
+            &keypairs,
+            blockhash,
+        )
+    }
+
+    /// Sends `instructions` in one transaction. Fees are paid by the context
+    /// payer, so `signers` only spend what the instructions move.
+    pub async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let transaction = self.transaction(instructions, signers).await;
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    /// Sends `instructions` like [`Env::send`] and returns the transaction as
+    /// `getTransaction` with `"encoding": "json"` would, failed or not. The
+    /// logs and inner instructions come from simulating it first.
+    pub async fn record(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Value {
+        let transaction = self.transaction(instructions, signers).await;
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction.clone())
+            .await
+            .unwrap();
+        let details = simulation.simulation_details.unwrap();
+        let error = self
+            .context
+            .banks_client
+            .process_transaction(transaction.clone())
+            .await
+            .err()
+            .map(|error| error.unwrap());
+        assert_eq!(error, simulation.result.unwrap().err());
+
+        let message = &transaction.message;
+        let compiled = |instruction: &CompiledInstruction| {
+            json!({
+                "programIdIndex": instruction.program_id_index,
+                "accounts": instruction.accounts,
+                "data": bs58::encode(&instruction.data).into_string(),
+            })
+        };
+        let inner_instructions: Vec<Value> = details
+            .inner_instructions
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .filter(|(_, inner)| !inner.is_empty())
+            .map(|(index, inner)| {
+                let instructions: Vec<Value> = inner
+                    .iter()
+                    .map(|inner| {
+                        let mut instruction = compiled(&inner.instruction);
+                        instruction["stackHeight"] = inner.stack_height.into();
+                        instruction
+                    })
+                    .collect();
+                json!({ "index": index, "instructions": instructions })
+            })
+            .collect();
+        let account_keys: Vec<String> =
+            message.account_keys.iter().map(Pubkey::to_string).collect();
+        let header = &message.header;
+        let clock = self.clock().await;
+
+        json!({
+            "slot": clock.slot,
+            "blockTime": clock.unix_timestamp,
+            "transaction": {
+                "signatures": [transaction.signatures[0].to_string()],
+                "message": {
+                    "header": {
+                        "numRequiredSignatures": header.num_required_signatures,
+                        "numReadonlySignedAccounts": header.num_readonly_signed_accounts,
+                        "numReadonlyUnsignedAccounts": header.num_readonly_unsigned_accounts,
+                    },
+                    "accountKeys": account_keys,
+                    "recentBlockhash": message.recent_blockhash.to_string(),
+                    "instructions": message.instructions.iter().map(compiled).collect::<Vec<_>>(),
+                },
+            },
+            "meta": {
+                "err": error,
+                "logMessages": details.logs,
+                "innerInstructions": inner_instructions,
+                "computeUnitsConsumed": details.units_consumed,
+            },
+        })
+    }
+
+    /// Simulates a read-only `instruction` and decodes its return data.
+    pub async fn view<T: AnchorDeserialize>(
+        &mut self,
+        instruction: Instruction,
+    ) -> Result<T, TransactionError> {
+        let transaction = self.transaction(&[instruction], &[]).await;
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        simulation.result.unwrap()?;
+
+        let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+        Ok(T::try_from_slice(&return_data.data).unwrap())
+    }
+
+    /// Creates a mint with `DECIMALS` decimals and the admin as its mint
+    /// authority.
+    pub async fn create_mint(
+        &mut self,
+        token_program: &Pubkey,
+        extensions: MintExtensions,
+    ) -> Pubkey {
+        let mint = Keypair::new();
+        let admin = self.admin.pubkey();
+        let mut extension_types = Vec::new();
+        let mut initialize = Vec::new();
+        if let Some(basis_points) = extensions.transfer_fee_bps {
+            extension_types.push(ExtensionType::TransferFeeConfig);
+            initialize.push(
+                transfer_fee::instruction::initialize_transfer_fee_config(
+                    token_program,
+                    &mint.pubkey(),
+                    Some(&admin),
+                    Some(&admin),
+                    basis_points,
+                    u64::MAX,
+                )
+                .unwrap(),
+            );
+        }
+        if let Some(program_id) = extensions.transfer_hook {
+            extension_types.push(ExtensionType::TransferHook);
+            initialize.push(
+                transfer_hook::instruction::initialize(
+                    token_program,
+                    &mint.pubkey(),
+                    Some(admin),
+                    Some(program_id),
+                )
+                .unwrap(),
+            );
+        }
+
+        let space = ExtensionType::try_calculate_account_len::<Mint>(&extension_types).unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let mut instructions = vec![system_instruction::create_account(
+            &self.context.payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            token_program,
+        )];
+        instructions.extend(initialize);
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(
+                token_program,
+                &mint.pubkey(),
+                &admin,
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+        );
+        self.send(&instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// Creates `owner`'s associated token account for `mint` if it does not
+    /// exist yet.
+    pub async fn create_ata(
+        &mut self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Pubkey {
+        let instruction = create_associated_token_account_idempotent(
+            &self.context.payer.pubkey(),
+            owner,
+            mint,
+            token_program,
+        );
+        self.send(&[instruction], &[]).await.unwrap();
+        pda::associated_token_account(owner, mint, token_program)
+    }
+
+    /// Mints `amount` into `owner`'s associated token account for `mint`.
+    pub async fn mint_to(
+        &mut self,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let account = self.create_ata(owner, mint, token_program).await;
+        let admin = self.admin();
+        let instruction = spl_token_2022::instruction::mint_to_checked(
+            token_program,
+            mint,
+            &account,
+            &admin.pubkey(),
+            &[],
+            amount,
+            DECIMALS,
+        )
+        .unwrap();
+        self.send(&[instruction], &[&admin]).await.unwrap();
+        account
+    }
+
+    pub async fn initialize_platform(
+        &mut self,
+        protocol_fee_rate: u16,
+    ) -> Result<(), BanksClientError> {
+        let admin = self.admin();
+        let instruction = instructions::initialize_platform(
+            &admin.pubkey(),
+            &self.base_mint,
+            &TOKEN_PROGRAM_ID,
+            args::InitializePlatform { protocol_fee_rate },
+        );
+        self.send(&[instruction], &[&admin]).await
+    }
+
+    /// The platform treasury, where swaps pay the protocol fee.
+    pub fn fee_collector(&self) -> Pubkey {
+        pda::associated_token_account(&pda::platform_state(), &self.base_mint, &TOKEN_PROGRAM_ID)
+    }
+
+    pub fn pair_keys(&self, paired_mint: &Pubkey, paired_token_program: &Pubkey) -> PairKeys {
+        PairKeys::new(
+            self.base_mint,
+            *paired_mint,
+            TOKEN_PROGRAM_ID,
+            *paired_token_program,
+        )
+    }
+
+    /// Creates the pair of MOJO and `paired_mint` as the admin.
+    pub async fn create_pair(
+        &mut self,
+        paired_mint: &Pubkey,
+        paired_token_program: &Pubkey,
+        lp_fee_rate: u16,
+    ) -> Result<PairKeys, BanksClientError> {
+        let keys = self.pair_keys(paired_mint, paired_token_program);
+        let admin = self.admin();
+        let instruction =
+            instructions::create_pair(&keys, &admin.pubkey(), args::CreatePair { lp_fee_rate });
+        self.send(&[instruction], &[&admin]).await?;
+        Ok(keys)
+    }
+
+    /// Creates a player token and its pair.
+    pub async fn new_pair(
+        &mut self,
+        paired_token_program: &Pubkey,
+        extensions: MintExtensions,
+        lp_fee_rate: u16,
+    ) -> PairKeys {
+        let mint = self.create_mint(paired_token_program, extensions).await;
+        self.create_pair(&mint, paired_token_program, lp_fee_rate)
+            .await
+            .unwrap()
+    }
+
+    /// Gives `user` `base` MOJO, `paired` player tokens and an LP token
+    /// account for the pair.
+    pub async fn fund(&mut self, keys: &PairKeys, user: &Pubkey, base: u64, paired: u64) {
+        self.mint_to(&keys.base_token_mint, &keys.token_program, user, base)
+            .await;
+        self.mint_to(
+            &keys.paired_token_mint,
+            &keys.paired_token_program,
+            user,
+            paired,
+        )
+        .await;
+        self.create_ata(user, &keys.lp_mint, &keys.token_program)
+            .await;
+    }
+
+    /// Deposits up to `base` and `paired` from `user`, with no minimums.
+    pub async fn add_liquidity(
+        &mut self,
+        keys: &PairKeys,
+        user: &Keypair,
+        base: u64,
+        paired: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instructions::add_liquidity(
+            keys,
+            &user.pubkey(),
+            args::AddLiquidity {
+                base_desired: base,
+                paired_desired: paired,
+                base_min: 0,
+                paired_min: 0,
+                min_lp_out: 0,
+                deadline: None,
+            },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    /// A pair whose admin-provided pool holds `base` MOJO and `paired`
+    /// player tokens.
+    pub async fn pool(&mut self, lp_fee_rate: u16, base: u64, paired: u64) -> PairKeys {
+        let keys = self
+            .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), lp_fee_rate)
+            .await;
+        let admin = self.admin();
+        self.fund(&keys, &admin.pubkey(), base, paired).await;
+        self.add_liquidity(&keys, &admin, base, paired)
+            .await
+            .unwrap();
+        keys
+    }
+
+    /// Swaps exactly `amount_in` for whatever it buys.
+    pub async fn swap(
+        &mut self,
+        keys: &PairKeys,
+        user: &Keypair,
+        amount_in: u64,
+        is_base_input: bool,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instructions::swap(
+            keys,
+            &user.pubkey(),
+            &self.fee_collector(),
+            args::Swap {
+                input_amount: amount_in,
+                min_output_amount: 0,
+                is_base_input,
+                deadline: None,
+            },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    pub async fn account_data(&mut self, address: &Pubkey) -> Option<Vec<u8>> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .map(|account| account.data)
+    }
+
+    /// Decodes a program account, checking its discriminator.
+    pub async fn account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let data = self
+            .account_data(address)
+            .await
+            .unwrap_or_else(|| panic!("no account at {address}"));
+        T::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    pub async fn pair(&mut self, keys: &PairKeys) -> Pair {
+        self.account(&keys.pair).await
+    }
+
+    pub async fn observations(&mut self, keys: &PairKeys) -> ObservationBuffer {
+        let data = self
+            .account_data(&keys.observations)
+            .await
+            .unwrap_or_else(|| panic!("no observations at {}", keys.observations));
+        state::observations(&data).unwrap()
+    }
+
+    pub async fn platform(&mut self) -> PlatformState {
+        self.account(&pda::platform_state()).await
+    }
+
+    /// Balance of an SPL Token or Token-2022 account.
+    pub async fn balance(&mut self, token_account: &Pubkey) -> u64 {
+        let data = self
+            .account_data(token_account)
+            .await
+            .unwrap_or_else(|| panic!("no token account at {token_account}"));
+        StateWithExtensions::<TokenAccount>::unpack(&data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    pub async fn supply(&mut self, mint: &Pubkey) -> u64 {
+        let data = self
+            .account_data(mint)
+            .await
+            .unwrap_or_else(|| panic!("no mint at {mint}"));
+        StateWithExtensions::<Mint>::unpack(&data)
+            .unwrap()
+            .base
+            .supply
+    }
+
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(*address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap()
+    }
+
+    /// Moves the cluster clock forward by `seconds`.
+    pub async fn warp(&mut self, seconds: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+}
+
+/// The custom program error a transaction failed with.
+#[track_caller]
+pub fn error_code(result: Result<(), BanksClientError>) -> u32 {
+    match result.expect_err("transaction should have failed").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => code,
+        error => panic!("expected a custom program error, got {error}"),
+    }
+}
+
+/// Asserts that a transaction failed with `error`, an [`AmmError`] or an
+/// Anchor `ErrorCode`.
+#[track_caller]
+pub fn assert_error(result: Result<(), BanksClientError>, error: impl Into<u32>) {
+    assert_eq!(error_code(result), error.into());
+}
@@ -0,0 +1,378 @@
+//! Stateful fuzzing of liquidity and swap sequences.
+//!
+//! Each case replays a random sequence of `add_liquidity`, `swap` in either
+//! direction and `remove_liquidity` calls from a few users against several
+//! pairs, and checks after every step that
+//!
+//! - each pair's reserves match its vault balances and its LP total matches
+//!   the LP supply plus the locked minimum,
+//! - no mint's supply grows beyond the accounts that hold it,
+//! - a swap never lowers the pool's constant product,
+//! - a deposit is never worth less than the LP it mints, and a withdrawal
+//!   never pays out more than the share of the pool the LP burned owned.
+//!
+//! Failing sequences are shrunk to a minimal reproduction and persisted in
+//! `proptest-regressions/fuzz.txt`, which is replayed on every run. The
+//! default case count keeps the suite fast; set `PROPTEST_CASES` for a
+//! longer session.
+
+use std::collections::BTreeSet;
+
+use anchor_spl::{token::ID as TOKEN_PROGRAM_ID, token_2022::ID as TOKEN_2022_PROGRAM_ID};
+use mojo_contract::MINIMUM_LIQUIDITY;
+use mojo_sdk::{args, instructions, PairKeys};
+use mojo_tests::{Env, MintExtensions};
+use proptest::{
+    prelude::*,
+    test_runner::{FileFailurePersistence, TestCaseError},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PROTOCOL_FEE_RATE: u16 = 200;
+/// Cases per run unless `PROPTEST_CASES` says otherwise
+const DEFAULT_CASES: u32 = 16;
+const USERS: usize = 2;
+/// Each user's starting balance of MOJO and of every player token, per pair
+const USER_FUNDS: u64 = 1_000_000_000;
+/// What the admin deposits into the seeded pairs
+const SEED_LIQUIDITY: u64 = 100_000_000;
+
+/// One pair under test.
+struct Pool {
+    keys: PairKeys,
+    /// The player token withholds a transfer fee, which Token-2022 keeps in
+    /// the recipient account outside its balance
+    transfer_fee: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Step {
+    AddLiquidity {
+        pool: usize,
+        user: usize,
+        base: u64,
+        paired: u64,
+    },
+    /// Burns `share_bps` of the user's LP tokens
+    RemoveLiquidity {
+        pool: usize,
+        user: usize,
+        share_bps: u16,
+    },
+    Swap {
+        pool: usize,
+        user: usize,
+        amount_in: u64,
+        is_base_input: bool,
+    },
+}
+
+/// Pool state around a step.
+struct Snapshot {
+    base_reserve: u64,
+    paired_reserve: u64,
+    total_liquidity: u64,
+    base_vault: u64,
+    paired_vault: u64,
+}
+
+impl Snapshot {
+    fn k(&self) -> u128 {
+        self.base_reserve as u128 * self.paired_reserve as u128
+    }
+}
+
+struct Harness {
+    env: Env,
+    pools: Vec<Pool>,
+    users: Vec<Keypair>,
+}
+
+impl Harness {
+    /// A fee-earning SPL Token pair and a Token-2022 pair with a transfer
+    /// fee, both seeded by the admin, and an empty pair whose first deposit
+    /// is up to the sequence.
+    async fn new() -> Self {
+        let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+        let admin = env.admin();
+        let pools = vec![
+            Pool {
+                keys: env
+                    .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), 30)
+                    .await,
+                transfer_fee: false,
+            },
+            Pool {
+                keys: env
+                    .new_pair(
+                        &TOKEN_2022_PROGRAM_ID,
+                        MintExtensions {
+                            transfer_fee_bps: Some(100),
+                            ..MintExtensions::default()
+                        },
+                        100,
+                    )
+                    .await,
+                transfer_fee: true,
+            },
+            Pool {
+                keys: env
+                    .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), 0)
+                    .await,
+                transfer_fee: false,
+            },
+        ];
+        for pool in &pools[..2] {
+            env.fund(&pool.keys, &admin.pubkey(), SEED_LIQUIDITY, SEED_LIQUIDITY)
+                .await;
+            env.add_liquidity(&pool.keys, &admin, SEED_LIQUIDITY, SEED_LIQUIDITY)
+                .await
+                .unwrap();
+        }
+
+        let users: Vec<_> = (0..USERS).map(|_| env.wallet()).collect();
+        for user in &users {
+            for pool in &pools {
+                env.fund(&pool.keys, &user.pubkey(), USER_FUNDS, USER_FUNDS)
+                    .await;
+            }
+        }
+
+        Self { env, pools, users }
+    }
+
+    async fn snapshot(&mut self, pool: usize) -> Snapshot {
+        let keys = self.pools[pool].keys;
+        let pair = self.env.pair(&keys).await;
+        Snapshot {
+            base_reserve: pair.base_reserve,
+            paired_reserve: pair.paired_reserve,
+            total_liquidity: pair.total_liquidity,
+            base_vault: self.env.balance(&keys.base_vault).await,
+            paired_vault: self.env.balance(&keys.paired_vault).await,
+        }
+    }
+
+    /// Runs `step`, which may fail, and checks what it did to its pool.
+    async fn apply(&mut self, step: &Step) -> Result<(), TestCaseError> {
+        match *step {
+            Step::AddLiquidity {
+                pool,
+                user,
+                base,
+                paired,
+            } => {
+                let keys = self.pools[pool].keys;
+                let user = self.users[user].insecure_clone();
+                let lp_account = keys.user_lp_ata(&user.pubkey());
+                let before = self.snapshot(pool).await;
+                let lp_before = self.env.balance(&lp_account).await;
+                if self
+                    .env
+                    .add_liquidity(&keys, &user, base, paired)
+                    .await
+                    .is_err()
+                {
+                    return Ok(());
+                }
+                let after = self.snapshot(pool).await;
+                let minted = (self.env.balance(&lp_account).await - lp_before) as u128;
+
+                // The new LP's share of the pool is at most what arrived
+                let total = after.total_liquidity as u128;
+                let base_in = (after.base_vault - before.base_vault) as u128;
+                let paired_in = (after.paired_vault - before.paired_vault) as u128;
+                prop_assert!(minted * after.base_reserve as u128 <= base_in * total);
+                prop_assert!(minted * after.paired_reserve as u128 <= paired_in * total);
+            }
+            Step::RemoveLiquidity {
+                pool,
+                user,
+                share_bps,
+            } => {
+                let keys = self.pools[pool].keys;
+                let user = self.users[user].insecure_clone();
+                let lp_balance = self.env.balance(&keys.user_lp_ata(&user.pubkey())).await;
+                let lp_amount = (lp_balance as u128 * share_bps as u128 / 10_000) as u64;
+                let before = self.snapshot(pool).await;
+                let instruction = instructions::remove_liquidity(
+                    &keys,
+                    &user.pubkey(),
+                    args::RemoveLiquidity {
+                        lp_amount,
+                        min_base: 0,
+                        min_paired: 0,
+                        deadline: None,
+                    },
+                );
+                if self.env.send(&[instruction], &[&user]).await.is_err() {
+                    return Ok(());
+                }
+                let after = self.snapshot(pool).await;
+
+                // Nothing beyond the burned share of the pool leaves it
+                let burned = (before.total_liquidity - after.total_liquidity) as u128;
+                prop_assert_eq!(burned, lp_amount as u128);
+                let total = before.total_liquidity as u128;
+                let base_out = (before.base_vault - after.base_vault) as u128;
+                let paired_out = (before.paired_vault - after.paired_vault) as u128;
+                prop_assert!(base_out * total <= burned * before.base_reserve as u128);
+                prop_assert!(paired_out * total <= burned * before.paired_reserve as u128);
+            }
+            Step::Swap {
+                pool,
+                user,
+                amount_in,
+                is_base_input,
+            } => {
+                let keys = self.pools[pool].keys;
+                let user = self.users[user].insecure_clone();
+                let before = self.snapshot(pool).await;
+                if self
+                    .env
+                    .swap(&keys, &user, amount_in, is_base_input)
+                    .await
+                    .is_err()
+                {
+                    return Ok(());
+                }
+                let after = self.snapshot(pool).await;
+                prop_assert!(after.k() >= before.k());
+            }
+        }
+        Ok(())
+    }
+
+    /// Invariants that hold between any two steps.
+    async fn check(&mut self) -> Result<(), TestCaseError> {
+        let admin = self.env.admin().pubkey();
+        let owners: Vec<_> = self
+            .users
+            .iter()
+            .map(Signer::pubkey)
+            .chain([admin])
+            .collect();
+        let mut base_accounts = BTreeSet::from([self.env.fee_collector()]);
+
+        for pool in 0..self.pools.len() {
+            let keys = self.pools[pool].keys;
+            let snapshot = self.snapshot(pool).await;
+            prop_assert_eq!(snapshot.base_reserve, snapshot.base_vault);
+            prop_assert_eq!(snapshot.paired_reserve, snapshot.paired_vault);
+
+            let lp_supply = self.env.supply(&keys.lp_mint).await;
+            let locked = if snapshot.total_liquidity == 0 {
+                0
+            } else {
+                MINIMUM_LIQUIDITY
+            };
+            prop_assert_eq!(snapshot.total_liquidity, lp_supply + locked);
+
+            let lp_accounts: Vec<_> = owners.iter().map(|o| keys.user_lp_ata(o)).collect();
+            let lp_held = self.held(&lp_accounts).await;
+            prop_assert_eq!(lp_held, lp_supply);
+
+            let mut paired_accounts = vec![keys.paired_vault];
+            paired_accounts.extend(owners.iter().map(|o| keys.user_paired_ata(o)));
+            let paired_held = self.held(&paired_accounts).await;
+            let paired_supply = self.env.supply(&keys.paired_token_mint).await;
+            if self.pools[pool].transfer_fee {
+                prop_assert!(paired_held <= paired_supply);
+            } else {
+                prop_assert_eq!(paired_held, paired_supply);
+            }
+
+            base_accounts.insert(keys.base_vault);
+            base_accounts.extend(owners.iter().map(|o| keys.user_base_ata(o)));
+        }
+
+        let base_accounts: Vec<_> = base_accounts.into_iter().collect();
+        let base_held = self.held(&base_accounts).await;
+        let base_mint = self.env.base_mint;
+        prop_assert_eq!(base_held, self.env.supply(&base_mint).await);
+        Ok(())
+    }
+
+    /// Total balance of the token accounts in `accounts` that exist.
+    async fn held(&mut self, accounts: &[Pubkey]) -> u64 {
+        let mut total = 0;
+        for account in accounts {
+            if self.env.account_data(account).await.is_some() {
+                total += self.env.balance(account).await;
+            }
+        }
+        total
+    }
+}
+
+async fn run(steps: Vec<Step>) -> Result<(), TestCaseError> {
+    let mut harness = Harness::new().await;
+    harness.check().await?;
+    for step in &steps {
+        harness.apply(step).await?;
+        harness.check().await?;
+    }
+    Ok(())
+}
+
+/// Amounts from dust to most of a user's funds.
+fn amount() -> impl Strategy<Value = u64> {
+    prop_oneof![1..1_000u64, 1_000..1_000_000u64, 1_000_000..=USER_FUNDS / 2,]
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    let pool = 0..3usize;
+    let user = 0..USERS;
+    prop_oneof![
+        (pool.clone(), user.clone(), amount(), amount()).prop_map(|(pool, user, base, paired)| {
+            Step::AddLiquidity {
+                pool,
+                user,
+                base,
+                paired,
+            }
+        }),
+        (pool.clone(), user.clone(), 1..=10_000u16).prop_map(|(pool, user, share_bps)| {
+            Step::RemoveLiquidity {
+                pool,
+                user,
+                share_bps,
+            }
+        }),
+        (pool, user, amount(), any::<bool>()).prop_map(|(pool, user, amount_in, is_base_input)| {
+            Step::Swap {
+                pool,
+                user,
+                amount_in,
+                is_base_input,
+            }
+        }),
+    ]
+}
+
+fn config() -> ProptestConfig {
+    let cases = std::env::var("PROPTEST_CASES")
+        .ok()
+        .and_then(|cases| cases.parse().ok())
+        .unwrap_or(DEFAULT_CASES);
+    ProptestConfig {
+        // Relative to the package, where `cargo test` runs
+        failure_persistence: Some(Box::new(FileFailurePersistence::Direct(
+            "proptest-regressions/fuzz.txt",
+        ))),
+        ..ProptestConfig::with_cases(cases)
+    }
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn liquidity_and_swap_sequences(steps in prop::collection::vec(step(), 1..24)) {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run(steps))?;
+    }
+}
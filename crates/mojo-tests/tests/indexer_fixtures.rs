@@ -0,0 +1,190 @@
+//! Records `crates/mojo-indexer/tests/fixtures/transactions.json`. It is
+//! ignored since it rewrites the fixture:
+//!
+//! ```sh
+//! cargo test -p mojo-tests --test indexer_fixtures --features event-cpi -- --ignored
+//! ```
+//!
+//! The program is built with `event-cpi` so its events are inner
+//! instructions, which the runtime records even for natively run programs.
+//! A native run does not capture the program's own log lines, though.
+#![cfg(feature = "event-cpi")]
+
+use std::path::PathBuf;
+
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use mojo_contract::DEFAULT_FEE_CHANGE_DELAY;
+use mojo_sdk::{args, instructions};
+use mojo_tests::{Env, MintExtensions};
+use serde_json::Value;
+use solana_sdk::signer::Signer;
+
+const PROTOCOL_FEE_RATE: u16 = 30;
+const LP_FEE_RATE: u16 = 25;
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../mojo-indexer/tests/fixtures/transactions.json")
+}
+
+/// One of every event, a two-hop route and a failed swap.
+async fn scenario() -> Vec<Value> {
+    let mut env = Env::new().await;
+    let admin = env.admin();
+    let mut transactions = Vec::new();
+
+    transactions.push(
+        env.record(
+            &[instructions::initialize_platform(
+                &admin.pubkey(),
+                &env.base_mint,
+                &TOKEN_PROGRAM_ID,
+                args::InitializePlatform {
+                    protocol_fee_rate: PROTOCOL_FEE_RATE,
+                },
+            )],
+            &[&admin],
+        )
+        .await,
+    );
+
+    let mut pairs = Vec::new();
+    for _ in 0..2 {
+        let mint = env
+            .create_mint(&TOKEN_PROGRAM_ID, MintExtensions::default())
+            .await;
+        let keys = env.pair_keys(&mint, &TOKEN_PROGRAM_ID);
+        transactions.push(
+            env.record(
+                &[instructions::create_pair(
+                    &keys,
+                    &admin.pubkey(),
+                    args::CreatePair {
+                        lp_fee_rate: LP_FEE_RATE,
+                    },
+                )],
+                &[&admin],
+            )
+            .await,
+        );
+
+        env.fund(&keys, &admin.pubkey(), 1_000_000, 2_000_000).await;
+        transactions.push(
+            env.record(
+                &[instructions::add_liquidity(
+                    &keys,
+                    &admin.pubkey(),
+                    args::AddLiquidity {
+                        base_desired: 1_000_000,
+                        paired_desired: 2_000_000,
+                        base_min: 0,
+                        paired_min: 0,
+                        min_lp_out: 0,
+                        deadline: None,
+                    },
+                )],
+                &[&admin],
+            )
+            .await,
+        );
+        pairs.push(keys);
+    }
+    let (pair_in, pair_out) = (&pairs[0], &pairs[1]);
+
+    let user = env.wallet();
+    env.fund(pair_in, &user.pubkey(), 0, 10_000).await;
+    env.fund(pair_out, &user.pubkey(), 0, 0).await;
+    let route = |min_amount_out| {
+        instructions::swap_route(
+            pair_in,
+            pair_out,
+            &user.pubkey(),
+            &env.fee_collector(),
+            args::SwapRoute {
+                amount_in: 10_000,
+                min_amount_out,
+                deadline: None,
+            },
+        )
+    };
+    let (route, failing_route) = (route(0), route(u64::MAX));
+    transactions.push(env.record(&[route], &[&user]).await);
+    transactions.push(env.record(&[failing_route], &[&user]).await);
+
+    let propose = |new_fee_rate| {
+        instructions::propose_fee_change(&admin.pubkey(), args::ProposeFeeChange { new_fee_rate })
+    };
+    transactions.push(env.record(&[propose(50)], &[&admin]).await);
+    transactions.push(
+        env.record(
+            &[instructions::pause_platform(
+                &admin.pubkey(),
+                args::PausePlatform { pause: true },
+            )],
+            &[&admin],
+        )
+        .await,
+    );
+    env.warp(DEFAULT_FEE_CHANGE_DELAY).await;
+    transactions.push(
+        env.record(
+            &[instructions::execute_fee_change(&admin.pubkey())],
+            &[&admin],
+        )
+        .await,
+    );
+
+    let fee_collector = env.fee_collector();
+    let fees = env.balance(&fee_collector).await;
+    let treasury = pair_in.user_base_ata(&admin.pubkey());
+    transactions.push(
+        env.record(
+            &[instructions::withdraw_platform_fees(
+                &admin.pubkey(),
+                &fee_collector,
+                &env.base_mint,
+                &treasury,
+                &TOKEN_PROGRAM_ID,
+                args::WithdrawPlatformFees { amount: fees },
+            )],
+            &[&admin],
+        )
+        .await,
+    );
+
+    let lp = env.balance(&pair_in.user_lp_ata(&admin.pubkey())).await;
+    transactions.push(
+        env.record(
+            &[instructions::remove_liquidity(
+                pair_in,
+                &admin.pubkey(),
+                args::RemoveLiquidity {
+                    lp_amount: lp / 2,
+                    min_base: 0,
+                    min_paired: 0,
+                    deadline: None,
+                },
+            )],
+            &[&admin],
+        )
+        .await,
+    );
+
+    transactions.push(env.record(&[propose(75)], &[&admin]).await);
+    transactions.push(
+        env.record(
+            &[instructions::cancel_fee_change(&admin.pubkey())],
+            &[&admin],
+        )
+        .await,
+    );
+    transactions
+}
+
+#[tokio::test]
+#[ignore = "rewrites the mojo-indexer fixtures"]
+async fn record_indexer_fixtures() {
+    let transactions = scenario().await;
+    let json = serde_json::to_string_pretty(&transactions).unwrap();
+    std::fs::write(fixture_path(), json + "\n").unwrap();
+}
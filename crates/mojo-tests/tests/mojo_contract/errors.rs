@@ -0,0 +1,596 @@
+//! One failing call per `AmmError` the program can return.
+//!
+//! `MathError`, `InvalidPairedToken`, `InvalidPauseState` and
+//! `InvalidFeeCollectorMint` are never raised. `InvariantViolated` guards
+//! state that the instructions always keep consistent, and `InvalidFeeConfig`
+//! needs fees of the whole input, which the fee rate bounds rule out, so
+//! none of them has a test.
+
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use mojo_contract::{
+    MAX_LP_FEE_RATE, MAX_OBSERVATION_CAPACITY, MAX_PROTOCOL_FEE_RATE, MIN_FEE_CHANGE_DELAY,
+};
+use mojo_sdk::{args, instructions, PairKeys};
+use mojo_tests::{assert_error, AmmError, Env, MintExtensions};
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    signature::Keypair,
+    signer::Signer,
+    transaction::TransactionError,
+};
+
+use crate::{INITIAL_LIQUIDITY, LP_FEE_RATE, PROTOCOL_FEE_RATE};
+
+/// A platform with one pool and a trader holding 100 of each token.
+async fn trading_env() -> (Env, PairKeys, Keypair) {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let trader = env.wallet();
+    env.fund(&keys, &trader.pubkey(), 100_000_000, 100_000_000)
+        .await;
+    (env, keys, trader)
+}
+
+fn swap(env: &Env, keys: &PairKeys, user: &Keypair, args: args::Swap) -> Instruction {
+    instructions::swap(keys, &user.pubkey(), &env.fee_collector(), args)
+}
+
+fn propose_fee_change(admin: &Keypair, new_fee_rate: u16) -> Instruction {
+    instructions::propose_fee_change(&admin.pubkey(), args::ProposeFeeChange { new_fee_rate })
+}
+
+/// A 1 MOJO swap with no minimum output.
+fn swap_args() -> args::Swap {
+    args::Swap {
+        input_amount: 1_000_000,
+        min_output_amount: 0,
+        is_base_input: true,
+        deadline: None,
+    }
+}
+
+fn pause(admin: &Keypair, pause: bool) -> Instruction {
+    instructions::pause_platform(&admin.pubkey(), args::PausePlatform { pause })
+}
+
+#[tokio::test]
+async fn slippage_exceeded() {
+    let (mut env, keys, trader) = trading_env().await;
+    let args = args::Swap {
+        min_output_amount: u64::MAX,
+        ..swap_args()
+    };
+    let swap = swap(&env, &keys, &trader, args);
+    assert_error(
+        env.send(&[swap], &[&trader]).await,
+        AmmError::SlippageExceeded,
+    );
+}
+
+#[tokio::test]
+async fn trading_paused() {
+    let (mut env, keys, trader) = trading_env().await;
+    let admin = env.admin();
+    env.send(&[pause(&admin, true)], &[&admin]).await.unwrap();
+
+    assert_error(
+        env.swap(&keys, &trader, 1_000_000, true).await,
+        AmmError::TradingPaused,
+    );
+    let zap = instructions::add_liquidity_single_sided(
+        &keys,
+        &trader.pubkey(),
+        &env.fee_collector(),
+        args::AddLiquiditySingleSided {
+            amount_in: 1_000_000,
+            is_base_input: true,
+            min_lp_out: 0,
+            deadline: None,
+        },
+    );
+    assert_error(env.send(&[zap], &[&trader]).await, AmmError::TradingPaused);
+}
+
+#[tokio::test]
+async fn zero_amount() {
+    let (mut env, keys, trader) = trading_env().await;
+    assert_error(
+        env.swap(&keys, &trader, 0, true).await,
+        AmmError::ZeroAmount,
+    );
+}
+
+#[tokio::test]
+async fn swap_cooldown() {
+    let (mut env, keys, trader) = trading_env().await;
+    let admin = env.admin();
+    let update = instructions::update_pair_swap_cooldown(
+        &keys.pair,
+        &admin.pubkey(),
+        args::UpdatePairSwapCooldown {
+            cooldown_seconds: Some(60),
+        },
+    );
+    env.send(&[update], &[&admin]).await.unwrap();
+
+    env.swap(&keys, &trader, 1_000_000, true).await.unwrap();
+    assert_error(
+        env.swap(&keys, &trader, 1_000_000, true).await,
+        AmmError::SwapCooldown,
+    );
+}
+
+#[tokio::test]
+async fn invalid_pair() {
+    let (mut env, keys, trader) = trading_env().await;
+    // With no trader state yet both `init_if_needed` copies of it would be
+    // created at once, failing on the account before the pairs are checked
+    env.swap(&keys, &trader, 1_000_000, true).await.unwrap();
+    let route = instructions::swap_route(
+        &keys,
+        &keys,
+        &trader.pubkey(),
+        &env.fee_collector(),
+        args::SwapRoute {
+            amount_in: 1_000_000,
+            min_amount_out: 0,
+            deadline: None,
+        },
+    );
+    assert_error(env.send(&[route], &[&trader]).await, AmmError::InvalidPair);
+}
+
+#[tokio::test]
+async fn fee_too_high() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let mint = env
+        .create_mint(&TOKEN_PROGRAM_ID, MintExtensions::default())
+        .await;
+    assert_error(
+        env.create_pair(&mint, &TOKEN_PROGRAM_ID, MAX_LP_FEE_RATE + 1)
+            .await
+            .map(drop),
+        AmmError::FeeTooHigh,
+    );
+}
+
+#[tokio::test]
+async fn protocol_fee_too_high() {
+    let mut env = Env::new().await;
+    assert_error(
+        env.initialize_platform(MAX_PROTOCOL_FEE_RATE + 1).await,
+        AmmError::ProtocolFeeTooHigh,
+    );
+
+    env.initialize_platform(PROTOCOL_FEE_RATE).await.unwrap();
+    let admin = env.admin();
+    let propose = instructions::propose_fee_change(
+        &admin.pubkey(),
+        args::ProposeFeeChange {
+            new_fee_rate: MAX_PROTOCOL_FEE_RATE + 1,
+        },
+    );
+    assert_error(
+        env.send(&[propose], &[&admin]).await,
+        AmmError::ProtocolFeeTooHigh,
+    );
+}
+
+#[tokio::test]
+async fn invalid_base_token() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let other_base = env
+        .create_mint(&TOKEN_PROGRAM_ID, MintExtensions::default())
+        .await;
+    let paired = env
+        .create_mint(&TOKEN_PROGRAM_ID, MintExtensions::default())
+        .await;
+    let keys = PairKeys::new(other_base, paired, TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID);
+
+    let admin = env.admin();
+    let create = instructions::create_pair(
+        &keys,
+        &admin.pubkey(),
+        args::CreatePair {
+            lp_fee_rate: LP_FEE_RATE,
+        },
+    );
+    assert_error(
+        env.send(&[create], &[&admin]).await,
+        AmmError::InvalidBaseToken,
+    );
+}
+
+#[tokio::test]
+async fn unauthorized() {
+    let (mut env, keys, intruder) = trading_env().await;
+    let intruder_key = intruder.pubkey();
+    let mint = env.base_mint;
+
+    // `pause_platform` checks its admin with a bare `has_one`
+    assert_error(
+        env.send(
+            &[instructions::pause_platform(
+                &intruder_key,
+                args::PausePlatform { pause: true },
+            )],
+            &[&intruder],
+        )
+        .await,
+        anchor_lang::error::ErrorCode::ConstraintHasOne,
+    );
+
+    // Executing and cancelling need a pending change to act on
+    let admin = env.admin();
+    env.send(&[propose_fee_change(&admin, 0)], &[&admin])
+        .await
+        .unwrap();
+
+    let admin_only = [
+        instructions::resume_plaform(&intruder_key),
+        instructions::propose_fee_change(&intruder_key, args::ProposeFeeChange { new_fee_rate: 0 }),
+        instructions::execute_fee_change(&intruder_key),
+        instructions::cancel_fee_change(&intruder_key),
+        instructions::update_fee_change_delay(
+            &intruder_key,
+            args::UpdateFeeChangeDelay { delay_seconds: 0 },
+        ),
+        instructions::update_swap_cooldown(
+            &intruder_key,
+            args::UpdateSwapCooldown {
+                cooldown_seconds: 0,
+            },
+        ),
+        instructions::update_pair_swap_cooldown(
+            &keys.pair,
+            &intruder_key,
+            args::UpdatePairSwapCooldown {
+                cooldown_seconds: None,
+            },
+        ),
+        instructions::update_observation_interval(
+            &intruder_key,
+            args::UpdateObservationInterval {
+                interval_seconds: 0,
+            },
+        ),
+        instructions::withdraw_platform_fees(
+            &intruder_key,
+            &env.fee_collector(),
+            &mint,
+            &keys.user_base_ata(&intruder_key),
+            &TOKEN_PROGRAM_ID,
+            args::WithdrawPlatformFees { amount: 1 },
+        ),
+    ];
+    for instruction in admin_only {
+        assert_error(
+            env.send(&[instruction], &[&intruder]).await,
+            AmmError::Unauthorized,
+        );
+    }
+}
+
+#[tokio::test]
+async fn token_mint_mismatch() {
+    let (mut env, keys, _) = trading_env().await;
+    let admin = env.admin();
+    let mint = env.base_mint;
+
+    // The destination holds the player token, not the collected MOJO
+    let withdraw = instructions::withdraw_platform_fees(
+        &admin.pubkey(),
+        &env.fee_collector(),
+        &mint,
+        &keys.user_paired_ata(&admin.pubkey()),
+        &TOKEN_PROGRAM_ID,
+        args::WithdrawPlatformFees { amount: 1 },
+    );
+    assert_error(
+        env.send(&[withdraw], &[&admin]).await,
+        AmmError::TokenMintMismatch,
+    );
+}
+
+#[tokio::test]
+async fn invalid_amount() {
+    let (mut env, keys, _) = trading_env().await;
+    let admin = env.admin();
+    let mint = env.base_mint;
+
+    let invalid = [
+        instructions::update_swap_cooldown(
+            &admin.pubkey(),
+            args::UpdateSwapCooldown {
+                cooldown_seconds: -1,
+            },
+        ),
+        instructions::update_pair_swap_cooldown(
+            &keys.pair,
+            &admin.pubkey(),
+            args::UpdatePairSwapCooldown {
+                cooldown_seconds: Some(-1),
+            },
+        ),
+        instructions::update_observation_interval(
+            &admin.pubkey(),
+            args::UpdateObservationInterval {
+                interval_seconds: -1,
+            },
+        ),
+        // Observations can only grow
+        instructions::grow_observations(
+            &keys.pair,
+            &admin.pubkey(),
+            args::GrowObservations { new_capacity: 4 },
+        ),
+        instructions::withdraw_platform_fees(
+            &admin.pubkey(),
+            &env.fee_collector(),
+            &mint,
+            &keys.user_base_ata(&admin.pubkey()),
+            &TOKEN_PROGRAM_ID,
+            args::WithdrawPlatformFees { amount: 0 },
+        ),
+        instructions::remove_liquidity(
+            &keys,
+            &admin.pubkey(),
+            args::RemoveLiquidity {
+                lp_amount: 0,
+                min_base: 0,
+                min_paired: 0,
+                deadline: None,
+            },
+        ),
+    ];
+    for instruction in invalid {
+        assert_error(
+            env.send(&[instruction], &[&admin]).await,
+            AmmError::InvalidAmount,
+        );
+    }
+}
+
+#[tokio::test]
+async fn already_paused() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let admin = env.admin();
+    env.send(&[pause(&admin, true)], &[&admin]).await.unwrap();
+    assert_error(
+        env.send(&[pause(&admin, true)], &[&admin]).await,
+        AmmError::AlreadyPaused,
+    );
+}
+
+#[tokio::test]
+async fn not_paused() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let admin = env.admin();
+    assert_error(
+        env.send(&[pause(&admin, false)], &[&admin]).await,
+        AmmError::NotPaused,
+    );
+}
+
+#[tokio::test]
+async fn insufficient_liquidity_minted() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), LP_FEE_RATE)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 1_000, 1_000).await;
+
+    // sqrt(1000 * 1000) is all locked
+    assert_error(
+        env.add_liquidity(&keys, &admin, 1_000, 1_000).await,
+        AmmError::InsufficientLiquidityMinted,
+    );
+}
+
+#[tokio::test]
+async fn insufficient_liquidity() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), LP_FEE_RATE)
+        .await;
+    let trader = env.wallet();
+    env.fund(&keys, &trader.pubkey(), 1_000_000, 0).await;
+
+    assert_error(
+        env.swap(&keys, &trader, 1_000_000, true).await,
+        AmmError::InsufficientLiquidity,
+    );
+}
+
+#[tokio::test]
+async fn math_overflow() {
+    let (mut env, keys, trader) = trading_env().await;
+
+    // The new reserve cannot be represented; pricing fails before any transfer
+    assert_error(
+        env.swap(&keys, &trader, u64::MAX, true).await,
+        AmmError::MathOverflow,
+    );
+}
+
+#[tokio::test]
+async fn excessive_input_amount() {
+    let (mut env, keys, trader) = trading_env().await;
+    let swap = instructions::swap_exact_out(
+        &keys,
+        &trader.pubkey(),
+        &env.fee_collector(),
+        args::SwapExactOut {
+            amount_out: 1_000_000,
+            max_amount_in: 1_000_000,
+            is_base_input: true,
+            deadline: None,
+        },
+    );
+    assert_error(
+        env.send(&[swap], &[&trader]).await,
+        AmmError::ExcessiveInputAmount,
+    );
+}
+
+/// Simulates `get_twap` between observation slots `start_index` and
+/// `end_index`, expecting `error`.
+async fn assert_twap_error(
+    env: &mut Env,
+    keys: &PairKeys,
+    start_index: u16,
+    end_index: Option<u16>,
+    error: AmmError,
+) {
+    let result = env
+        .view::<mojo_sdk::TwapResult>(instructions::get_twap(
+            &keys.pair,
+            args::GetTwap {
+                start_index,
+                end_index,
+            },
+        ))
+        .await;
+    let code: u32 = error.into();
+    assert!(
+        matches!(
+            result,
+            Err(TransactionError::InstructionError(_, InstructionError::Custom(c))) if c == code
+        ),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn invalid_twap_window() {
+    let (mut env, keys, trader) = trading_env().await;
+    // Sampled in the current second
+    env.send(&[swap(&env, &keys, &trader, swap_args())], &[&trader])
+        .await
+        .unwrap();
+
+    assert_twap_error(&mut env, &keys, 0, None, AmmError::InvalidTwapWindow).await;
+    assert_twap_error(&mut env, &keys, 0, Some(0), AmmError::InvalidTwapWindow).await;
+
+    // A window must run from the older sample to the newer one
+    let admin = env.admin();
+    env.send(
+        &[instructions::update_observation_interval(
+            &admin.pubkey(),
+            args::UpdateObservationInterval {
+                interval_seconds: 1,
+            },
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    env.warp(10).await;
+    env.send(&[swap(&env, &keys, &trader, swap_args())], &[&trader])
+        .await
+        .unwrap();
+    assert_twap_error(&mut env, &keys, 1, Some(0), AmmError::InvalidTwapWindow).await;
+}
+
+#[tokio::test]
+async fn observation_not_found() {
+    let (mut env, keys, trader) = trading_env().await;
+    assert_twap_error(&mut env, &keys, 0, None, AmmError::ObservationNotFound).await;
+
+    env.send(&[swap(&env, &keys, &trader, swap_args())], &[&trader])
+        .await
+        .unwrap();
+    env.warp(10).await;
+    assert_twap_error(&mut env, &keys, 1, None, AmmError::ObservationNotFound).await;
+    assert_twap_error(&mut env, &keys, 0, Some(1), AmmError::ObservationNotFound).await;
+}
+
+#[tokio::test]
+async fn not_native_sol_pair() {
+    let (mut env, keys, trader) = trading_env().await;
+    let swap = instructions::swap_sol(
+        &keys,
+        &trader.pubkey(),
+        &env.fee_collector(),
+        args::SwapSol {
+            input_amount: 1_000_000,
+            min_output_amount: 0,
+            is_base_input: true,
+            deadline: None,
+        },
+    );
+    assert_error(
+        env.send(&[swap], &[&trader]).await,
+        AmmError::NotNativeSolPair,
+    );
+}
+
+#[tokio::test]
+async fn transaction_expired() {
+    let (mut env, keys, trader) = trading_env().await;
+    let args = args::Swap {
+        deadline: Some(env.clock().await.unix_timestamp - 1),
+        ..swap_args()
+    };
+    let swap = swap(&env, &keys, &trader, args);
+    assert_error(
+        env.send(&[swap], &[&trader]).await,
+        AmmError::TransactionExpired,
+    );
+}
+
+#[tokio::test]
+async fn fee_change_not_ready() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let admin = env.admin();
+    env.send(&[propose_fee_change(&admin, 100)], &[&admin])
+        .await
+        .unwrap();
+    let delay = env.platform().await.fee_change_delay_seconds;
+
+    // One second short of the delay
+    env.warp(delay - 1).await;
+    assert_error(
+        env.send(
+            &[instructions::execute_fee_change(&admin.pubkey())],
+            &[&admin],
+        )
+        .await,
+        AmmError::FeeChangeNotReady,
+    );
+}
+
+#[tokio::test]
+async fn fee_change_delay_too_short() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let admin = env.admin();
+    let update_delay = instructions::update_fee_change_delay(
+        &admin.pubkey(),
+        args::UpdateFeeChangeDelay {
+            delay_seconds: MIN_FEE_CHANGE_DELAY - 1,
+        },
+    );
+    assert_error(
+        env.send(&[update_delay], &[&admin]).await,
+        AmmError::FeeChangeDelayTooShort,
+    );
+}
+
+#[tokio::test]
+async fn observation_capacity_too_large() {
+    let (mut env, keys, trader) = trading_env().await;
+    let grow = instructions::grow_observations(
+        &keys.pair,
+        &trader.pubkey(),
+        args::GrowObservations {
+            new_capacity: MAX_OBSERVATION_CAPACITY + 1,
+        },
+    );
+    assert_error(
+        env.send(&[grow], &[&trader]).await,
+        AmmError::ObservationCapacityTooLarge,
+    );
+}
@@ -0,0 +1,428 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::{token::ID as TOKEN_PROGRAM_ID, token_2022::spl_token_2022};
+use mojo_contract::MINIMUM_LIQUIDITY;
+use mojo_sdk::{args, instructions, PairKeys};
+use mojo_tests::{assert_error, AmmError, Env, MintExtensions, DECIMALS};
+use solana_program_test::BanksClientError;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use crate::{INITIAL_LIQUIDITY, LP_FEE_RATE, PROTOCOL_FEE_RATE};
+
+async fn add_liquidity(
+    env: &mut Env,
+    keys: &PairKeys,
+    user: &Keypair,
+    args: args::AddLiquidity,
+) -> Result<(), BanksClientError> {
+    let instruction = instructions::add_liquidity(keys, &user.pubkey(), args);
+    env.send(&[instruction], &[user]).await
+}
+
+async fn remove_liquidity(
+    env: &mut Env,
+    keys: &PairKeys,
+    user: &Keypair,
+    lp_amount: u64,
+    min_base: u64,
+    min_paired: u64,
+) -> Result<(), BanksClientError> {
+    let instruction = instructions::remove_liquidity(
+        keys,
+        &user.pubkey(),
+        args::RemoveLiquidity {
+            lp_amount,
+            min_base,
+            min_paired,
+            deadline: None,
+        },
+    );
+    env.send(&[instruction], &[user]).await
+}
+
+/// Base, paired and LP balances of `user`.
+async fn balances(env: &mut Env, keys: &PairKeys, user: &Pubkey) -> (u64, u64, u64) {
+    (
+        env.balance(&keys.user_base_ata(user)).await,
+        env.balance(&keys.user_paired_ata(user)).await,
+        env.balance(&keys.user_lp_ata(user)).await,
+    )
+}
+
+#[tokio::test]
+async fn adds_initial_liquidity() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+
+    let pair = env.pair(&keys).await;
+    assert_eq!(pair.base_reserve, INITIAL_LIQUIDITY);
+    assert_eq!(pair.paired_reserve, INITIAL_LIQUIDITY);
+    // sqrt(base * paired), of which MINIMUM_LIQUIDITY stays locked
+    assert_eq!(pair.total_liquidity, INITIAL_LIQUIDITY);
+    assert_eq!(
+        env.supply(&keys.lp_mint).await,
+        INITIAL_LIQUIDITY - MINIMUM_LIQUIDITY
+    );
+
+    let admin = env.admin.pubkey();
+    assert_eq!(
+        balances(&mut env, &keys, &admin).await,
+        (0, 0, INITIAL_LIQUIDITY - MINIMUM_LIQUIDITY)
+    );
+    assert_eq!(env.balance(&keys.base_vault).await, INITIAL_LIQUIDITY);
+    assert_eq!(env.balance(&keys.paired_vault).await, INITIAL_LIQUIDITY);
+}
+
+#[tokio::test]
+async fn adds_liquidity_in_proportion() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    let before = env.pair(&keys).await;
+
+    // Half again as much mints half again as much LP
+    let half = INITIAL_LIQUIDITY / 2;
+    env.fund(&keys, &admin.pubkey(), half, half).await;
+    env.add_liquidity(&keys, &admin, half, half).await.unwrap();
+
+    let after = env.pair(&keys).await;
+    assert_eq!(after.base_reserve, before.base_reserve + half);
+    assert_eq!(after.paired_reserve, before.paired_reserve + half);
+    assert_eq!(after.total_liquidity, before.total_liquidity + half);
+    assert_eq!(
+        env.balance(&keys.user_lp_ata(&admin.pubkey())).await,
+        INITIAL_LIQUIDITY - MINIMUM_LIQUIDITY + half
+    );
+}
+
+#[tokio::test]
+async fn deposits_only_matching_amounts() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, 2 * INITIAL_LIQUIDITY)
+        .await;
+    let pair = env.pair(&keys).await;
+
+    // 1 paired token only matches half a MOJO; the rest stays with the user
+    let user = env.wallet();
+    env.fund(&keys, &user.pubkey(), 100_000_000, 1_000_000)
+        .await;
+    env.add_liquidity(&keys, &user, 100_000_000, 1_000_000)
+        .await
+        .unwrap();
+
+    let base_taken = 1_000_000 * pair.base_reserve / pair.paired_reserve;
+    let lp = 1_000_000 * pair.total_liquidity / pair.paired_reserve;
+    assert_eq!(
+        balances(&mut env, &keys, &user.pubkey()).await,
+        (100_000_000 - base_taken, 0, lp)
+    );
+}
+
+#[tokio::test]
+async fn enforces_liquidity_bounds() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), LP_FEE_RATE)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 200_000_000, 300_000_000)
+        .await;
+
+    // Open the pool at 1 base : 2 paired
+    env.add_liquidity(&keys, &admin, 100_000_000, 200_000_000)
+        .await
+        .unwrap();
+    let before = env.pair(&keys).await;
+    let lp_share = before.total_liquidity / 10;
+    let bounds =
+        |base_desired, paired_desired, base_min, paired_min, min_lp_out| args::AddLiquidity {
+            base_desired,
+            paired_desired,
+            base_min,
+            paired_min,
+            min_lp_out,
+            deadline: None,
+        };
+
+    // 10 base needs 20 paired, below the paired minimum of 25
+    let below_paired_min = bounds(10_000_000, 50_000_000, 0, 25_000_000, 0);
+    // 20 paired needs 10 base, below the base minimum of 15
+    let below_base_min = bounds(50_000_000, 20_000_000, 15_000_000, 0, 0);
+    // Depositing 10% of the pool cannot mint more than 10% of the LP
+    let above_lp_share = bounds(10_000_000, 20_000_000, 0, 0, lp_share + 1);
+    for args in [below_paired_min, below_base_min, above_lp_share] {
+        assert_error(
+            add_liquidity(&mut env, &keys, &admin, args).await,
+            AmmError::SlippageExceeded,
+        );
+    }
+
+    // An oversized paired side is scaled down to the pool ratio
+    let (base_before, paired_before, _) = balances(&mut env, &keys, &admin.pubkey()).await;
+    let args = bounds(10_000_000, 50_000_000, 10_000_000, 20_000_000, lp_share);
+    add_liquidity(&mut env, &keys, &admin, args).await.unwrap();
+    let (base_after, paired_after, _) = balances(&mut env, &keys, &admin.pubkey()).await;
+
+    assert_eq!(base_before - base_after, 10_000_000);
+    assert_eq!(paired_before - paired_after, 20_000_000);
+    assert_eq!(
+        env.pair(&keys).await.total_liquidity - before.total_liquidity,
+        lp_share
+    );
+}
+
+#[tokio::test]
+async fn rejects_zero_liquidity() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+
+    for (base, paired) in [(0, INITIAL_LIQUIDITY), (INITIAL_LIQUIDITY, 0)] {
+        assert_error(
+            env.add_liquidity(&keys, &admin, base, paired).await,
+            AmmError::InsufficientLiquidityMinted,
+        );
+    }
+}
+
+#[tokio::test]
+async fn rejects_unfunded_liquidity() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+
+    let user = env.wallet();
+    env.fund(&keys, &user.pubkey(), 0, 0).await;
+    assert!(env
+        .add_liquidity(&keys, &user, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await
+        .is_err());
+    assert_eq!(env.pair(&keys).await.base_reserve, INITIAL_LIQUIDITY);
+}
+
+#[tokio::test]
+async fn locks_minimum_liquidity_against_inflation() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), LP_FEE_RATE)
+        .await;
+
+    // The attacker opens the pool; a dust-sized deposit mints no LP
+    let attacker = env.wallet();
+    let donation = 100_000_000;
+    let opening = MINIMUM_LIQUIDITY + 1;
+    env.fund(
+        &keys,
+        &attacker.pubkey(),
+        opening + donation,
+        opening + donation,
+    )
+    .await;
+    assert_error(
+        env.add_liquidity(&keys, &attacker, 1, 1).await,
+        AmmError::InsufficientLiquidityMinted,
+    );
+
+    // The cheapest possible first deposit buys a single LP token
+    env.add_liquidity(&keys, &attacker, opening, opening)
+        .await
+        .unwrap();
+    let attacker_lp = keys.user_lp_ata(&attacker.pubkey());
+    assert_eq!(env.balance(&attacker_lp).await, 1);
+
+    // Donate straight to the vaults to try to inflate the LP share price
+    let donate = |from, mint, to| {
+        spl_token_2022::instruction::transfer_checked(
+            &TOKEN_PROGRAM_ID,
+            &from,
+            &mint,
+            &to,
+            &attacker.pubkey(),
+            &[],
+            donation,
+            DECIMALS,
+        )
+        .unwrap()
+    };
+    let donations = [
+        donate(
+            keys.user_base_ata(&attacker.pubkey()),
+            keys.base_token_mint,
+            keys.base_vault,
+        ),
+        donate(
+            keys.user_paired_ata(&attacker.pubkey()),
+            keys.paired_token_mint,
+            keys.paired_vault,
+        ),
+    ];
+    env.send(&donations, &[&attacker]).await.unwrap();
+
+    // The victim's deposit is not diluted by the donation
+    let victim = env.wallet();
+    let deposit = 1_000_000;
+    env.fund(&keys, &victim.pubkey(), deposit, deposit).await;
+    env.add_liquidity(&keys, &victim, deposit, deposit)
+        .await
+        .unwrap();
+    let victim_lp = env.balance(&keys.user_lp_ata(&victim.pubkey())).await;
+    assert_eq!(victim_lp, deposit);
+
+    // Both exit, and the victim gets their deposit back less rounding
+    remove_liquidity(&mut env, &keys, &attacker, 1, 0, 0)
+        .await
+        .unwrap();
+    remove_liquidity(&mut env, &keys, &victim, victim_lp, 0, 0)
+        .await
+        .unwrap();
+    let (base, paired, _) = balances(&mut env, &keys, &victim.pubkey()).await;
+    assert!(base >= deposit - 1);
+    assert!(paired >= deposit - 1);
+
+    // The locked liquidity keeps the pool alive
+    let pair = env.pair(&keys).await;
+    assert_eq!(pair.total_liquidity, MINIMUM_LIQUIDITY);
+    assert!(pair.base_reserve > 0 && pair.paired_reserve > 0);
+}
+
+#[tokio::test]
+async fn removes_liquidity() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, 2 * INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    let before = env.pair(&keys).await;
+
+    let lp_amount = before.total_liquidity / 4;
+    let base_out = lp_amount * before.base_reserve / before.total_liquidity;
+    let paired_out = lp_amount * before.paired_reserve / before.total_liquidity;
+
+    for (min_base, min_paired) in [(base_out + 1, 0), (0, paired_out + 1)] {
+        assert_error(
+            remove_liquidity(&mut env, &keys, &admin, lp_amount, min_base, min_paired).await,
+            AmmError::SlippageExceeded,
+        );
+    }
+    assert_error(
+        remove_liquidity(&mut env, &keys, &admin, 0, 0, 0).await,
+        AmmError::InvalidAmount,
+    );
+
+    let (_, _, lp_before) = balances(&mut env, &keys, &admin.pubkey()).await;
+    remove_liquidity(&mut env, &keys, &admin, lp_amount, base_out, paired_out)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        balances(&mut env, &keys, &admin.pubkey()).await,
+        (base_out, paired_out, lp_before - lp_amount)
+    );
+    let after = env.pair(&keys).await;
+    assert_eq!(after.base_reserve, before.base_reserve - base_out);
+    assert_eq!(after.paired_reserve, before.paired_reserve - paired_out);
+    assert_eq!(after.total_liquidity, before.total_liquidity - lp_amount);
+    assert_eq!(env.balance(&keys.base_vault).await, after.base_reserve);
+    assert_eq!(env.balance(&keys.paired_vault).await, after.paired_reserve);
+}
+
+#[tokio::test]
+async fn zaps_single_token_in() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env.pool(LP_FEE_RATE, 100_000_000, 100_000_000).await;
+    let admin = env.admin();
+    let zap_in = 20_000_000;
+    env.fund(&keys, &admin.pubkey(), zap_in, zap_in).await;
+    let fee_collector = env.fee_collector();
+    let zap = |is_base_input, min_lp_out| {
+        instructions::add_liquidity_single_sided(
+            &keys,
+            &admin.pubkey(),
+            &fee_collector,
+            args::AddLiquiditySingleSided {
+                amount_in: zap_in,
+                is_base_input,
+                min_lp_out,
+                deadline: None,
+            },
+        )
+    };
+
+    // 20 MOJO into a 100/100 pool can never be worth 10% of the LP
+    assert_error(
+        env.send(&[zap(true, 10_000_000)], &[&admin]).await,
+        AmmError::SlippageExceeded,
+    );
+
+    for is_base_input in [true, false] {
+        let before = balances(&mut env, &keys, &admin.pubkey()).await;
+        env.send(&[zap(is_base_input, 1)], &[&admin]).await.unwrap();
+        let after = balances(&mut env, &keys, &admin.pubkey()).await;
+
+        let (spent, left_over) = if is_base_input {
+            (before.0 - after.0, after.1 - before.1)
+        } else {
+            (before.1 - after.1, after.0 - before.0)
+        };
+        assert!(after.2 > before.2);
+        // Nearly all of the input is deposited, leaving dust of the other side
+        assert!(spent <= zap_in && spent >= zap_in - 5, "spent {spent}");
+        assert!(left_over <= 5, "left over {left_over}");
+    }
+}
+
+#[tokio::test]
+async fn zaps_liquidity_out_to_single_token() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env.pool(LP_FEE_RATE, 100_000_000, 100_000_000).await;
+    let admin = env.admin();
+    let pair = env.pair(&keys).await;
+    let lp_amount = pair.total_liquidity / 4;
+    // Each side of a quarter of the pool
+    let share = pair.base_reserve * lp_amount / pair.total_liquidity;
+    let fee_collector = env.fee_collector();
+    let zap = |want_base, min_out| {
+        instructions::remove_liquidity_single_sided(
+            &keys,
+            &admin.pubkey(),
+            &fee_collector,
+            args::RemoveLiquiditySingleSided {
+                lp_amount,
+                want_base,
+                min_out,
+                deadline: None,
+            },
+        )
+    };
+
+    // Swapping the unwanted side back costs price impact and fees, so the
+    // result is always worth less than both sides at the pool price
+    assert_error(
+        env.send(&[zap(true, 2 * share)], &[&admin]).await,
+        AmmError::SlippageExceeded,
+    );
+
+    for want_base in [true, false] {
+        let before = balances(&mut env, &keys, &admin.pubkey()).await;
+        env.send(&[zap(want_base, share)], &[&admin]).await.unwrap();
+        let after = balances(&mut env, &keys, &admin.pubkey()).await;
+
+        let (wanted, unwanted) = if want_base {
+            (after.0 - before.0, after.1 - before.1)
+        } else {
+            (after.1 - before.1, after.0 - before.0)
+        };
+        assert_eq!(before.2 - after.2, lp_amount);
+        assert!(wanted > share, "got {wanted}");
+        assert_eq!(unwanted, 0);
+    }
+}
@@ -0,0 +1,17 @@
+//! The scenarios of `tests/mojo_contract.ts`, plus coverage of every
+//! instruction and of every `AmmError` the program can return.
+
+mod errors;
+mod liquidity;
+mod oracle;
+mod pairs;
+mod platform;
+mod swaps;
+mod tokens;
+
+/// 2%, the highest rate allowed, as in the TypeScript suite
+pub const PROTOCOL_FEE_RATE: u16 = 200;
+/// 0.3%, kept in the pool for LPs
+pub const LP_FEE_RATE: u16 = 30;
+/// 1000 tokens of 6 decimals on each side of a pool
+pub const INITIAL_LIQUIDITY: u64 = 1_000_000_000;
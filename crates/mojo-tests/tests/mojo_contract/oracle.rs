@@ -0,0 +1,234 @@
+use anchor_lang::Space;
+use mojo_contract::MAX_OBSERVATION_CAPACITY;
+use mojo_sdk::{args, instructions, quote, Observation, PriceObservation, TwapResult};
+use mojo_tests::Env;
+use solana_sdk::signer::Signer;
+
+use crate::{INITIAL_LIQUIDITY, LP_FEE_RATE, PROTOCOL_FEE_RATE};
+
+#[tokio::test]
+async fn reports_twap() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, 2 * INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 1_000_000, 0).await;
+
+    // Let some time pass at the opening price, then trade
+    let before = env.pair(&keys).await;
+    env.warp(100).await;
+    env.swap(&keys, &admin, 1_000_000, true).await.unwrap();
+    let pair = env.pair(&keys).await;
+    assert!(pair.base_price_cumulative > before.base_price_cumulative);
+    assert!(pair.paired_price_cumulative > before.paired_price_cumulative);
+
+    // The swap was sampled; the pool then sits at its new price for the
+    // whole window
+    let observations = env.observations(&keys).await;
+    let sample = observations.samples[observations.header.index as usize];
+    assert_eq!(sample.timestamp, pair.last_swap_time);
+    env.warp(300).await;
+    let twap: TwapResult = env
+        .view(instructions::get_twap(
+            &keys.pair,
+            args::GetTwap {
+                start_index: observations.header.index,
+                end_index: None,
+            },
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(twap.window_seconds, 300);
+    assert_eq!(
+        twap.base_price_x64,
+        ((pair.paired_reserve as u128) << 64) / pair.base_reserve as u128
+    );
+    assert_eq!(
+        twap.paired_price_x64,
+        ((pair.base_reserve as u128) << 64) / pair.paired_reserve as u128
+    );
+
+    // Off chain the window can start from any snapshot
+    let now = env.clock().await.unix_timestamp;
+    let since = PriceObservation::from(&sample);
+    assert_eq!(quote::twap(&pair, &since, now).unwrap(), twap);
+}
+
+#[tokio::test]
+async fn reports_twap_between_observations() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, 2 * INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 10_000_000, 0).await;
+    let interval = env.platform().await.observation_interval_seconds;
+
+    // Three samples, with the price moving between each
+    for _ in 0..3 {
+        env.warp(interval).await;
+        env.swap(&keys, &admin, 1_000_000, true).await.unwrap();
+    }
+    let observations = env.observations(&keys).await;
+    assert_eq!(observations.header.count, 3);
+    let [first, second, third] =
+        [0, 1, 2].map(|i| PriceObservation::from(&observations.samples[i]));
+
+    // A window between two samples ignores what happened after it
+    env.warp(1_000).await;
+    let twap: TwapResult = env
+        .view(instructions::get_twap(
+            &keys.pair,
+            args::GetTwap {
+                start_index: 0,
+                end_index: Some(1),
+            },
+        ))
+        .await
+        .unwrap();
+    assert_eq!(twap.window_seconds, interval);
+    assert_eq!(twap, quote::twap_between(&first, &second).unwrap());
+    assert_ne!(twap, quote::twap_between(&first, &third).unwrap());
+}
+
+#[tokio::test]
+async fn samples_prices_into_observations() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 10_000_000, 10_000_000)
+        .await;
+
+    // Two quick swaps fall inside the default interval: one sample
+    env.swap(&keys, &admin, 1_000_000, true).await.unwrap();
+    env.swap(&keys, &admin, 1_000_000, false).await.unwrap();
+    let observations = env.observations(&keys).await;
+    assert_eq!(observations.header.count, 1);
+
+    // Anyone can pay to enlarge the buffer
+    let payer = env.wallet();
+    env.send(
+        &[instructions::grow_observations(
+            &keys.pair,
+            &payer.pubkey(),
+            args::GrowObservations { new_capacity: 16 },
+        )],
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    let observations = env.observations(&keys).await;
+    assert_eq!(observations.header.capacity, 16);
+    assert_eq!(observations.header.count, 1);
+
+    // With a one second interval the next swap is sampled again
+    env.send(
+        &[instructions::update_observation_interval(
+            &admin.pubkey(),
+            args::UpdateObservationInterval {
+                interval_seconds: 1,
+            },
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    env.warp(2).await;
+    env.swap(&keys, &admin, 1_000_000, true).await.unwrap();
+
+    let observations = env.observations(&keys).await;
+    assert_eq!(observations.header.count, 2);
+    let latest = observations.samples[observations.header.index as usize];
+    let pair = env.pair(&keys).await;
+    assert_eq!(latest.timestamp, pair.last_swap_time);
+    assert_eq!(latest.base_price_cumulative, pair.base_price_cumulative);
+    assert_eq!(latest.paired_price_cumulative, pair.paired_price_cumulative);
+    assert_eq!(latest.liquidity, pair.total_liquidity);
+}
+
+#[tokio::test]
+async fn wraps_observation_buffer() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 10_000_000, 0).await;
+
+    let observations = env.observations(&keys).await;
+    let capacity = observations.header.capacity as usize;
+    let interval = env.platform().await.observation_interval_seconds;
+
+    // One more sample than fits overwrites the oldest
+    for _ in 0..=capacity {
+        env.warp(interval).await;
+        env.swap(&keys, &admin, 1_000_000, true).await.unwrap();
+    }
+
+    let observations = env.observations(&keys).await;
+    assert_eq!(observations.header.count as usize, capacity);
+    assert_eq!(observations.header.index, 0);
+    let pair = env.pair(&keys).await;
+    assert_eq!(observations.samples[0].timestamp, pair.last_swap_time);
+    let oldest = observations.samples[1].timestamp;
+    assert_eq!(
+        pair.last_swap_time - oldest,
+        interval * (capacity as i64 - 1)
+    );
+}
+
+#[tokio::test]
+async fn trades_against_full_size_observation_buffer() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 10_000_000, 10_000_000)
+        .await;
+
+    // Each call may grow an account by at most 10 KiB
+    let step = (10 * 1024 / Observation::INIT_SPACE) as u16;
+    let payer = env.wallet();
+    let mut capacity = env.observations(&keys).await.header.capacity;
+    while capacity < MAX_OBSERVATION_CAPACITY {
+        capacity = (capacity + step).min(MAX_OBSERVATION_CAPACITY);
+        env.send(
+            &[instructions::grow_observations(
+                &keys.pair,
+                &payer.pubkey(),
+                args::GrowObservations {
+                    new_capacity: capacity,
+                },
+            )],
+            &[&payer],
+        )
+        .await
+        .unwrap();
+    }
+
+    // Swaps only touch the slot they write, however large the buffer
+    let interval = env.platform().await.observation_interval_seconds;
+    for is_base_input in [true, false] {
+        env.warp(interval).await;
+        env.swap(&keys, &admin, 1_000_000, is_base_input)
+            .await
+            .unwrap();
+    }
+    let observations = env.observations(&keys).await;
+    assert_eq!(observations.header.capacity, MAX_OBSERVATION_CAPACITY);
+    assert_eq!(observations.header.count, 2);
+    env.view::<TwapResult>(instructions::get_twap(
+        &keys.pair,
+        args::GetTwap {
+            start_index: 0,
+            end_index: Some(1),
+        },
+    ))
+    .await
+    .unwrap();
+}
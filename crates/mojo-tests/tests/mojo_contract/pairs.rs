@@ -0,0 +1,107 @@
+use anchor_spl::{
+    token::ID as TOKEN_PROGRAM_ID,
+    token_2022::spl_token_2022::{extension::StateWithExtensions, state::Mint},
+};
+use mojo_contract::DEFAULT_OBSERVATION_CAPACITY;
+use mojo_sdk::{args, instructions, pda};
+use mojo_tests::{assert_error, AmmError, Env, MintExtensions};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use crate::{LP_FEE_RATE, PROTOCOL_FEE_RATE};
+
+#[tokio::test]
+async fn creates_pair() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), LP_FEE_RATE)
+        .await;
+
+    let pair = env.pair(&keys).await;
+    assert_eq!(
+        keys.pair,
+        pda::pair(&env.base_mint, &keys.paired_token_mint)
+    );
+    assert_eq!(pair.base_token_mint, env.base_mint);
+    assert_eq!(pair.paired_token_mint, keys.paired_token_mint);
+    assert_eq!(pair.lp_mint, keys.lp_mint);
+    assert_eq!(pair.base_vault, keys.base_vault);
+    assert_eq!(pair.paired_vault, keys.paired_vault);
+    assert_eq!(pair.lp_fee_rate, LP_FEE_RATE);
+    assert_eq!(pair.base_reserve, 0);
+    assert_eq!(pair.paired_reserve, 0);
+    assert_eq!(pair.total_liquidity, 0);
+    assert_eq!(pair.swap_cooldown_seconds, None);
+    assert_eq!(env.balance(&keys.base_vault).await, 0);
+    assert_eq!(env.balance(&keys.paired_vault).await, 0);
+
+    // The pair mints its LP token
+    let data = env.account_data(&keys.lp_mint).await.unwrap();
+    let lp_mint = StateWithExtensions::<Mint>::unpack(&data).unwrap().base;
+    assert_eq!(lp_mint.decimals, 9);
+    assert_eq!(lp_mint.mint_authority, Some(keys.pair).into());
+    assert_eq!(lp_mint.supply, 0);
+
+    let observations = env.observations(&keys).await;
+    assert_eq!(observations.header.pair, keys.pair);
+    assert_eq!(observations.header.count, 0);
+    assert_eq!(observations.header.capacity, DEFAULT_OBSERVATION_CAPACITY);
+}
+
+#[tokio::test]
+async fn creates_each_pair_once() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), LP_FEE_RATE)
+        .await;
+
+    assert!(env
+        .create_pair(&keys.paired_token_mint, &TOKEN_PROGRAM_ID, LP_FEE_RATE)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn creates_multiple_pairs() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let first = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), LP_FEE_RATE)
+        .await;
+    let second = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), 100)
+        .await;
+
+    assert_ne!(first.pair, second.pair);
+    assert_ne!(first.lp_mint, second.lp_mint);
+    assert_eq!(env.pair(&first).await.lp_fee_rate, LP_FEE_RATE);
+    assert_eq!(env.pair(&second).await.lp_fee_rate, 100);
+}
+
+#[tokio::test]
+async fn updates_pair_swap_cooldown() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), LP_FEE_RATE)
+        .await;
+    let admin = env.admin();
+    let update = |signer: &Keypair, cooldown_seconds| {
+        instructions::update_pair_swap_cooldown(
+            &keys.pair,
+            &signer.pubkey(),
+            args::UpdatePairSwapCooldown { cooldown_seconds },
+        )
+    };
+
+    env.send(&[update(&admin, Some(30))], &[&admin])
+        .await
+        .unwrap();
+    assert_eq!(env.pair(&keys).await.swap_cooldown_seconds, Some(30));
+
+    env.send(&[update(&admin, None)], &[&admin]).await.unwrap();
+    assert_eq!(env.pair(&keys).await.swap_cooldown_seconds, None);
+
+    let intruder = env.wallet();
+    assert_error(
+        env.send(&[update(&intruder, Some(0))], &[&intruder]).await,
+        AmmError::Unauthorized,
+    );
+}
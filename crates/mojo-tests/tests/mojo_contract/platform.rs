@@ -0,0 +1,342 @@
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use mojo_contract::{
+    DEFAULT_FEE_CHANGE_DELAY, DEFAULT_OBSERVATION_INTERVAL, MAX_PROTOCOL_FEE_RATE,
+    MIN_FEE_CHANGE_DELAY,
+};
+use mojo_sdk::{args, instructions, pda, quote, PendingFeeChange};
+use mojo_tests::{assert_error, AmmError, Env};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use crate::{INITIAL_LIQUIDITY, LP_FEE_RATE, PROTOCOL_FEE_RATE};
+
+fn propose_fee_change(admin: &Keypair, new_fee_rate: u16) -> Instruction {
+    instructions::propose_fee_change(&admin.pubkey(), args::ProposeFeeChange { new_fee_rate })
+}
+
+#[tokio::test]
+async fn initializes_platform() {
+    let mut env = Env::new().await;
+    env.initialize_platform(PROTOCOL_FEE_RATE).await.unwrap();
+
+    let platform = env.platform().await;
+    assert_eq!(platform.admin, env.admin.pubkey());
+    assert_eq!(platform.base_token_mint, env.base_mint);
+    assert_eq!(platform.fee_collector, env.fee_collector());
+    assert!(!platform.is_paused);
+    assert_eq!(platform.protocol_fee_rate, PROTOCOL_FEE_RATE);
+    assert_eq!(platform.swap_cooldown_seconds, 0);
+    assert_eq!(
+        platform.observation_interval_seconds,
+        DEFAULT_OBSERVATION_INTERVAL
+    );
+
+    // The treasury is the platform state's MOJO account
+    let fee_collector = env.fee_collector();
+    assert_eq!(env.balance(&fee_collector).await, 0);
+}
+
+#[tokio::test]
+async fn initializes_platform_once() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+
+    let intruder = env.wallet();
+    let instruction = instructions::initialize_platform(
+        &intruder.pubkey(),
+        &env.base_mint,
+        &TOKEN_PROGRAM_ID,
+        args::InitializePlatform {
+            protocol_fee_rate: 0,
+        },
+    );
+    assert!(env.send(&[instruction], &[&intruder]).await.is_err());
+
+    let platform = env.platform().await;
+    assert_eq!(platform.admin, env.admin.pubkey());
+    assert_eq!(platform.protocol_fee_rate, PROTOCOL_FEE_RATE);
+}
+
+#[tokio::test]
+async fn pauses_and_unpauses_trading() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 10_000_000, 0).await;
+
+    let pause =
+        |pause| instructions::pause_platform(&admin.pubkey(), args::PausePlatform { pause });
+    env.send(&[pause(true)], &[&admin]).await.unwrap();
+    assert!(env.platform().await.is_paused);
+    assert_error(
+        env.swap(&keys, &admin, 1_000_000, true).await,
+        AmmError::TradingPaused,
+    );
+
+    env.send(&[pause(false)], &[&admin]).await.unwrap();
+    assert!(!env.platform().await.is_paused);
+    env.swap(&keys, &admin, 1_000_000, true).await.unwrap();
+}
+
+#[tokio::test]
+async fn resume_plaform_is_admin_only() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+
+    let intruder = env.wallet();
+    assert_error(
+        env.send(
+            &[instructions::resume_plaform(&intruder.pubkey())],
+            &[&intruder],
+        )
+        .await,
+        AmmError::Unauthorized,
+    );
+
+    // `resume_plaform` sets `is_paused` instead of clearing it; unpausing
+    // goes through `pause_platform`
+    let admin = env.admin();
+    env.send(&[instructions::resume_plaform(&admin.pubkey())], &[&admin])
+        .await
+        .unwrap();
+    assert!(env.platform().await.is_paused);
+}
+
+#[tokio::test]
+async fn changes_fee_rate_after_delay() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 10_000_000, 0).await;
+
+    env.send(&[propose_fee_change(&admin, 100)], &[&admin])
+        .await
+        .unwrap();
+    let now = env.clock().await.unix_timestamp;
+    let pending: PendingFeeChange = env.account(&pda::pending_fee_change()).await;
+    assert_eq!(pending.new_fee_rate, 100);
+    assert_eq!(pending.proposed_at, now);
+    assert_eq!(pending.effective_at, now + DEFAULT_FEE_CHANGE_DELAY);
+    assert_eq!(env.platform().await.protocol_fee_rate, PROTOCOL_FEE_RATE);
+
+    env.warp(DEFAULT_FEE_CHANGE_DELAY).await;
+    env.send(
+        &[instructions::execute_fee_change(&admin.pubkey())],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.platform().await.protocol_fee_rate, 100);
+    assert!(env.account_data(&pda::pending_fee_change()).await.is_none());
+
+    // Swaps charge the new rate once it applies
+    let fee_collector = env.fee_collector();
+    env.swap(&keys, &admin, 10_000_000, true).await.unwrap();
+    assert_eq!(env.balance(&fee_collector).await, 100_000);
+}
+
+#[tokio::test]
+async fn cancels_fee_change() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let admin = env.admin();
+
+    // A second proposal replaces the first
+    env.send(&[propose_fee_change(&admin, 100)], &[&admin])
+        .await
+        .unwrap();
+    env.send(&[propose_fee_change(&admin, 50)], &[&admin])
+        .await
+        .unwrap();
+    let pending: PendingFeeChange = env.account(&pda::pending_fee_change()).await;
+    assert_eq!(pending.new_fee_rate, 50);
+
+    env.send(
+        &[instructions::cancel_fee_change(&admin.pubkey())],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    assert!(env.account_data(&pda::pending_fee_change()).await.is_none());
+
+    env.warp(DEFAULT_FEE_CHANGE_DELAY).await;
+    assert_error(
+        env.send(
+            &[instructions::execute_fee_change(&admin.pubkey())],
+            &[&admin],
+        )
+        .await,
+        anchor_lang::error::ErrorCode::AccountNotInitialized,
+    );
+    assert_eq!(env.platform().await.protocol_fee_rate, PROTOCOL_FEE_RATE);
+}
+
+#[tokio::test]
+async fn updates_fee_change_delay() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let admin = env.admin();
+    let update_delay = |delay_seconds| {
+        instructions::update_fee_change_delay(
+            &admin.pubkey(),
+            args::UpdateFeeChangeDelay { delay_seconds },
+        )
+    };
+    let execute = instructions::execute_fee_change(&admin.pubkey());
+
+    env.send(&[propose_fee_change(&admin, 100)], &[&admin])
+        .await
+        .unwrap();
+    env.send(&[update_delay(MIN_FEE_CHANGE_DELAY)], &[&admin])
+        .await
+        .unwrap();
+    assert_eq!(
+        env.platform().await.fee_change_delay_seconds,
+        MIN_FEE_CHANGE_DELAY
+    );
+
+    // Shortening the delay does not bring a pending change forward
+    env.warp(MIN_FEE_CHANGE_DELAY).await;
+    assert_error(
+        env.send(std::slice::from_ref(&execute), &[&admin]).await,
+        AmmError::FeeChangeNotReady,
+    );
+    env.warp(DEFAULT_FEE_CHANGE_DELAY - MIN_FEE_CHANGE_DELAY)
+        .await;
+    env.send(&[execute], &[&admin]).await.unwrap();
+    assert_eq!(env.platform().await.protocol_fee_rate, 100);
+
+    // Later proposals give the new notice
+    env.send(&[propose_fee_change(&admin, 50)], &[&admin])
+        .await
+        .unwrap();
+    let pending: PendingFeeChange = env.account(&pda::pending_fee_change()).await;
+    assert_eq!(
+        pending.effective_at,
+        pending.proposed_at + MIN_FEE_CHANGE_DELAY
+    );
+}
+
+#[tokio::test]
+async fn rejects_fee_change_within_one_transaction() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let admin = env.admin();
+    let update_delay = |delay_seconds| {
+        instructions::update_fee_change_delay(
+            &admin.pubkey(),
+            args::UpdateFeeChangeDelay { delay_seconds },
+        )
+    };
+    let execute = instructions::execute_fee_change(&admin.pubkey());
+
+    // The delay cannot be dropped to zero ahead of a proposal...
+    assert_error(
+        env.send(
+            &[
+                update_delay(0),
+                propose_fee_change(&admin, MAX_PROTOCOL_FEE_RATE),
+                execute.clone(),
+            ],
+            &[&admin],
+        )
+        .await,
+        AmmError::FeeChangeDelayTooShort,
+    );
+
+    // ...and the shortest delay still leaves notice before it applies
+    assert_error(
+        env.send(
+            &[
+                update_delay(MIN_FEE_CHANGE_DELAY),
+                propose_fee_change(&admin, MAX_PROTOCOL_FEE_RATE),
+                execute,
+            ],
+            &[&admin],
+        )
+        .await,
+        AmmError::FeeChangeNotReady,
+    );
+    assert_eq!(env.platform().await.protocol_fee_rate, PROTOCOL_FEE_RATE);
+    assert_eq!(
+        env.platform().await.fee_change_delay_seconds,
+        DEFAULT_FEE_CHANGE_DELAY
+    );
+}
+
+#[tokio::test]
+async fn updates_swap_cooldown_and_observation_interval() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let admin = env.admin();
+
+    env.send(
+        &[
+            instructions::update_swap_cooldown(
+                &admin.pubkey(),
+                args::UpdateSwapCooldown {
+                    cooldown_seconds: 3_600,
+                },
+            ),
+            instructions::update_observation_interval(
+                &admin.pubkey(),
+                args::UpdateObservationInterval {
+                    interval_seconds: 15,
+                },
+            ),
+        ],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    let platform = env.platform().await;
+    assert_eq!(platform.swap_cooldown_seconds, 3_600);
+    assert_eq!(platform.observation_interval_seconds, 15);
+}
+
+#[tokio::test]
+async fn withdraws_platform_fees() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    let mint = env.base_mint;
+    env.fund(&keys, &admin.pubkey(), 10_000_000, 10_000_000)
+        .await;
+
+    // Swaps in both directions pay the protocol fee in MOJO
+    let platform = env.platform().await;
+    let mut collected = 0;
+    for is_base_input in [true, false] {
+        let pair = env.pair(&keys).await;
+        let quote = quote::quote_exact_in(&pair, &platform, 5_000_000, is_base_input).unwrap();
+        env.swap(&keys, &admin, 5_000_000, is_base_input)
+            .await
+            .unwrap();
+        collected += quote.protocol_fee;
+    }
+    let fee_collector = env.fee_collector();
+    assert!(collected > 0);
+    assert_eq!(env.balance(&fee_collector).await, collected);
+
+    let treasurer = env.wallet().pubkey();
+    let destination = env.create_ata(&treasurer, &mint, &TOKEN_PROGRAM_ID).await;
+    env.send(
+        &[instructions::withdraw_platform_fees(
+            &admin.pubkey(),
+            &env.fee_collector(),
+            &mint,
+            &destination,
+            &TOKEN_PROGRAM_ID,
+            args::WithdrawPlatformFees {
+                amount: collected - 1,
+            },
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(env.balance(&fee_collector).await, 1);
+    assert_eq!(env.balance(&destination).await, collected - 1);
+}
@@ -0,0 +1,470 @@
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use mojo_sdk::{args, instructions, quote, PairKeys, SwapQuote, TraderState};
+use mojo_tests::{assert_error, AmmError, Env, MintExtensions};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use crate::{INITIAL_LIQUIDITY, LP_FEE_RATE, PROTOCOL_FEE_RATE};
+
+/// Balances a swap moves: the user's base and paired accounts, the fee
+/// collector and both vaults.
+async fn snapshot(env: &mut Env, keys: &PairKeys, user: &Keypair) -> [u64; 5] {
+    let user = user.pubkey();
+    let fee_collector = env.fee_collector();
+    [
+        env.balance(&keys.user_base_ata(&user)).await,
+        env.balance(&keys.user_paired_ata(&user)).await,
+        env.balance(&fee_collector).await,
+        env.balance(&keys.base_vault).await,
+        env.balance(&keys.paired_vault).await,
+    ]
+}
+
+/// Swaps exactly `amount_in` against a 1000/1000 pool and checks every
+/// balance against the program's own quote.
+async fn swaps_exact_in(is_base_input: bool, amount_in: u64) {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let user = env.wallet();
+    env.fund(&keys, &user.pubkey(), amount_in, amount_in).await;
+
+    let quote = quote::quote_exact_in(
+        &env.pair(&keys).await,
+        &env.platform().await,
+        amount_in,
+        is_base_input,
+    )
+    .unwrap();
+    let [base, paired, fees, base_vault, paired_vault] = snapshot(&mut env, &keys, &user).await;
+    let fee_collector = env.fee_collector();
+
+    // The quote is also the tightest minimum the swap accepts
+    let swap = |min_output_amount| {
+        instructions::swap(
+            &keys,
+            &user.pubkey(),
+            &fee_collector,
+            args::Swap {
+                input_amount: amount_in,
+                min_output_amount,
+                is_base_input,
+                deadline: None,
+            },
+        )
+    };
+    let (too_tight, exact) = (swap(quote.amount_out + 1), swap(quote.amount_out));
+    assert_error(
+        env.send(&[too_tight], &[&user]).await,
+        AmmError::SlippageExceeded,
+    );
+    env.send(&[exact], &[&user]).await.unwrap();
+
+    let after = snapshot(&mut env, &keys, &user).await;
+    // MOJO pays the protocol fee on whichever side of the swap it is
+    let fee = if is_base_input {
+        amount_in * PROTOCOL_FEE_RATE as u64 / 10_000
+    } else {
+        (quote.amount_out + quote.protocol_fee) * PROTOCOL_FEE_RATE as u64 / 10_000
+    };
+    assert_eq!(quote.protocol_fee, fee);
+    assert_eq!(after[2], fees + fee);
+    if is_base_input {
+        assert_eq!(after[0], base - amount_in);
+        assert_eq!(after[1], paired + quote.amount_out);
+        assert_eq!(after[3], base_vault + amount_in - fee);
+        assert_eq!(after[4], paired_vault - quote.amount_out);
+    } else {
+        assert_eq!(after[0], base + quote.amount_out);
+        assert_eq!(after[1], paired - amount_in);
+        assert_eq!(after[3], base_vault - quote.amount_out - fee);
+        assert_eq!(after[4], paired_vault + amount_in);
+    }
+
+    let pair = env.pair(&keys).await;
+    assert_eq!(pair.base_reserve, quote.new_base_reserve);
+    assert_eq!(pair.paired_reserve, quote.new_paired_reserve);
+    assert_eq!(pair.base_reserve, after[3]);
+    assert_eq!(pair.paired_reserve, after[4]);
+}
+
+#[tokio::test]
+async fn swaps_base_for_paired() {
+    swaps_exact_in(true, 10_000_000).await;
+}
+
+#[tokio::test]
+async fn swaps_paired_for_base() {
+    swaps_exact_in(false, 10_000_000).await;
+}
+
+#[tokio::test]
+async fn performs_large_swap() {
+    swaps_exact_in(true, INITIAL_LIQUIDITY / 2).await;
+}
+
+#[tokio::test]
+async fn swaps_for_exact_output() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let user = env.wallet();
+    env.fund(&keys, &user.pubkey(), 100_000_000, 100_000_000)
+        .await;
+    let amount_out = 5_000_000;
+    let fee_collector = env.fee_collector();
+
+    for is_base_input in [true, false] {
+        let quote = quote::quote_exact_out(
+            &env.pair(&keys).await,
+            &env.platform().await,
+            amount_out,
+            is_base_input,
+        )
+        .unwrap();
+        let before = snapshot(&mut env, &keys, &user).await;
+
+        let instruction = instructions::swap_exact_out(
+            &keys,
+            &user.pubkey(),
+            &fee_collector,
+            args::SwapExactOut {
+                amount_out,
+                max_amount_in: quote.amount_in,
+                is_base_input,
+                deadline: None,
+            },
+        );
+        env.send(&[instruction], &[&user]).await.unwrap();
+        let after = snapshot(&mut env, &keys, &user).await;
+
+        let (spent, received) = if is_base_input {
+            (before[0] - after[0], after[1] - before[1])
+        } else {
+            (before[1] - after[1], after[0] - before[0])
+        };
+        assert_eq!(spent, quote.amount_in);
+        assert_eq!(received, amount_out);
+        assert_eq!(after[2] - before[2], quote.protocol_fee);
+    }
+}
+
+#[tokio::test]
+async fn routes_through_mojo() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let pair_in = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let pair_out = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, 2 * INITIAL_LIQUIDITY)
+        .await;
+    let user = env.wallet();
+    let amount_in = 10_000_000;
+    env.fund(&pair_in, &user.pubkey(), 0, amount_in).await;
+    env.fund(&pair_out, &user.pubkey(), 0, 0).await;
+    let fee_collector = env.fee_collector();
+
+    // Each hop is priced like a plain swap on its pair
+    let platform = env.platform().await;
+    let hop_in =
+        quote::quote_exact_in(&env.pair(&pair_in).await, &platform, amount_in, false).unwrap();
+    let hop_out = quote::quote_exact_in(
+        &env.pair(&pair_out).await,
+        &platform,
+        hop_in.amount_out,
+        true,
+    )
+    .unwrap();
+    let route = |min_amount_out| {
+        instructions::swap_route(
+            &pair_in,
+            &pair_out,
+            &user.pubkey(),
+            &fee_collector,
+            args::SwapRoute {
+                amount_in,
+                min_amount_out,
+                deadline: None,
+            },
+        )
+    };
+    let (too_tight, exact) = (route(hop_out.amount_out + 1), route(hop_out.amount_out));
+    assert_error(
+        env.send(&[too_tight], &[&user]).await,
+        AmmError::SlippageExceeded,
+    );
+    env.send(&[exact], &[&user]).await.unwrap();
+
+    let user = user.pubkey();
+    assert_eq!(env.balance(&pair_in.user_paired_ata(&user)).await, 0);
+    assert_eq!(
+        env.balance(&pair_out.user_paired_ata(&user)).await,
+        hop_out.amount_out
+    );
+    // The intermediate MOJO never reaches the user
+    assert_eq!(env.balance(&pair_in.user_base_ata(&user)).await, 0);
+    assert_eq!(
+        env.balance(&fee_collector).await,
+        hop_in.protocol_fee + hop_out.protocol_fee
+    );
+
+    let (pair_in, pair_out) = (env.pair(&pair_in).await, env.pair(&pair_out).await);
+    assert_eq!(pair_in.base_reserve, hop_in.new_base_reserve);
+    assert_eq!(pair_in.paired_reserve, hop_in.new_paired_reserve);
+    assert_eq!(pair_out.base_reserve, hop_out.new_base_reserve);
+    assert_eq!(pair_out.paired_reserve, hop_out.new_paired_reserve);
+}
+
+#[tokio::test]
+async fn accrues_lp_fee() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let deposit = 100_000_000;
+    // 1% LP fee
+    let keys = env.pool(100, deposit, deposit).await;
+    let admin = env.admin();
+    let before = env.pair(&keys).await;
+
+    // Trade back and forth so fees pile up on both sides
+    env.fund(&keys, &admin.pubkey(), 10_000_000, 10_000_000)
+        .await;
+    env.swap(&keys, &admin, 10_000_000, true).await.unwrap();
+    env.swap(&keys, &admin, 10_000_000, false).await.unwrap();
+    let after = env.pair(&keys).await;
+    assert!(after.base_reserve as u128 * after.paired_reserve as u128 > before.k());
+
+    // The sole LP gets all but the locked share back, fees included
+    let [base, paired, ..] = snapshot(&mut env, &keys, &admin).await;
+    let instruction = instructions::remove_liquidity(
+        &keys,
+        &admin.pubkey(),
+        args::RemoveLiquidity {
+            lp_amount: after.total_liquidity - mojo_contract::MINIMUM_LIQUIDITY,
+            min_base: 0,
+            min_paired: 0,
+            deadline: None,
+        },
+    );
+    env.send(&[instruction], &[&admin]).await.unwrap();
+    let [base_after, paired_after, ..] = snapshot(&mut env, &keys, &admin).await;
+
+    let withdrawn = (base_after - base) as u128 * (paired_after - paired) as u128;
+    assert!(withdrawn > deposit as u128 * deposit as u128);
+}
+
+#[tokio::test]
+async fn enforces_swap_cooldown() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 10_000_000, 0).await;
+
+    // A one hour platform-wide cooldown
+    env.send(
+        &[instructions::update_swap_cooldown(
+            &admin.pubkey(),
+            args::UpdateSwapCooldown {
+                cooldown_seconds: 3_600,
+            },
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    env.swap(&keys, &admin, 1_000_000, true).await.unwrap();
+    assert_error(
+        env.swap(&keys, &admin, 1_000_000, true).await,
+        AmmError::SwapCooldown,
+    );
+
+    // It runs out after the hour
+    env.warp(3_600).await;
+    env.swap(&keys, &admin, 1_000_000, true).await.unwrap();
+
+    // A per-pair override of zero lifts it for this pool only
+    env.send(
+        &[instructions::update_pair_swap_cooldown(
+            &keys.pair,
+            &admin.pubkey(),
+            args::UpdatePairSwapCooldown {
+                cooldown_seconds: Some(0),
+            },
+        )],
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    env.swap(&keys, &admin, 1_000_000, true).await.unwrap();
+
+    let trader: TraderState = env.account(&keys.trader_state(&admin.pubkey())).await;
+    assert_eq!(trader.user, admin.pubkey());
+    assert_eq!(trader.pair, keys.pair);
+    assert_eq!(trader.last_swap_time, env.clock().await.unix_timestamp);
+}
+
+#[tokio::test]
+async fn quotes_match_execution() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let user = env.wallet();
+    let amount_in = 3_000_000;
+    env.fund(&keys, &user.pubkey(), amount_in, amount_in).await;
+
+    for is_base_input in [true, false] {
+        let quote: SwapQuote = env
+            .view(instructions::quote_swap(
+                &keys,
+                args::QuoteSwap {
+                    amount_in,
+                    is_base_input,
+                },
+            ))
+            .await
+            .unwrap();
+        // The SDK prices it the same way off-chain
+        let offline = quote::quote_exact_in(
+            &env.pair(&keys).await,
+            &env.platform().await,
+            amount_in,
+            is_base_input,
+        )
+        .unwrap();
+        assert_eq!(quote, offline);
+        assert!(quote.price_impact_bps > 0);
+
+        let before = snapshot(&mut env, &keys, &user).await;
+        env.swap(&keys, &user, amount_in, is_base_input)
+            .await
+            .unwrap();
+        let after = snapshot(&mut env, &keys, &user).await;
+
+        let received = if is_base_input {
+            after[1] - before[1]
+        } else {
+            after[0] - before[0]
+        };
+        assert_eq!(received, quote.amount_out);
+        assert_eq!(after[2] - before[2], quote.protocol_fee);
+        let pair = env.pair(&keys).await;
+        assert_eq!(pair.base_reserve, quote.new_base_reserve);
+        assert_eq!(pair.paired_reserve, quote.new_paired_reserve);
+    }
+}
+
+#[tokio::test]
+async fn rejects_trades_after_deadline() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .new_pair(&TOKEN_PROGRAM_ID, MintExtensions::default(), LP_FEE_RATE)
+        .await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 200_000_000, 200_000_000)
+        .await;
+    let now = env.clock().await.unix_timestamp;
+    let (future, past) = (Some(now + 600), Some(now - 600));
+    let fee_collector = env.fee_collector();
+
+    let add_liquidity = |deadline| {
+        instructions::add_liquidity(
+            &keys,
+            &admin.pubkey(),
+            args::AddLiquidity {
+                base_desired: 100_000_000,
+                paired_desired: 100_000_000,
+                base_min: 0,
+                paired_min: 0,
+                min_lp_out: 0,
+                deadline,
+            },
+        )
+    };
+    let swap = |deadline| {
+        instructions::swap(
+            &keys,
+            &admin.pubkey(),
+            &fee_collector,
+            args::Swap {
+                input_amount: 1_000_000,
+                min_output_amount: 0,
+                is_base_input: true,
+                deadline,
+            },
+        )
+    };
+    let remove_liquidity = |deadline| {
+        instructions::remove_liquidity(
+            &keys,
+            &admin.pubkey(),
+            args::RemoveLiquidity {
+                lp_amount: 1_000,
+                min_base: 0,
+                min_paired: 0,
+                deadline,
+            },
+        )
+    };
+
+    env.send(&[add_liquidity(future)], &[&admin]).await.unwrap();
+    env.send(&[swap(future)], &[&admin]).await.unwrap();
+
+    for instruction in [swap(past), add_liquidity(past), remove_liquidity(past)] {
+        assert_error(
+            env.send(&[instruction], &[&admin]).await,
+            AmmError::TransactionExpired,
+        );
+    }
+}
+
+#[tokio::test]
+async fn rejects_insufficient_funds() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let user = env.wallet();
+    env.fund(&keys, &user.pubkey(), 1_000_000, 0).await;
+
+    assert!(env.swap(&keys, &user, 1_000_001, true).await.is_err());
+    assert_eq!(
+        env.balance(&keys.user_base_ata(&user.pubkey())).await,
+        1_000_000
+    );
+}
+
+#[tokio::test]
+async fn rejects_swapped_token_accounts() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .pool(LP_FEE_RATE, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY)
+        .await;
+    let user = env.wallet();
+    env.fund(&keys, &user.pubkey(), 1_000_000, 1_000_000).await;
+    let fee_collector = env.fee_collector();
+
+    let mut instruction = instructions::swap(
+        &keys,
+        &user.pubkey(),
+        &fee_collector,
+        args::Swap {
+            input_amount: 1_000_000,
+            min_output_amount: 0,
+            is_base_input: true,
+            deadline: None,
+        },
+    );
+    let base_ata = keys.user_base_ata(&user.pubkey());
+    let paired_ata = keys.user_paired_ata(&user.pubkey());
+    for meta in &mut instruction.accounts {
+        if meta.pubkey == base_ata {
+            meta.pubkey = paired_ata;
+        } else if meta.pubkey == paired_ata {
+            meta.pubkey = base_ata;
+        }
+    }
+
+    assert!(env.send(&[instruction], &[&user]).await.is_err());
+}
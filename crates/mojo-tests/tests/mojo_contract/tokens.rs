@@ -0,0 +1,564 @@
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    token::{spl_token, ID as TOKEN_PROGRAM_ID},
+    token_2022::ID as TOKEN_2022_PROGRAM_ID,
+};
+use mojo_contract::{MINIMUM_LIQUIDITY, WSOL_MINT};
+use mojo_sdk::{args, instructions, pda, quote, PairKeys, SwapQuote};
+use mojo_tests::{assert_error, AmmError, Env, MintExtensions};
+use solana_sdk::{rent::Rent, signer::Signer, system_instruction};
+
+use crate::PROTOCOL_FEE_RATE;
+
+/// Opens a zero LP fee pool of `deposit` on each side, the paired token
+/// owned by `paired_token_program`.
+async fn pool(
+    env: &mut Env,
+    paired_token_program: &Pubkey,
+    extensions: MintExtensions,
+    deposit: u64,
+) -> PairKeys {
+    let keys = env.new_pair(paired_token_program, extensions, 0).await;
+    let admin = env.admin();
+    env.fund(&keys, &admin.pubkey(), 2 * deposit, 2 * deposit)
+        .await;
+    keys
+}
+
+async fn provides_liquidity_and_swaps(paired_token_program: Pubkey) {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let deposit = 100_000_000;
+    let keys = pool(
+        &mut env,
+        &paired_token_program,
+        MintExtensions::default(),
+        deposit,
+    )
+    .await;
+    let admin = env.admin();
+    let paired_ata = keys.user_paired_ata(&admin.pubkey());
+
+    env.add_liquidity(&keys, &admin, deposit, deposit)
+        .await
+        .unwrap();
+    assert_eq!(env.balance(&keys.paired_vault).await, deposit);
+
+    // One swap in each direction through the paired token program
+    for is_base_input in [true, false] {
+        let before = env.balance(&paired_ata).await;
+        env.swap(&keys, &admin, 5_000_000, is_base_input)
+            .await
+            .unwrap();
+        let after = env.balance(&paired_ata).await;
+        if is_base_input {
+            assert!(after > before);
+        } else {
+            assert_eq!(before - after, 5_000_000);
+        }
+    }
+
+    let pair = env.pair(&keys).await;
+    let before = env.balance(&paired_ata).await;
+    let lp_amount = pair.total_liquidity - MINIMUM_LIQUIDITY;
+    let instruction = instructions::remove_liquidity(
+        &keys,
+        &admin.pubkey(),
+        args::RemoveLiquidity {
+            lp_amount,
+            min_base: 0,
+            min_paired: 0,
+            deadline: None,
+        },
+    );
+    env.send(&[instruction], &[&admin]).await.unwrap();
+
+    assert_eq!(
+        env.balance(&paired_ata).await - before,
+        pair.paired_reserve * lp_amount / pair.total_liquidity
+    );
+    assert_eq!(env.pair(&keys).await.total_liquidity, MINIMUM_LIQUIDITY);
+}
+
+#[tokio::test]
+async fn trades_spl_token_player_token() {
+    provides_liquidity_and_swaps(TOKEN_PROGRAM_ID).await;
+}
+
+#[tokio::test]
+async fn trades_token_2022_player_token() {
+    provides_liquidity_and_swaps(TOKEN_2022_PROGRAM_ID).await;
+}
+
+#[tokio::test]
+async fn credits_only_what_arrives_with_transfer_fee() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let deposit = 100_000_000;
+    // 1% withheld on every transfer
+    let extensions = MintExtensions {
+        transfer_fee_bps: Some(100),
+        ..MintExtensions::default()
+    };
+    let keys = pool(&mut env, &TOKEN_2022_PROGRAM_ID, extensions, deposit).await;
+    let admin = env.admin();
+    let paired_ata = keys.user_paired_ata(&admin.pubkey());
+
+    env.add_liquidity(&keys, &admin, deposit, deposit)
+        .await
+        .unwrap();
+    let pair = env.pair(&keys).await;
+    assert_eq!(pair.paired_reserve, deposit - deposit / 100);
+    assert_eq!(env.balance(&keys.paired_vault).await, pair.paired_reserve);
+
+    // The quote nets the transfer fee out of the output, like the SDK
+    let amount_in = 5_000_000;
+    let quote: SwapQuote = env
+        .view(instructions::quote_swap(
+            &keys,
+            args::QuoteSwap {
+                amount_in,
+                is_base_input: true,
+            },
+        ))
+        .await
+        .unwrap();
+    let mint = env.account_data(&keys.paired_token_mint).await.unwrap();
+    let config = quote::transfer_fee_config(&TOKEN_2022_PROGRAM_ID, &mint)
+        .unwrap()
+        .unwrap();
+    let fees = quote::TransferFees {
+        output: Some(&config),
+        epoch: env.clock().await.epoch,
+        ..quote::TransferFees::default()
+    };
+    let offline = quote::quote_exact_in_after_transfer_fees(
+        &pair,
+        &env.platform().await,
+        amount_in,
+        true,
+        &fees,
+    )
+    .unwrap();
+    assert_eq!(quote, offline);
+    assert!(quote.amount_received < quote.amount_out);
+
+    // Slippage is measured on what the user receives, not what the pool sends
+    let fee_collector = env.fee_collector();
+    let swap = |min_output_amount| {
+        instructions::swap(
+            &keys,
+            &admin.pubkey(),
+            &fee_collector,
+            args::Swap {
+                input_amount: amount_in,
+                min_output_amount,
+                is_base_input: true,
+                deadline: None,
+            },
+        )
+    };
+    let (too_tight, exact) = (swap(quote.amount_out), swap(quote.amount_received));
+    assert_error(
+        env.send(&[too_tight], &[&admin]).await,
+        AmmError::SlippageExceeded,
+    );
+    let before = env.balance(&paired_ata).await;
+    env.send(&[exact], &[&admin]).await.unwrap();
+    assert_eq!(
+        env.balance(&paired_ata).await - before,
+        quote.amount_received
+    );
+
+    // Selling the player token credits the pool with the net input only
+    let before = env.pair(&keys).await.paired_reserve;
+    env.swap(&keys, &admin, amount_in, false).await.unwrap();
+    let pair = env.pair(&keys).await;
+    assert_eq!(pair.paired_reserve - before, amount_in - amount_in / 100);
+    assert_eq!(env.balance(&keys.paired_vault).await, pair.paired_reserve);
+}
+
+#[tokio::test]
+async fn quotes_exact_out_through_transfer_fees() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let deposit = 100_000_000;
+    let extensions = MintExtensions {
+        transfer_fee_bps: Some(100),
+        ..MintExtensions::default()
+    };
+    let keys = pool(&mut env, &TOKEN_2022_PROGRAM_ID, extensions, deposit).await;
+    let admin = env.admin();
+    env.add_liquidity(&keys, &admin, deposit, deposit)
+        .await
+        .unwrap();
+    let mint = env.account_data(&keys.paired_token_mint).await.unwrap();
+    let config = quote::transfer_fee_config(&TOKEN_2022_PROGRAM_ID, &mint)
+        .unwrap()
+        .unwrap();
+    let base_ata = keys.user_base_ata(&admin.pubkey());
+    let paired_ata = keys.user_paired_ata(&admin.pubkey());
+
+    // The player token is the output when buying it and the input when
+    // selling it; either way the SDK prices exactly what the swap moves
+    for is_base_input in [true, false] {
+        let epoch = env.clock().await.epoch;
+        let fees = if is_base_input {
+            quote::TransferFees {
+                output: Some(&config),
+                epoch,
+                ..quote::TransferFees::default()
+            }
+        } else {
+            quote::TransferFees {
+                input: Some(&config),
+                epoch,
+                ..quote::TransferFees::default()
+            }
+        };
+        let amount_out = 1_000_000;
+        let quote = quote::quote_exact_out_after_transfer_fees(
+            &env.pair(&keys).await,
+            &env.platform().await,
+            amount_out,
+            is_base_input,
+            &fees,
+        )
+        .unwrap();
+
+        let (input_ata, output_ata) = if is_base_input {
+            (base_ata, paired_ata)
+        } else {
+            (paired_ata, base_ata)
+        };
+        let (input_before, output_before) = (
+            env.balance(&input_ata).await,
+            env.balance(&output_ata).await,
+        );
+        let swap = instructions::swap_exact_out(
+            &keys,
+            &admin.pubkey(),
+            &env.fee_collector(),
+            args::SwapExactOut {
+                amount_out,
+                max_amount_in: quote.amount_in,
+                is_base_input,
+                deadline: None,
+            },
+        );
+        env.send(&[swap], &[&admin]).await.unwrap();
+
+        assert_eq!(
+            input_before - env.balance(&input_ata).await,
+            quote.amount_in
+        );
+        assert_eq!(env.balance(&output_ata).await - output_before, amount_out);
+    }
+}
+
+#[tokio::test]
+async fn zaps_through_transfer_fees() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let deposit = 100_000_000;
+    let extensions = MintExtensions {
+        transfer_fee_bps: Some(100),
+        ..MintExtensions::default()
+    };
+    let keys = pool(&mut env, &TOKEN_2022_PROGRAM_ID, extensions, deposit).await;
+    let admin = env.admin();
+    env.add_liquidity(&keys, &admin, deposit, deposit)
+        .await
+        .unwrap();
+    let base_ata = keys.user_base_ata(&admin.pubkey());
+    let paired_ata = keys.user_paired_ata(&admin.pubkey());
+    let lp_ata = keys.user_lp_ata(&admin.pubkey());
+    let fee_collector = env.fee_collector();
+
+    // The player token is withheld from on the way out of the pool and back
+    // in when bought, and on the way in when sold. Either way the split
+    // still leaves only dust behind
+    let zap_in = 20_000_000;
+    for is_base_input in [true, false] {
+        let (input_ata, output_ata) = if is_base_input {
+            (base_ata, paired_ata)
+        } else {
+            (paired_ata, base_ata)
+        };
+        let input_before = env.balance(&input_ata).await;
+        let output_before = env.balance(&output_ata).await;
+        let lp_before = env.balance(&lp_ata).await;
+
+        let zap = instructions::add_liquidity_single_sided(
+            &keys,
+            &admin.pubkey(),
+            &fee_collector,
+            args::AddLiquiditySingleSided {
+                amount_in: zap_in,
+                is_base_input,
+                min_lp_out: 1,
+                deadline: None,
+            },
+        );
+        env.send(&[zap], &[&admin]).await.unwrap();
+
+        let unspent = zap_in - (input_before - env.balance(&input_ata).await);
+        let left_over = env.balance(&output_ata).await - output_before;
+        assert!(env.balance(&lp_ata).await > lp_before);
+        assert!(unspent <= zap_in / 10_000, "unspent {unspent}");
+        assert!(left_over <= 5, "left over {left_over}");
+    }
+}
+
+/// Transfers the test hook has seen for the mint of `counter`.
+async fn hook_transfers(env: &mut Env, counter: &Pubkey) -> u64 {
+    env.account::<test_transfer_hook::Counter>(counter)
+        .await
+        .transfers
+}
+
+#[tokio::test]
+async fn forwards_transfer_hook_accounts() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let extensions = MintExtensions {
+        transfer_hook: Some(test_transfer_hook::ID),
+        ..MintExtensions::default()
+    };
+    let deposit = 100_000_000;
+    let keys = pool(&mut env, &TOKEN_2022_PROGRAM_ID, extensions, deposit).await;
+    let admin = env.admin();
+    let mint = keys.paired_token_mint;
+
+    let (counter, _) =
+        Pubkey::find_program_address(&[b"counter", mint.as_ref()], &test_transfer_hook::ID);
+    let (extra_account_meta_list, _) = Pubkey::find_program_address(
+        &[b"extra-account-metas", mint.as_ref()],
+        &test_transfer_hook::ID,
+    );
+    let initialize = Instruction {
+        program_id: test_transfer_hook::ID,
+        accounts: test_transfer_hook::accounts::InitializeExtraAccountMetaList {
+            payer: admin.pubkey(),
+            extra_account_meta_list,
+            mint,
+            counter,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: test_transfer_hook::instruction::InitializeExtraAccountMetaList {}.data(),
+    };
+    env.send(&[initialize], &[&admin]).await.unwrap();
+
+    // Token-2022 resolves the hook from these, passed after the named accounts
+    let hook_accounts = [
+        AccountMeta::new(counter, false),
+        AccountMeta::new_readonly(test_transfer_hook::ID, false),
+        AccountMeta::new_readonly(extra_account_meta_list, false),
+    ];
+    let with_hook = |mut instruction: Instruction| {
+        instruction.accounts.extend_from_slice(&hook_accounts);
+        instruction
+    };
+    let fee_collector = env.fee_collector();
+    let swap = |is_base_input| {
+        instructions::swap(
+            &keys,
+            &admin.pubkey(),
+            &fee_collector,
+            args::Swap {
+                input_amount: 5_000_000,
+                min_output_amount: 1,
+                is_base_input,
+                deadline: None,
+            },
+        )
+    };
+
+    let add_liquidity = instructions::add_liquidity(
+        &keys,
+        &admin.pubkey(),
+        args::AddLiquidity {
+            base_desired: deposit,
+            paired_desired: deposit,
+            base_min: 0,
+            paired_min: 0,
+            min_lp_out: 0,
+            deadline: None,
+        },
+    );
+    env.send(&[with_hook(add_liquidity)], &[&admin])
+        .await
+        .unwrap();
+    assert_eq!(hook_transfers(&mut env, &counter).await, 1);
+
+    // Without the hook accounts Token-2022 cannot run the hook
+    assert!(env.send(&[swap(true)], &[&admin]).await.is_err());
+
+    for is_base_input in [true, false] {
+        env.send(&[with_hook(swap(is_base_input))], &[&admin])
+            .await
+            .unwrap();
+    }
+    assert_eq!(hook_transfers(&mut env, &counter).await, 3);
+
+    // A route selling the hooked token into a plain pool
+    let plain = env.pool(0, deposit, deposit).await;
+    let route = instructions::swap_route(
+        &keys,
+        &plain,
+        &admin.pubkey(),
+        &fee_collector,
+        args::SwapRoute {
+            amount_in: 5_000_000,
+            min_amount_out: 1,
+            deadline: None,
+        },
+    );
+    let without_hook = env.send(std::slice::from_ref(&route), &[&admin]).await;
+    assert!(without_hook.is_err());
+    env.send(&[with_hook(route)], &[&admin]).await.unwrap();
+    assert_eq!(hook_transfers(&mut env, &counter).await, 4);
+
+    let pair = env.pair(&keys).await;
+    let remove_liquidity = instructions::remove_liquidity(
+        &keys,
+        &admin.pubkey(),
+        args::RemoveLiquidity {
+            lp_amount: pair.total_liquidity - MINIMUM_LIQUIDITY,
+            min_base: 0,
+            min_paired: 0,
+            deadline: None,
+        },
+    );
+    env.send(&[with_hook(remove_liquidity)], &[&admin])
+        .await
+        .unwrap();
+    assert_eq!(hook_transfers(&mut env, &counter).await, 5);
+}
+
+#[tokio::test]
+async fn trades_native_sol() {
+    let mut env = Env::with_platform(PROTOCOL_FEE_RATE).await;
+    let keys = env
+        .create_pair(&WSOL_MINT, &TOKEN_PROGRAM_ID, 0)
+        .await
+        .unwrap();
+    let admin = env.admin();
+    let wallet = admin.pubkey();
+    let base_mint = env.base_mint;
+    env.mint_to(&base_mint, &TOKEN_PROGRAM_ID, &wallet, 200_000_000)
+        .await;
+    env.create_ata(&wallet, &keys.lp_mint, &TOKEN_PROGRAM_ID)
+        .await;
+    let wsol_ata = keys.user_paired_ata(&wallet);
+    let base_ata = keys.user_base_ata(&wallet);
+    let native_sol_account = pda::native_sol_account(&wallet);
+
+    // WSOL the wallet already holds stays wrapped throughout
+    let held = 50_000_000;
+    env.create_ata(&wallet, &WSOL_MINT, &TOKEN_PROGRAM_ID).await;
+    let wrap = [
+        system_instruction::transfer(&wallet, &wsol_ata, held),
+        spl_token::instruction::sync_native(&TOKEN_PROGRAM_ID, &wsol_ata).unwrap(),
+    ];
+    env.send(&wrap, &[&admin]).await.unwrap();
+
+    // Lamports go straight from the wallet into the pool
+    let deposit = 1_000_000_000;
+    let before = env.lamports(&wallet).await;
+    let add_liquidity = instructions::add_liquidity_sol(
+        &keys,
+        &wallet,
+        args::AddLiquiditySol {
+            base_desired: 100_000_000,
+            paired_desired: deposit,
+            base_min: 0,
+            paired_min: 0,
+            min_lp_out: 0,
+            deadline: None,
+        },
+    );
+    env.send(&[add_liquidity], &[&admin]).await.unwrap();
+    assert_eq!(before - env.lamports(&wallet).await, deposit);
+    assert_eq!(env.balance(&wsol_ata).await, held);
+    assert_eq!(env.balance(&keys.paired_vault).await, deposit);
+
+    let fee_collector = env.fee_collector();
+    let swap_sol = |input_amount, is_base_input| {
+        instructions::swap_sol(
+            &keys,
+            &wallet,
+            &fee_collector,
+            args::SwapSol {
+                input_amount,
+                min_output_amount: 1,
+                is_base_input,
+                deadline: None,
+            },
+        )
+    };
+
+    // SOL in, MOJO out
+    let sol_in = 100_000_000;
+    let (before, base_before) = (env.lamports(&wallet).await, env.balance(&base_ata).await);
+    env.send(&[swap_sol(sol_in, false)], &[&admin])
+        .await
+        .unwrap();
+    // The first swap also pays the rent of the trader state
+    let trader_state_rent = env.lamports(&keys.trader_state(&wallet)).await;
+    assert_eq!(
+        before - env.lamports(&wallet).await,
+        sol_in + trader_state_rent
+    );
+    assert!(env.balance(&base_ata).await > base_before);
+    assert_eq!(env.balance(&wsol_ata).await, held);
+
+    // MOJO in, SOL out
+    let mojo_in = 5_000_000;
+    let quote: SwapQuote = env
+        .view(instructions::quote_swap(
+            &keys,
+            args::QuoteSwap {
+                amount_in: mojo_in,
+                is_base_input: true,
+            },
+        ))
+        .await
+        .unwrap();
+    // Lamports sent to the temporary account's address don't block it
+    let griefed = Rent::default().minimum_balance(0);
+    let funding = system_instruction::transfer(&wallet, &native_sol_account, griefed);
+    env.send(&[funding], &[&admin]).await.unwrap();
+    let before = env.lamports(&wallet).await;
+    env.send(&[swap_sol(mojo_in, true)], &[&admin])
+        .await
+        .unwrap();
+    assert_eq!(
+        env.lamports(&wallet).await - before,
+        quote.amount_received + griefed
+    );
+    assert_eq!(env.balance(&wsol_ata).await, held);
+    assert!(env.account_data(&native_sol_account).await.is_none());
+
+    // Withdraw everything but the locked liquidity back to native SOL
+    let pair = env.pair(&keys).await;
+    let lp_amount = pair.total_liquidity - MINIMUM_LIQUIDITY;
+    let before = env.lamports(&wallet).await;
+    let remove_liquidity = instructions::remove_liquidity_sol(
+        &keys,
+        &wallet,
+        args::RemoveLiquiditySol {
+            lp_amount,
+            min_base: 0,
+            min_paired: 0,
+            deadline: None,
+        },
+    );
+    env.send(&[remove_liquidity], &[&admin]).await.unwrap();
+    assert_eq!(
+        env.lamports(&wallet).await - before,
+        pair.paired_reserve * lp_amount / pair.total_liquidity
+    );
+    assert_eq!(env.balance(&wsol_ata).await, held);
+    assert!(env.account_data(&native_sol_account).await.is_none());
+}
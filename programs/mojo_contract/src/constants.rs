@@ -4,5 +4,37 @@ use anchor_lang::prelude::*;
 pub const SEED: &str = "anchor";
 
 #[constant]
-pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+pub const MAX_LP_FEE_RATE: u16 = 1_000; // 10% in basis points
+
+#[constant]
+pub const MAX_PROTOCOL_FEE_RATE: u16 = 200; // 2% in basis points
+
+#[constant]
+pub const DEFAULT_FEE_CHANGE_DELAY: i64 = 86_400; // seconds
+
+/// Shortest notice a fee change can be given. Without a floor the admin
+/// could zero the delay and propose and execute a change in one transaction.
+#[constant]
+pub const MIN_FEE_CHANGE_DELAY: i64 = 3_600; // seconds
+
+#[constant]
+pub const DEFAULT_OBSERVATION_CAPACITY: u16 = 8;
+
+/// Largest observation buffer `grow_observations` allows, about 192 KB.
+/// Swaps only touch one slot, but every swap on the pair still loads the
+/// whole account.
+#[constant]
+pub const MAX_OBSERVATION_CAPACITY: u16 = 4_096;
+
+#[constant]
+pub const DEFAULT_OBSERVATION_INTERVAL: i64 = 60; // seconds
+
+/// LP locked forever by the first deposit into a pair. It is counted in
+/// `Pair::total_liquidity` but never minted, so a pool can never be emptied
+/// and the first depositor cannot cheaply inflate the LP share price.
+#[constant]
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+#[constant]
+pub const WSOL_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 
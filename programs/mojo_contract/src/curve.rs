@@ -0,0 +1,720 @@
+//! Constant-product curve math shared by every instruction.
+//!
+//! Everything here is a pure function over `u64`/`u128` so it can be unit
+//! tested on the host. Each function rounds in the pool's favour: amounts paid
+//! out of the pool round down and amounts paid into the pool round up, so the
+//! product of the reserves never decreases.
+
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, MINIMUM_LIQUIDITY};
+
+/// Basis point denominator used for all fee rates.
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Fee of `fee_rate` basis points on `amount`. Rounds down.
+pub fn fee_amount(amount: u64, fee_rate: u16) -> Result<u64> {
+    Ok(amount
+        .checked_mul(fee_rate as u64)
+        .ok_or(AmmError::MathOverflow)?
+        .checked_div(BPS_DENOMINATOR)
+        .ok_or(AmmError::MathOverflow)?)
+}
+
+/// What is left of `amount` after `fee_amount` is taken. Rounds up.
+pub fn after_fee(amount: u64, fee_rate: u16) -> Result<u64> {
+    Ok(amount
+        .checked_sub(fee_amount(amount, fee_rate)?)
+        .ok_or(AmmError::MathOverflow)?)
+}
+
+/// Gross amount that still leaves at least `net` after `fee_amount` is
+/// taken: `net * 10_000 / (10_000 - fee_rate)`. Rounds up.
+pub fn gross_up(net: u64, fee_rate: u16) -> Result<u64> {
+    let denominator = (BPS_DENOMINATOR as u128)
+        .checked_sub(fee_rate as u128)
+        .filter(|d| *d > 0)
+        .ok_or(AmmError::InvalidFeeConfig)?;
+
+    let gross = (net as u128)
+        .checked_mul(BPS_DENOMINATOR as u128)
+        .ok_or(AmmError::MathOverflow)?
+        .div_ceil(denominator);
+
+    Ok(u64::try_from(gross).map_err(|_| AmmError::MathOverflow)?)
+}
+
+/// Output released by the curve for `amount_in`: `y * dx / (x + dx)`.
+/// Rounds down.
+pub fn get_amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
+    require!(
+        reserve_in > 0 && reserve_out > 0,
+        AmmError::InsufficientLiquidity
+    );
+
+    let numerator = (reserve_out as u128)
+        .checked_mul(amount_in as u128)
+        .ok_or(AmmError::MathOverflow)?;
+
+    let denominator = (reserve_in as u128)
+        .checked_add(amount_in as u128)
+        .ok_or(AmmError::MathOverflow)?;
+
+    Ok((numerator / denominator) as u64)
+}
+
+/// Input the curve needs to release `amount_out`: `x * dy / (y - dy)`.
+/// Rounds up.
+pub fn get_amount_in(amount_out: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
+    require!(
+        reserve_in > 0 && amount_out < reserve_out,
+        AmmError::InsufficientLiquidity
+    );
+
+    let numerator = (reserve_in as u128)
+        .checked_mul(amount_out as u128)
+        .ok_or(AmmError::MathOverflow)?;
+
+    let denominator = (reserve_out - amount_out) as u128;
+
+    Ok(u64::try_from(numerator.div_ceil(denominator)).map_err(|_| AmmError::MathOverflow)?)
+}
+
+/// LP tokens minted to the depositor. The first deposit mints
+/// `sqrt(base * paired) - MINIMUM_LIQUIDITY`, the remainder being locked in
+/// the pool; later deposits mint the smaller of the two proportional shares.
+/// Rounds down.
+pub fn lp_to_mint(
+    base_amount: u64,
+    paired_amount: u64,
+    base_reserve: u64,
+    paired_reserve: u64,
+    total_lp: u64,
+) -> Result<u64> {
+    if total_lp == 0 {
+        let product = (base_amount as u128)
+            .checked_mul(paired_amount as u128)
+            .ok_or(AmmError::MathOverflow)?;
+        let liquidity = u64::try_from(sqrt(product)).map_err(|_| AmmError::MathOverflow)?;
+        return Ok(liquidity
+            .checked_sub(MINIMUM_LIQUIDITY)
+            .filter(|minted| *minted > 0)
+            .ok_or(AmmError::InsufficientLiquidityMinted)?);
+    }
+
+    let lp_from_base = (base_amount as u128)
+        .checked_mul(total_lp as u128)
+        .ok_or(AmmError::MathOverflow)?
+        .checked_div(base_reserve as u128)
+        .ok_or(AmmError::MathOverflow)?;
+    let lp_from_paired = (paired_amount as u128)
+        .checked_mul(total_lp as u128)
+        .ok_or(AmmError::MathOverflow)?
+        .checked_div(paired_reserve as u128)
+        .ok_or(AmmError::MathOverflow)?;
+
+    Ok(u64::try_from(lp_from_base.min(lp_from_paired)).map_err(|_| AmmError::MathOverflow)?)
+}
+
+/// Amounts to deposit out of `base_desired`/`paired_desired` so that they
+/// match the current reserve ratio. One side is taken in full and the other
+/// is scaled down to `amount * reserve_other / reserve`, rounding down; an
+/// empty pool takes both in full. Fails with `SlippageExceeded` when the
+/// scaled side falls below its minimum.
+pub fn optimal_deposit(
+    base_desired: u64,
+    paired_desired: u64,
+    base_min: u64,
+    paired_min: u64,
+    base_reserve: u64,
+    paired_reserve: u64,
+) -> Result<(u64, u64)> {
+    if base_reserve == 0 && paired_reserve == 0 {
+        return Ok((base_desired, paired_desired));
+    }
+    require!(
+        base_reserve > 0 && paired_reserve > 0,
+        AmmError::InsufficientLiquidity
+    );
+
+    let paired_optimal = quote(base_desired, base_reserve, paired_reserve)?;
+    if paired_optimal <= paired_desired {
+        require!(paired_optimal >= paired_min, AmmError::SlippageExceeded);
+        return Ok((base_desired, paired_optimal));
+    }
+
+    // base_optimal <= base_desired whenever paired_optimal > paired_desired
+    let base_optimal = quote(paired_desired, paired_reserve, base_reserve)?;
+    require!(base_optimal >= base_min, AmmError::SlippageExceeded);
+    Ok((base_optimal, paired_desired))
+}
+
+/// `amount` of one reserve priced in the other: `amount * reserve_other /
+/// reserve`. Rounds down.
+fn quote(amount: u64, reserve: u64, reserve_other: u64) -> Result<u64> {
+    let value = (amount as u128)
+        .checked_mul(reserve_other as u128)
+        .ok_or(AmmError::MathOverflow)?
+        / reserve as u128;
+
+    Ok(u64::try_from(value).map_err(|_| AmmError::MathOverflow)?)
+}
+
+/// Base and paired amounts returned for burning `lp_amount`. Rounds down.
+pub fn lp_to_burn_amounts(
+    lp_amount: u64,
+    base_reserve: u64,
+    paired_reserve: u64,
+    total_lp: u64,
+) -> Result<(u64, u64)> {
+    require!(lp_amount <= total_lp, AmmError::InsufficientLiquidity);
+
+    let base_amount = (lp_amount as u128)
+        .checked_mul(base_reserve as u128)
+        .ok_or(AmmError::MathOverflow)?
+        .checked_div(total_lp as u128)
+        .ok_or(AmmError::MathOverflow)? as u64;
+    let paired_amount = (lp_amount as u128)
+        .checked_mul(paired_reserve as u128)
+        .ok_or(AmmError::MathOverflow)?
+        .checked_div(total_lp as u128)
+        .ok_or(AmmError::MathOverflow)? as u64;
+
+    Ok((base_amount, paired_amount))
+}
+
+/// Part of `amount_in` to swap before depositing the rest alongside the swap
+/// output, so that both sides match the reserve ratio after the swap.
+///
+/// The LP fee is taken from the input and stays in the pool. The protocol fee
+/// leaves the pool, taken from the input when `protocol_fee_on_input` and
+/// from the output otherwise. Transfer fees of `input_transfer_fee_rate` on
+/// the swap input and `output_transfer_fee_rate` on the swap output shrink
+/// what reaches the pool and the user alike; the deposit is matched on what
+/// the user sends, so the rest of the input is not netted. With `q` the
+/// share of the input that stays in the pool and `g` the share that is
+/// exchanged after all fees, this solves `q g s^2 + r (1 + g) s - a r = 0`
+/// for `s`. Rounds down.
+pub fn zap_swap_amount(
+    amount_in: u64,
+    reserve_in: u64,
+    lp_fee_rate: u16,
+    protocol_fee_rate: u16,
+    protocol_fee_on_input: bool,
+    input_transfer_fee_rate: u16,
+    output_transfer_fee_rate: u16,
+) -> Result<u64> {
+    require!(reserve_in > 0, AmmError::InsufficientLiquidity);
+
+    let denominator = BPS_DENOMINATOR as u128;
+    let kept = |fee_rate: u16| {
+        denominator
+            .checked_sub(fee_rate as u128)
+            .filter(|kept| *kept > 0)
+            .ok_or(AmmError::InvalidFeeConfig)
+    };
+    // q and g scaled by D and D^2
+    let arrived = kept(input_transfer_fee_rate)?;
+    let exchanged =
+        kept(lp_fee_rate)? * kept(protocol_fee_rate)? * arrived * kept(output_transfer_fee_rate)?
+            / (denominator * denominator);
+    let retained = if protocol_fee_on_input {
+        kept(protocol_fee_rate)?
+    } else {
+        denominator
+    } * arrived
+        / denominator;
+    require!(retained > 0 && exchanged > 0, AmmError::InvalidFeeConfig);
+    let reserve_in = reserve_in as u128;
+
+    // Scaled by D^3: b = r D (D^2 + G), and the discriminant is
+    // D^2 r (r (D^2 + G)^2 + 4 Q G D a)
+    let linear = reserve_in
+        .checked_mul(denominator * denominator + exchanged)
+        .ok_or(AmmError::MathOverflow)?;
+    let discriminant_factor = linear
+        .checked_mul(denominator * denominator + exchanged)
+        .and_then(|term| {
+            (amount_in as u128)
+                .checked_mul(4 * retained * exchanged * denominator)
+                .and_then(|other| term.checked_add(other))
+        })
+        .ok_or(AmmError::MathOverflow)?;
+
+    let swap_amount = sqrt_product(reserve_in, discriminant_factor)
+        .saturating_sub(linear)
+        .checked_mul(denominator)
+        .ok_or(AmmError::MathOverflow)?
+        / (2 * retained * exchanged);
+
+    Ok(u64::try_from(swap_amount)
+        .unwrap_or(u64::MAX)
+        .min(amount_in))
+}
+
+/// Integer square root. Rounds down.
+pub fn sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    // Babylonian method
+    let mut x0 = value / 2 + 1;
+    let mut x1 = (x0 + value / x0) / 2;
+    while x1 < x0 {
+        x0 = x1;
+        x1 = (x0 + value / x0) / 2;
+    }
+    x0
+}
+
+/// `sqrt(a * b)` for products that may not fit in a `u128`. Exact when the
+/// product fits; otherwise the larger factor drops its low bits first, which
+/// only ever rounds the result down.
+fn sqrt_product(a: u128, b: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return sqrt(product);
+    }
+
+    let (small, large) = if a < b { (a, b) } else { (b, a) };
+    let bits = 256 - small.leading_zeros() - large.leading_zeros();
+    // Shift by an even number of bits so half of it can be restored exactly
+    let shift = (bits - 128).next_multiple_of(2);
+    sqrt(small * large.checked_shr(shift).unwrap_or(0)) << (shift / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn k(x: u64, y: u64) -> u128 {
+        x as u128 * y as u128
+    }
+
+    #[test]
+    fn sqrt_small_values() {
+        let expected = [0, 1, 1, 1, 2, 2, 2, 2, 2, 3, 3];
+        for (value, root) in expected.iter().enumerate() {
+            assert_eq!(sqrt(value as u128), *root, "sqrt({value})");
+        }
+        assert_eq!(sqrt(u128::MAX), u64::MAX as u128);
+    }
+
+    #[test]
+    fn fee_rounds_down() {
+        assert_eq!(fee_amount(10_000, 30).unwrap(), 30);
+        assert_eq!(fee_amount(333, 30).unwrap(), 0);
+        assert_eq!(after_fee(333, 30).unwrap(), 333);
+        assert_eq!(fee_amount(u64::MAX, 0).unwrap(), 0);
+        assert!(fee_amount(u64::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn gross_up_rejects_full_fee() {
+        assert_eq!(
+            gross_up(1, 10_000).unwrap_err(),
+            AmmError::InvalidFeeConfig.into()
+        );
+        assert_eq!(gross_up(0, 250).unwrap(), 0);
+        assert_eq!(gross_up(9_750, 250).unwrap(), 10_000);
+        assert_eq!(gross_up(9_751, 250).unwrap(), 10_002);
+    }
+
+    #[test]
+    fn amount_out_rounds_down() {
+        // 10 * 1 / 11 = 0.909..
+        assert_eq!(get_amount_out(1, 10, 10).unwrap(), 0);
+        assert_eq!(get_amount_out(10, 10, 10).unwrap(), 5);
+        assert_eq!(get_amount_out(0, 10, 10).unwrap(), 0);
+    }
+
+    #[test]
+    fn amount_out_needs_liquidity() {
+        assert_eq!(
+            get_amount_out(1, 0, 10).unwrap_err(),
+            AmmError::InsufficientLiquidity.into()
+        );
+        assert_eq!(
+            get_amount_out(1, 10, 0).unwrap_err(),
+            AmmError::InsufficientLiquidity.into()
+        );
+    }
+
+    #[test]
+    fn amount_in_rounds_up() {
+        // 10 * 1 / 9 = 1.11..
+        assert_eq!(get_amount_in(1, 10, 10).unwrap(), 2);
+        assert_eq!(get_amount_in(5, 10, 10).unwrap(), 10);
+        assert_eq!(get_amount_in(0, 10, 10).unwrap(), 0);
+    }
+
+    #[test]
+    fn amount_in_cannot_drain_pool() {
+        assert_eq!(
+            get_amount_in(10, 10, 10).unwrap_err(),
+            AmmError::InsufficientLiquidity.into()
+        );
+        assert_eq!(
+            get_amount_in(11, 10, 10).unwrap_err(),
+            AmmError::InsufficientLiquidity.into()
+        );
+    }
+
+    #[test]
+    fn amount_in_overflow_is_an_error() {
+        assert_eq!(
+            get_amount_in(u64::MAX - 1, u64::MAX, u64::MAX).unwrap_err(),
+            AmmError::MathOverflow.into()
+        );
+    }
+
+    #[test]
+    fn first_deposit_mints_geometric_mean_less_locked() {
+        assert_eq!(lp_to_mint(10_000, 40_000, 0, 0, 0).unwrap(), 19_000);
+        assert_eq!(
+            lp_to_mint(u64::MAX, u64::MAX, 0, 0, 0).unwrap(),
+            u64::MAX - MINIMUM_LIQUIDITY
+        );
+    }
+
+    #[test]
+    fn first_deposit_must_exceed_locked_liquidity() {
+        assert_eq!(
+            lp_to_mint(1, 1, 0, 0, 0).unwrap_err(),
+            AmmError::InsufficientLiquidityMinted.into()
+        );
+        assert_eq!(
+            lp_to_mint(1_000, 1_000, 0, 0, 0).unwrap_err(),
+            AmmError::InsufficientLiquidityMinted.into()
+        );
+        assert_eq!(lp_to_mint(1_001, 1_001, 0, 0, 0).unwrap(), 1);
+    }
+
+    #[test]
+    fn later_deposit_mints_smaller_share() {
+        // Pool 1000/2000 with 1000 LP; depositing 100/100 only counts as 50
+        assert_eq!(lp_to_mint(100, 100, 1_000, 2_000, 1_000).unwrap(), 50);
+        assert_eq!(lp_to_mint(100, 200, 1_000, 2_000, 1_000).unwrap(), 100);
+    }
+
+    #[test]
+    fn optimal_deposit_scales_the_excess_side() {
+        // Pool 1000/2000: 100 base needs 200 paired, 100 paired needs 50 base
+        assert_eq!(
+            optimal_deposit(100, 500, 0, 0, 1_000, 2_000).unwrap(),
+            (100, 200)
+        );
+        assert_eq!(
+            optimal_deposit(500, 100, 0, 0, 1_000, 2_000).unwrap(),
+            (50, 100)
+        );
+        assert_eq!(
+            optimal_deposit(7, 9, 7, 9, 0, 0).unwrap(),
+            (7, 9),
+            "an empty pool takes both sides in full"
+        );
+    }
+
+    #[test]
+    fn optimal_deposit_enforces_minimums() {
+        assert_eq!(
+            optimal_deposit(100, 500, 0, 201, 1_000, 2_000).unwrap_err(),
+            AmmError::SlippageExceeded.into()
+        );
+        assert_eq!(
+            optimal_deposit(500, 100, 51, 0, 1_000, 2_000).unwrap_err(),
+            AmmError::SlippageExceeded.into()
+        );
+        assert_eq!(
+            optimal_deposit(500, 100, 50, 100, 1_000, 2_000).unwrap(),
+            (50, 100)
+        );
+    }
+
+    #[test]
+    fn zap_swap_amount_matches_closed_form() {
+        // Without fees s = sqrt(r (r + a)) - r
+        assert_eq!(
+            zap_swap_amount(3_000, 1_000, 0, 0, true, 0, 0).unwrap(),
+            1_000
+        );
+        assert_eq!(zap_swap_amount(0, 1_000, 30, 30, true, 0, 0).unwrap(), 0);
+        // A fee pushes the swap a little past half of the input
+        assert_eq!(
+            zap_swap_amount(1_000_000, 1 << 40, 30, 0, true, 0, 0).unwrap(),
+            500_751
+        );
+        // An LP fee and a protocol fee on the output exchange alike
+        assert_eq!(
+            zap_swap_amount(1_000_000, 1 << 40, 30, 0, true, 0, 0).unwrap(),
+            zap_swap_amount(1_000_000, 1 << 40, 0, 30, false, 0, 0).unwrap(),
+        );
+        // A transfer fee on either side means swapping more to make up for it
+        assert!(
+            zap_swap_amount(1_000_000, 1 << 40, 30, 0, true, 0, 100).unwrap()
+                > zap_swap_amount(1_000_000, 1 << 40, 30, 0, true, 0, 0).unwrap()
+        );
+        assert!(
+            zap_swap_amount(1_000_000, 1 << 40, 30, 0, true, 100, 0).unwrap()
+                > zap_swap_amount(1_000_000, 1 << 40, 30, 0, true, 0, 0).unwrap()
+        );
+        assert_eq!(
+            zap_swap_amount(1, 0, 30, 0, true, 0, 0).unwrap_err(),
+            AmmError::InsufficientLiquidity.into()
+        );
+    }
+
+    #[test]
+    fn zap_swap_amount_handles_wide_reserves() {
+        let swap_amount = zap_swap_amount(u64::MAX, u64::MAX, 30, 0, true, 0, 0).unwrap();
+        // Equal input and reserve: s = r (sqrt((2 - f)^2 + 4 (1 - f)) - (2 - f)) / (2 (1 - f))
+        let expected = 0.414_835_95_f64 * u64::MAX as f64;
+        assert!((swap_amount as f64 - expected).abs() / expected < 1e-5);
+    }
+
+    #[test]
+    fn sqrt_product_beyond_u128() {
+        assert_eq!(sqrt_product(1 << 100, 1 << 100), 1 << 100);
+        assert_eq!(sqrt_product(u128::MAX, 1), sqrt(u128::MAX));
+        assert_eq!(
+            sqrt_product(u64::MAX as u128, u64::MAX as u128),
+            u64::MAX as u128
+        );
+    }
+
+    #[test]
+    fn burn_amounts_are_proportional() {
+        assert_eq!(
+            lp_to_burn_amounts(250, 1_000, 2_000, 1_000).unwrap(),
+            (250, 500)
+        );
+        assert_eq!(lp_to_burn_amounts(1, 2, 3, 4).unwrap(), (0, 0));
+        assert_eq!(
+            lp_to_burn_amounts(5, 10, 10, 4).unwrap_err(),
+            AmmError::InsufficientLiquidity.into()
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn sqrt_is_floor(value in any::<u128>()) {
+            let root = sqrt(value);
+            prop_assert!(root * root <= value);
+            let next = root + 1;
+            prop_assert!(next.checked_mul(next).is_none_or(|sq| sq > value));
+        }
+
+        #[test]
+        fn gross_up_covers_net(net in 0u64..1 << 48, fee_rate in 0u16..5_000) {
+            let gross = gross_up(net, fee_rate).unwrap();
+            prop_assert!(after_fee(gross, fee_rate).unwrap() >= net);
+            // Never more than one unit above the exact real-valued gross
+            let exact = net as u128 * 10_000 / (10_000 - fee_rate as u128);
+            prop_assert!(gross as u128 <= exact + 1);
+        }
+
+        #[test]
+        fn swap_never_decreases_k(
+            reserve_in in 1u64..=u64::MAX,
+            reserve_out in 1u64..=u64::MAX,
+            amount_in in any::<u64>(),
+        ) {
+            let amount_out = get_amount_out(amount_in, reserve_in, reserve_out).unwrap();
+            prop_assert!(amount_out < reserve_out);
+            if let Some(new_in) = reserve_in.checked_add(amount_in) {
+                let new_out = reserve_out - amount_out;
+                prop_assert!(
+                    k(new_in, new_out)
+                        >= k(reserve_in, reserve_out)
+                );
+            }
+        }
+
+        #[test]
+        fn exact_out_never_decreases_k(
+            reserve_in in 1u64..1 << 48,
+            reserve_out in 2u64..1 << 48,
+            out_fraction in 1u64..1_000,
+        ) {
+            let amount_out = (reserve_out as u128 * out_fraction as u128 / 1_000) as u64;
+            let amount_in = get_amount_in(amount_out, reserve_in, reserve_out).unwrap();
+            prop_assert!(
+                k(reserve_in + amount_in, reserve_out - amount_out) >= k(reserve_in, reserve_out)
+            );
+            // Paying the quoted input through the exact-in path covers the output
+            prop_assert!(get_amount_out(amount_in, reserve_in, reserve_out).unwrap() >= amount_out);
+        }
+
+        #[test]
+        fn amount_out_is_monotonic(
+            reserve_in in 1u64..1 << 60,
+            reserve_out in 1u64..1 << 60,
+            a in 0u64..1 << 60,
+            b in 0u64..1 << 60,
+        ) {
+            let (small, large) = if a <= b { (a, b) } else { (b, a) };
+            prop_assert!(
+                get_amount_out(small, reserve_in, reserve_out).unwrap()
+                    <= get_amount_out(large, reserve_in, reserve_out).unwrap()
+            );
+        }
+
+        #[test]
+        fn round_trip_swap_extracts_nothing(
+            reserve_in in 1u64..1 << 48,
+            reserve_out in 1u64..1 << 48,
+            amount_in in 0u64..1 << 48,
+        ) {
+            let amount_out = get_amount_out(amount_in, reserve_in, reserve_out).unwrap();
+            let back = get_amount_out(
+                amount_out,
+                reserve_out - amount_out,
+                reserve_in + amount_in,
+            )
+            .unwrap();
+            prop_assert!(back <= amount_in);
+        }
+
+        #[test]
+        fn deposit_then_withdraw_extracts_nothing(
+            base_reserve in 1u64..1 << 40,
+            paired_reserve in 1u64..1 << 40,
+            total_lp in 1u64..1 << 40,
+            base_amount in 0u64..1 << 40,
+            paired_amount in 0u64..1 << 40,
+        ) {
+            let minted = lp_to_mint(
+                base_amount,
+                paired_amount,
+                base_reserve,
+                paired_reserve,
+                total_lp,
+            )
+            .unwrap();
+            let (base_out, paired_out) = lp_to_burn_amounts(
+                minted,
+                base_reserve + base_amount,
+                paired_reserve + paired_amount,
+                total_lp + minted,
+            )
+            .unwrap();
+            prop_assert!(base_out <= base_amount);
+            prop_assert!(paired_out <= paired_amount);
+        }
+
+        #[test]
+        fn optimal_deposit_stays_within_desired(
+            base_reserve in 1u64..1 << 40,
+            paired_reserve in 1u64..1 << 40,
+            base_desired in 0u64..1 << 40,
+            paired_desired in 0u64..1 << 40,
+        ) {
+            let (base_amount, paired_amount) = optimal_deposit(
+                base_desired,
+                paired_desired,
+                0,
+                0,
+                base_reserve,
+                paired_reserve,
+            )
+            .unwrap();
+            prop_assert!(base_amount <= base_desired);
+            prop_assert!(paired_amount <= paired_desired);
+            prop_assert!(base_amount == base_desired || paired_amount == paired_desired);
+            // Neither side exceeds its share at the current price
+            prop_assert!(
+                base_amount as u128 * paired_reserve as u128
+                    <= (paired_amount as u128 + 1) * base_reserve as u128
+            );
+            prop_assert!(
+                paired_amount as u128 * base_reserve as u128
+                    <= (base_amount as u128 + 1) * paired_reserve as u128
+            );
+        }
+
+        #[test]
+        fn zap_leaves_only_dust(
+            reserve_in in 1u64 << 20..1 << 48,
+            reserve_out in 1u64 << 20..1 << 48,
+            amount_in in 1u64..1 << 40,
+            lp_fee_rate in 0u16..1_000,
+            protocol_fee_rate in 0u16..1_000,
+            protocol_fee_on_input in any::<bool>(),
+            input_transfer_fee_rate in 0u16..1_000,
+            output_transfer_fee_rate in 0u16..1_000,
+        ) {
+            let swap_amount = zap_swap_amount(
+                amount_in,
+                reserve_in,
+                lp_fee_rate,
+                protocol_fee_rate,
+                protocol_fee_on_input,
+                input_transfer_fee_rate,
+                output_transfer_fee_rate,
+            )
+            .unwrap();
+            prop_assert!(swap_amount <= amount_in);
+
+            // Swap what arrives as `quote_exact_in` does for either fee
+            // placement
+            let arrived = after_fee(swap_amount, input_transfer_fee_rate).unwrap();
+            let (retained, amount_out, gross_amount_out) = if protocol_fee_on_input {
+                let retained = after_fee(arrived, protocol_fee_rate).unwrap();
+                let curve_in = after_fee(retained, lp_fee_rate).unwrap();
+                let amount_out = get_amount_out(curve_in, reserve_in, reserve_out).unwrap();
+                (retained, amount_out, amount_out)
+            } else {
+                let curve_in = after_fee(arrived, lp_fee_rate).unwrap();
+                let gross = get_amount_out(curve_in, reserve_in, reserve_out).unwrap();
+                (arrived, after_fee(gross, protocol_fee_rate).unwrap(), gross)
+            };
+            let new_reserve_in = reserve_in + retained;
+            let new_reserve_out = reserve_out - gross_amount_out;
+            let received = after_fee(amount_out, output_transfer_fee_rate).unwrap();
+            let (deposit_in, _) = optimal_deposit(
+                amount_in - swap_amount,
+                received,
+                0,
+                0,
+                new_reserve_in,
+                new_reserve_out,
+            )
+            .unwrap();
+
+            // What is left over is worth a few output units, from rounding.
+            // Rounding the output also moves the price the rest is matched
+            // at, which counts once the output dwarfs what the pool keeps
+            let dust = (amount_in - swap_amount - deposit_in) as u128;
+            let unit = (new_reserve_in as u128).div_ceil(new_reserve_out as u128);
+            let units = 3 * (1 + received as u128 / new_reserve_out as u128);
+            prop_assert!(dust <= units * unit + 2, "dust {} unit {}", dust, unit);
+        }
+
+        #[test]
+        fn first_deposit_withdraws_at_most_deposit(
+            base_amount in 1u64 << 10..1 << 48,
+            paired_amount in 1u64 << 10..1 << 48,
+        ) {
+            let minted = lp_to_mint(base_amount, paired_amount, 0, 0, 0).unwrap();
+            let total_lp = minted + MINIMUM_LIQUIDITY;
+            let (base_out, paired_out) =
+                lp_to_burn_amounts(minted, base_amount, paired_amount, total_lp).unwrap();
+            prop_assert!(base_out <= base_amount);
+            prop_assert!(paired_out <= paired_amount);
+        }
+
+        #[test]
+        fn burn_never_exceeds_reserves(
+            base_reserve in any::<u64>(),
+            paired_reserve in any::<u64>(),
+            total_lp in 1u64..=u64::MAX,
+            lp_fraction in 0u64..=1_000,
+        ) {
+            let lp_amount = (total_lp as u128 * lp_fraction as u128 / 1_000) as u64;
+            let (base_out, paired_out) =
+                lp_to_burn_amounts(lp_amount, base_reserve, paired_reserve, total_lp).unwrap();
+            prop_assert!(base_out <= base_reserve);
+            prop_assert!(paired_out <= paired_reserve);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Transaction deadlines.
+//!
+//! Trading instructions take an optional unix timestamp after which they
+//! refuse to execute, so a transaction that lands late cannot trade at a
+//! price the user no longer expects.
+
+use anchor_lang::prelude::*;
+
+use crate::error::AmmError;
+
+/// Fails with `TransactionExpired` once the cluster clock is past `deadline`.
+pub fn check_deadline(deadline: Option<i64>) -> Result<()> {
+    if let Some(deadline) = deadline {
+        require!(
+            Clock::get()?.unix_timestamp <= deadline,
+            AmmError::TransactionExpired
+        );
+    }
+    Ok(())
+}
@@ -49,4 +49,22 @@ pub enum AmmError {
     MathOverflow,
     #[msg("Invalid fee collector mint")]
     InvalidFeeCollectorMint,
+    #[msg("Required input exceeds the maximum amount in")]
+    ExcessiveInputAmount,
+    #[msg("TWAP window must be positive")]
+    InvalidTwapWindow,
+    #[msg("Pool invariant violated")]
+    InvariantViolated,
+    #[msg("Pair is not paired with wrapped SOL")]
+    NotNativeSolPair,
+    #[msg("Transaction deadline has passed")]
+    TransactionExpired,
+    #[msg("Fee change is not effective yet")]
+    FeeChangeNotReady,
+    #[msg("Observation slot has not been written")]
+    ObservationNotFound,
+    #[msg("Fee change delay is below the minimum")]
+    FeeChangeDelayTooShort,
+    #[msg("Observation capacity exceeds the maximum")]
+    ObservationCapacityTooLarge,
 }
@@ -0,0 +1,55 @@
+//! Event emission.
+//!
+//! Events are written to the program logs with `emit!` by default. Logs can
+//! be truncated, so with the `event-cpi` feature every event is instead sent
+//! as a self-CPI that indexers read back from the inner instructions. The
+//! accounts structs that emit carry `#[event_cpi]` in that mode, which adds
+//! the `event_authority` and `program` accounts the CPI needs.
+
+#[cfg(feature = "event-cpi")]
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    },
+};
+
+/// Seed of the PDA that signs event CPIs, as used by `#[event_cpi]`.
+#[cfg(feature = "event-cpi")]
+const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// Emits `$event` from a method of an accounts struct `$accounts`.
+macro_rules! emit_event {
+    ($accounts:expr, $event:expr) => {{
+        #[cfg(feature = "event-cpi")]
+        $crate::events::emit_cpi(&$accounts.event_authority, &$event)?;
+        #[cfg(not(feature = "event-cpi"))]
+        anchor_lang::prelude::emit!($event);
+    }};
+}
+pub(crate) use emit_event;
+
+/// What `emit_cpi!` does, for callers without a `Context` in scope.
+#[cfg(feature = "event-cpi")]
+pub fn emit_cpi<E: anchor_lang::Event>(event_authority: &AccountInfo, event: &E) -> Result<()> {
+    let (_, bump) = Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &crate::ID);
+
+    let data = anchor_lang::event::EVENT_IX_TAG_LE
+        .iter()
+        .copied()
+        .chain(event.data())
+        .collect::<Vec<u8>>();
+    let instruction = Instruction::new_with_bytes(
+        crate::ID,
+        &data,
+        vec![AccountMeta::new_readonly(event_authority.key(), true)],
+    );
+
+    invoke_signed(
+        &instruction,
+        std::slice::from_ref(event_authority),
+        &[&[EVENT_AUTHORITY_SEED, &[bump]]],
+    )?;
+    Ok(())
+}
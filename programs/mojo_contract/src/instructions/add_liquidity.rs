@@ -1,13 +1,28 @@
-use crate::{error::AmmError, state::Pair};
+use crate::{
+    curve::{lp_to_mint, optimal_deposit},
+    error::AmmError,
+    events::emit_event,
+    native_sol::{native_sol, native_sol_out, require_native_sol_pair},
+    state::{LiquidityAdded, Pair},
+    transfer_fee::amount_after_transfer_fee,
+    transfer_hook::transfer_checked,
+    MINIMUM_LIQUIDITY,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{mint_to, transfer, MintTo, Transfer},
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
 };
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
-#[instruction(base_amount: u64, paired_amount: u64)]
+#[instruction(
+    base_desired: u64,
+    paired_desired: u64,
+    base_min: u64,
+    paired_min: u64,
+    min_lp_out: u64,
+)]
 pub struct AddLiquidity<'info> {
     /// The user adding liquidity
     #[account(mut)]
@@ -24,6 +39,20 @@ pub struct AddLiquidity<'info> {
     )]
     pub pair: Account<'info, Pair>,
 
+    /// Base token mint (MOJO)
+    #[account(
+        address = pair.base_token_mint,
+        mint::token_program = token_program,
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Paired token mint
+    #[account(
+        address = pair.paired_token_mint,
+        mint::token_program = paired_token_program,
+    )]
+    pub paired_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
     /// Vault holding base token (MOJO)
     #[account(mut, address = pair.base_vault)]
     pub base_vault: Box<InterfaceAccount<'info, TokenAccount>>,
@@ -33,6 +62,7 @@ pub struct AddLiquidity<'info> {
         mut,
         associated_token::mint = pair.base_token_mint,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -40,11 +70,13 @@ pub struct AddLiquidity<'info> {
     #[account(mut, address = pair.paired_vault)]
     pub paired_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// User's paired token ATA
+    /// User's paired token ATA, created on demand for native SOL deposits
     #[account(
-        mut,
-        associated_token::mint = pair.paired_token_mint,
+        init_if_needed,
+        payer = user,
+        associated_token::mint = paired_token_mint,
         associated_token::authority = user,
+        associated_token::token_program = paired_token_program,
     )]
     pub user_paired_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -57,67 +89,217 @@ pub struct AddLiquidity<'info> {
         mut,
         associated_token::mint = pair.lp_mint,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// Token program
+    /// Temporary WSOL account the `*_sol` variants unwrap through, created
+    /// and closed within the instruction. Other instructions leave it out.
+    /// CHECK: never read; `NativeSol::unwrap` creates it at this address
+    #[account(mut, seeds = [b"native-sol", user.key().as_ref()], bump)]
+    pub native_sol_account: Option<UncheckedAccount<'info>>,
+
+    /// Token program of the base token (MOJO) and LP mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the paired token, SPL Token or Token-2022
+    pub paired_token_program: Interface<'info, TokenInterface>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
-    pub token_program: Interface<'info, TokenInterface>,
 }
 
 impl<'info> AddLiquidity<'info> {
-    pub fn add_liquidity(&mut self, base_amount: u64, paired_amount: u64) -> Result<()> {
+    /// See [`LiquidityAccounts::add_liquidity`].
+    pub fn add_liquidity(
+        &mut self,
+        base_desired: u64,
+        paired_desired: u64,
+        base_min: u64,
+        paired_min: u64,
+        min_lp_out: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        LiquidityAccounts {
+            user: &self.user,
+            pair: &mut self.pair,
+            base_token_mint: &self.base_token_mint,
+            paired_token_mint: &self.paired_token_mint,
+            base_vault: &mut self.base_vault,
+            paired_vault: &mut self.paired_vault,
+            user_base_ata: &self.user_base_ata,
+            user_paired_ata: &self.user_paired_ata,
+            lp_mint: &self.lp_mint,
+            user_lp_ata: &self.user_lp_ata,
+            token_program: &self.token_program,
+            paired_token_program: &self.paired_token_program,
+            #[cfg(feature = "event-cpi")]
+            event_authority: &self.event_authority,
+        }
+        .add_liquidity(
+            base_desired,
+            paired_desired,
+            base_min,
+            paired_min,
+            min_lp_out,
+            remaining_accounts,
+        )
+    }
+
+    /// `add_liquidity` for a wrapped-SOL pair, paying the paired side in
+    /// lamports. `paired_desired` is wrapped up front and whatever the
+    /// deposit leaves over is unwrapped afterwards.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_liquidity_sol(
+        &mut self,
+        base_desired: u64,
+        paired_desired: u64,
+        base_min: u64,
+        paired_min: u64,
+        min_lp_out: u64,
+        bumps: &AddLiquidityBumps,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require_native_sol_pair(&self.pair)?;
+        let held = self.user_paired_ata.amount;
+
+        native_sol(
+            &self.user,
+            &self.user_paired_ata,
+            &self.paired_token_mint,
+            &self.native_sol_account,
+            bumps.native_sol_account,
+            &self.paired_token_program,
+            &self.system_program,
+        )?
+        .wrap(paired_desired)?;
+
+        self.add_liquidity(
+            base_desired,
+            paired_desired,
+            base_min,
+            paired_min,
+            min_lp_out,
+            remaining_accounts,
+        )?;
+
+        let left_over = native_sol_out(&mut self.user_paired_ata, held)?;
+        native_sol(
+            &self.user,
+            &self.user_paired_ata,
+            &self.paired_token_mint,
+            &self.native_sol_account,
+            bumps.native_sol_account,
+            &self.paired_token_program,
+            &self.system_program,
+        )?
+        .unwrap(left_over)
+    }
+}
+
+/// The accounts depositing into and withdrawing from one pair read and
+/// write, borrowed from the instruction running it. `AddLiquidity`,
+/// `RemoveLiquidity` and the single-sided liquidity instructions all move
+/// liquidity through here.
+pub struct LiquidityAccounts<'a, 'info> {
+    pub user: &'a Signer<'info>,
+    pub pair: &'a mut Account<'info, Pair>,
+    pub base_token_mint: &'a InterfaceAccount<'info, Mint>,
+    pub paired_token_mint: &'a InterfaceAccount<'info, Mint>,
+    pub base_vault: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub paired_vault: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub user_base_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    pub user_paired_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    pub lp_mint: &'a InterfaceAccount<'info, Mint>,
+    pub user_lp_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub paired_token_program: &'a Interface<'info, TokenInterface>,
+    #[cfg(feature = "event-cpi")]
+    pub event_authority: &'a AccountInfo<'info>,
+}
+
+impl<'info> LiquidityAccounts<'_, 'info> {
+    /// Deposits at most `base_desired`/`paired_desired`, taking only the
+    /// amounts that match the current reserve ratio. The scaled-down side
+    /// must stay at or above its minimum and the LP minted at or above
+    /// `min_lp_out`.
+    ///
+    /// `remaining_accounts` carries the extra accounts of any Token-2022
+    /// transfer hook and is forwarded to both deposits.
+    pub fn add_liquidity(
+        &mut self,
+        base_desired: u64,
+        paired_desired: u64,
+        base_min: u64,
+        paired_min: u64,
+        min_lp_out: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.pair
+            .update_price_cumulatives(Clock::get()?.unix_timestamp)?;
+
         // Load current reserves and total supply
         let base_reserve = self.pair.base_reserve;
         let paired_reserve = self.pair.paired_reserve;
         let total_lp = self.pair.total_liquidity;
+        let k_before = self.pair.k();
+
+        // Only the amounts matching the current ratio leave the user
+        let (base_amount, paired_amount) = optimal_deposit(
+            base_desired,
+            paired_desired,
+            base_min,
+            paired_min,
+            base_reserve,
+            paired_reserve,
+        )?;
+
+        // Only what reaches the vaults is credited to the pool
+        let base_received =
+            amount_after_transfer_fee(&self.base_token_mint.to_account_info(), base_amount)?;
+        let paired_received =
+            amount_after_transfer_fee(&self.paired_token_mint.to_account_info(), paired_amount)?;
 
         // Calculate liquidity to mint
-        let minted_lp = if total_lp == 0 {
-            // Initial liquidity: sqrt(base_amount * paired_amount)
-            let product = (base_amount as u128)
-                .checked_mul(paired_amount as u128)
-                .ok_or(AmmError::MathOverflow)?;
-            let sqrt = integer_sqrt(product);
-            sqrt as u64
-        } else {
-            // Proportional liquidity
-            let lp_from_base = (base_amount as u128)
-                .checked_mul(total_lp as u128)
-                .ok_or(AmmError::MathOverflow)?
-                .checked_div(base_reserve as u128)
-                .ok_or(AmmError::MathOverflow)?;
-            let lp_from_paired = (paired_amount as u128)
-                .checked_mul(total_lp as u128)
-                .ok_or(AmmError::MathOverflow)?
-                .checked_div(paired_reserve as u128)
-                .ok_or(AmmError::MathOverflow)?;
-            let min_lp = std::cmp::min(lp_from_base, lp_from_paired);
-            min_lp as u64
-        };
+        let minted_lp = lp_to_mint(
+            base_received,
+            paired_received,
+            base_reserve,
+            paired_reserve,
+            total_lp,
+        )?;
 
         require!(minted_lp > 0, AmmError::InsufficientLiquidityMinted);
+        require!(minted_lp >= min_lp_out, AmmError::SlippageExceeded);
+
+        // The first deposit locks MINIMUM_LIQUIDITY by counting it in the
+        // total without minting it to anyone
+        let locked_lp = if total_lp == 0 { MINIMUM_LIQUIDITY } else { 0 };
 
         // Transfer base tokens from user to vault
-        let cpi_accounts_base = Transfer {
+        let cpi_accounts_base = TransferChecked {
             from: self.user_base_ata.to_account_info(),
+            mint: self.base_token_mint.to_account_info(),
             to: self.base_vault.to_account_info(),
             authority: self.user.to_account_info(),
         };
         let cpi_program = self.token_program.to_account_info();
-        let cpi_ctx_base = CpiContext::new(cpi_program.clone(), cpi_accounts_base);
-        transfer(cpi_ctx_base, base_amount)?;
+        let cpi_ctx_base = CpiContext::new(cpi_program.clone(), cpi_accounts_base)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked(cpi_ctx_base, base_amount, self.base_token_mint.decimals)?;
 
         // Transfer paired tokens from user to vault
-        let cpi_accounts_paired = Transfer {
+        let cpi_accounts_paired = TransferChecked {
             from: self.user_paired_ata.to_account_info(),
+            mint: self.paired_token_mint.to_account_info(),
             to: self.paired_vault.to_account_info(),
             authority: self.user.to_account_info(),
         };
-        let cpi_ctx_paired = CpiContext::new(cpi_program.clone(), cpi_accounts_paired);
-        transfer(cpi_ctx_paired, paired_amount)?;
+        let cpi_ctx_paired = CpiContext::new(
+            self.paired_token_program.to_account_info(),
+            cpi_accounts_paired,
+        )
+        .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_checked(cpi_ctx_paired, paired_amount, self.paired_token_mint.decimals)?;
 
         // Mint LP tokens to user
         let seeds = &[
@@ -141,30 +323,36 @@ impl<'info> AddLiquidity<'info> {
 
         // Update reserves and total liquidity
         self.pair.base_reserve = base_reserve
-            .checked_add(base_amount)
+            .checked_add(base_received)
             .ok_or(AmmError::MathOverflow)?;
         self.pair.paired_reserve = paired_reserve
-            .checked_add(paired_amount)
+            .checked_add(paired_received)
             .ok_or(AmmError::MathOverflow)?;
         self.pair.total_liquidity = total_lp
             .checked_add(minted_lp)
+            .and_then(|total| total.checked_add(locked_lp))
             .ok_or(AmmError::MathOverflow)?;
 
-        Ok(())
-    }
-}
+        self.base_vault.reload()?;
+        self.paired_vault.reload()?;
+        self.pair.check_invariant(
+            self.base_vault.amount,
+            self.paired_vault.amount,
+            Some(k_before),
+        )?;
 
-/// Integer square root for u128
-fn integer_sqrt(value: u128) -> u128 {
-    if value < 2 {
-        return value;
-    }
-    // Babylonian method
-    let mut x0 = value / 2 + 1;
-    let mut x1 = (x0 + value / x0) / 2;
-    while x1 < x0 {
-        x0 = x1;
-        x1 = (x0 + value / x0) / 2;
+        emit_event!(self, LiquidityAdded {
+            pair: self.pair.key(),
+            user: self.user.key(),
+            base_amount: base_received,
+            paired_amount: paired_received,
+            lp_minted: minted_lp,
+            base_reserve: self.pair.base_reserve,
+            paired_reserve: self.pair.paired_reserve,
+            total_liquidity: self.pair.total_liquidity,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
     }
-    x0
 }
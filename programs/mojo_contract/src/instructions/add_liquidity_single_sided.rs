@@ -0,0 +1,254 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    curve::zap_swap_amount,
+    error::AmmError,
+    instructions::{
+        add_liquidity::LiquidityAccounts,
+        swap::{quote_exact_in_with_transfer_fees, SwapAccounts},
+    },
+    transfer_fee::{mint_transfer_fee_config, TransferFees},
+    Observations, Pair, PlatformState, TraderState,
+};
+
+/// Accounts of `Swap` and `AddLiquidity` together, so a single-sided deposit
+/// can run both in one instruction through `SwapAccounts` and
+/// `LiquidityAccounts`.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct AddLiquiditySingleSided<'info> {
+    /// The platform state contains global configuration
+    #[account(
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        constraint = !platform_state.is_paused @ AmmError::TradingPaused,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    /// The user adding liquidity
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The Pair state for the MOJO/paired token pool
+    #[account(
+        mut,
+        seeds = [b"pair", pair.base_token_mint.as_ref(), pair.paired_token_mint.as_ref()],
+        bump = pair.bump,
+        has_one = base_vault,
+        has_one = paired_vault,
+        has_one = lp_mint,
+        constraint = pair.total_liquidity > 0 @ AmmError::InsufficientLiquidity,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    /// Base token mint (MOJO)
+    #[account(
+        address = pair.base_token_mint,
+        mint::token_program = token_program,
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Paired token mint
+    #[account(
+        address = pair.paired_token_mint,
+        mint::token_program = paired_token_program,
+    )]
+    pub paired_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Vault holding base token (MOJO)
+    #[account(mut, address = pair.base_vault)]
+    pub base_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault holding paired token
+    #[account(mut, address = pair.paired_vault)]
+    pub paired_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User's base token ATA
+    #[account(
+        mut,
+        associated_token::mint = base_token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User's paired token ATA, created on demand for MOJO-only deposits
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = paired_token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = paired_token_program,
+    )]
+    pub user_paired_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// LP token mint for this pool
+    #[account(mut, address = pair.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// User's LP token ATA
+    #[account(
+        mut,
+        associated_token::mint = pair.lp_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Platform fee collector account
+    #[account(
+        mut,
+        address = platform_state.fee_collector,
+    )]
+    pub fee_collector: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Tracks the user's last swap on this pair for the cooldown
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + TraderState::INIT_SPACE,
+        seeds = [b"trader", pair.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub trader_state: Box<Account<'info, TraderState>>,
+
+    /// Price sample ring buffer for this pair
+    #[account(
+        mut,
+        seeds = [b"observations", pair.key().as_ref()],
+        bump = observations.load()?.bump,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+
+    /// Token program of the base token (MOJO) and LP mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the paired token, SPL Token or Token-2022
+    pub paired_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddLiquiditySingleSided<'info> {
+    /// Deposits `amount_in` of one side only. Part of it is swapped against
+    /// the pool, sized by `zap_swap_amount` for the pair's fees and the
+    /// mints' transfer fees, and the swap output is deposited together with
+    /// the rest. Any dust the deposit cannot take at the new ratio stays with
+    /// the user.
+    ///
+    /// The swap counts towards the user's swap cooldown like any other.
+    pub fn add_liquidity_single_sided(
+        &mut self,
+        amount_in: u64,
+        is_base_input: bool,
+        min_lp_out: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(amount_in > 0, AmmError::ZeroAmount);
+
+        let protocol_fee_rate = self.platform_state.protocol_fee_rate;
+        let base_config = mint_transfer_fee_config(&self.base_token_mint.to_account_info())?;
+        let paired_config = mint_transfer_fee_config(&self.paired_token_mint.to_account_info())?;
+        let (reserve_in, input, output) = if is_base_input {
+            (self.pair.base_reserve, &base_config, &paired_config)
+        } else {
+            (self.pair.paired_reserve, &paired_config, &base_config)
+        };
+        let fees = TransferFees {
+            input: input.as_ref(),
+            output: output.as_ref(),
+            epoch: Clock::get()?.epoch,
+        };
+
+        // The protocol fee is always paid in MOJO, so it comes out of the
+        // input only when the input is MOJO
+        let transfer_fee_rate = |config: Option<&TransferFeeConfig>| {
+            config.map_or(0, |config| {
+                u16::from(config.get_epoch_fee(fees.epoch).transfer_fee_basis_points)
+            })
+        };
+        let swap_amount = zap_swap_amount(
+            amount_in,
+            reserve_in,
+            self.pair.lp_fee_rate,
+            protocol_fee_rate,
+            is_base_input,
+            transfer_fee_rate(fees.input),
+            transfer_fee_rate(fees.output),
+        )?;
+        let quote = quote_exact_in_with_transfer_fees(
+            &self.pair,
+            protocol_fee_rate,
+            swap_amount,
+            is_base_input,
+            &fees,
+        )?;
+
+        self.swap_accounts().swap(
+            swap_amount,
+            quote.amount_received,
+            is_base_input,
+            remaining_accounts,
+        )?;
+
+        let remainder = amount_in - swap_amount;
+        let (base_desired, paired_desired) = if is_base_input {
+            (remainder, quote.amount_received)
+        } else {
+            (quote.amount_received, remainder)
+        };
+
+        self.liquidity_accounts().add_liquidity(
+            base_desired,
+            paired_desired,
+            0,
+            0,
+            min_lp_out,
+            remaining_accounts,
+        )
+    }
+
+    fn swap_accounts(&mut self) -> SwapAccounts<'_, 'info> {
+        SwapAccounts {
+            platform_state: &self.platform_state,
+            user: &self.user,
+            pair: &mut self.pair,
+            base_token_mint: &self.base_token_mint,
+            paired_token_mint: &self.paired_token_mint,
+            base_vault: &mut self.base_vault,
+            paired_vault: &mut self.paired_vault,
+            user_base_ata: &self.user_base_ata,
+            user_paired_ata: &self.user_paired_ata,
+            fee_collector: &self.fee_collector,
+            trader_state: &mut self.trader_state,
+            observations: &self.observations,
+            token_program: &self.token_program,
+            paired_token_program: &self.paired_token_program,
+            #[cfg(feature = "event-cpi")]
+            event_authority: &self.event_authority,
+        }
+    }
+
+    fn liquidity_accounts(&mut self) -> LiquidityAccounts<'_, 'info> {
+        LiquidityAccounts {
+            user: &self.user,
+            pair: &mut self.pair,
+            base_token_mint: &self.base_token_mint,
+            paired_token_mint: &self.paired_token_mint,
+            base_vault: &mut self.base_vault,
+            paired_vault: &mut self.paired_vault,
+            user_base_ata: &self.user_base_ata,
+            user_paired_ata: &self.user_paired_ata,
+            lp_mint: &self.lp_mint,
+            user_lp_ata: &self.user_lp_ata,
+            token_program: &self.token_program,
+            paired_token_program: &self.paired_token_program,
+            #[cfg(feature = "event-cpi")]
+            event_authority: &self.event_authority,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError,
+    events::emit_event,
+    state::{FeeChangeCancelled, PendingFeeChange, PlatformState},
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CancelFeeChange<'info> {
+    #[account(
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"pending-fee-change"],
+        bump = pending_fee_change.bump,
+    )]
+    pub pending_fee_change: Account<'info, PendingFeeChange>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+impl<'info> CancelFeeChange<'info> {
+    /// Drops the pending fee change and refunds its account to the admin.
+    pub fn cancel_fee_change(&mut self) -> Result<()> {
+        let new_fee_rate = self.pending_fee_change.new_fee_rate;
+
+        msg!("Protocol fee rate change to {}bps cancelled", new_fee_rate);
+
+        emit_event!(
+            self,
+            FeeChangeCancelled {
+                admin: self.admin.key(),
+                new_fee_rate,
+                timestamp: Clock::get()?.unix_timestamp,
+            }
+        );
+        Ok(())
+    }
+}
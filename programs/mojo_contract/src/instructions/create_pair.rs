@@ -1,12 +1,13 @@
-use crate::state::Pair;
+use crate::state::{Observations, Pair, PairCreated};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::{error::AmmError, PlatformState};
+use crate::{error::AmmError, events::emit_event, PlatformState, DEFAULT_OBSERVATION_CAPACITY, MAX_LP_FEE_RATE};
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct CreatePair<'info> {
     #[account(mut)]
@@ -21,7 +22,9 @@ pub struct CreatePair<'info> {
     )]
     pub pair: Account<'info, Pair>,
 
+    #[account(mint::token_program = token_program)]
     pub base_token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mint::token_program = paired_token_program)]
     pub paired_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
@@ -47,9 +50,19 @@ pub struct CreatePair<'info> {
         payer = creator,
         associated_token::mint = paired_token_mint,
         associated_token::authority = pair,
+        associated_token::token_program = paired_token_program,
     )]
     pub paired_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    #[account(
+        init,
+        payer = creator,
+        space = Observations::space(DEFAULT_OBSERVATION_CAPACITY as usize),
+        seeds = [b"observations", pair.key().as_ref()],
+        bump,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+
     #[account(
         mut,
         seeds = [b"platform-state"],
@@ -58,6 +71,8 @@ pub struct CreatePair<'info> {
     pub platform_state: Account<'info, PlatformState>,
 
     pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the paired token, SPL Token or Token-2022
+    pub paired_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -66,7 +81,8 @@ pub struct CreatePair<'info> {
 impl<'info> CreatePair<'info> {
     pub fn create_pair(
         &mut self,
-        bump: u8,
+        lp_fee_rate: u16,
+        bumps: &CreatePairBumps,
     ) -> Result<()> {
 
         // Check that base token is the platform's base token (MOJO)
@@ -75,6 +91,8 @@ impl<'info> CreatePair<'info> {
             AmmError::InvalidBaseToken
         );
 
+        require!(lp_fee_rate <= MAX_LP_FEE_RATE, AmmError::FeeTooHigh);
+
         // Initialize pair state
         self.pair.set_inner(Pair {
             base_token_mint: self.base_token_mint.key(),
@@ -83,10 +101,30 @@ impl<'info> CreatePair<'info> {
             base_reserve: 0,
             paired_reserve: 0,
             total_liquidity: 0,
-            bump,
+            bump: bumps.pair,
             last_swap_time: Clock::get()?.unix_timestamp,
+            lp_fee_rate,
             base_vault: self.base_vault.key(),
             paired_vault: self.paired_vault.key(),
+            swap_cooldown_seconds: None,
+            base_price_cumulative: 0,
+            paired_price_cumulative: 0,
+        });
+
+        let mut observations = self.observations.load_init()?;
+        observations.pair = self.pair.key();
+        observations.bump = bumps.observations;
+        observations.capacity = DEFAULT_OBSERVATION_CAPACITY;
+        drop(observations);
+
+        emit_event!(self, PairCreated {
+            pair: self.pair.key(),
+            creator: self.creator.key(),
+            base_token_mint: self.base_token_mint.key(),
+            paired_token_mint: self.paired_token_mint.key(),
+            lp_mint: self.lp_mint.key(),
+            lp_fee_rate,
+            timestamp: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError,
+    events::emit_event,
+    state::{FeeRateUpdated, PendingFeeChange, PlatformState},
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ExecuteFeeChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"pending-fee-change"],
+        bump = pending_fee_change.bump,
+    )]
+    pub pending_fee_change: Account<'info, PendingFeeChange>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+impl<'info> ExecuteFeeChange<'info> {
+    /// Applies the pending fee change once it is effective and refunds its
+    /// account to the admin.
+    pub fn execute_fee_change(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= self.pending_fee_change.effective_at,
+            AmmError::FeeChangeNotReady
+        );
+
+        let old_fee_rate = self.platform_state.protocol_fee_rate;
+        let new_fee_rate = self.pending_fee_change.new_fee_rate;
+        self.platform_state.protocol_fee_rate = new_fee_rate;
+
+        msg!("Protocol fee rate updated to: {}bps", new_fee_rate);
+
+        emit_event!(
+            self,
+            FeeRateUpdated {
+                admin: self.admin.key(),
+                old_fee_rate,
+                new_fee_rate,
+                timestamp: now,
+            }
+        );
+        Ok(())
+    }
+}
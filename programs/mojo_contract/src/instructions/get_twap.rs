@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError,
+    state::{Observation, Observations, Pair},
+};
+
+/// A snapshot of a pair's price accumulators at `timestamp`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceObservation {
+    pub timestamp: i64,
+    pub base_price_cumulative: u128,
+    pub paired_price_cumulative: u128,
+}
+
+impl From<&Observation> for PriceObservation {
+    fn from(sample: &Observation) -> Self {
+        Self {
+            timestamp: sample.timestamp,
+            base_price_cumulative: sample.base_price_cumulative,
+            paired_price_cumulative: sample.paired_price_cumulative,
+        }
+    }
+}
+
+/// Time-weighted average prices over a window, in Q64.64 fixed point.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TwapResult {
+    pub base_price_x64: u128,   // Paired tokens per MOJO
+    pub paired_price_x64: u128, // MOJO per paired token
+    pub window_seconds: i64,
+}
+
+#[derive(Accounts)]
+pub struct GetTwap<'info> {
+    #[account(
+        seeds = [b"pair", pair.base_token_mint.as_ref(), pair.paired_token_mint.as_ref()],
+        bump = pair.bump,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    /// Price samples the window is taken between
+    #[account(
+        seeds = [b"observations", pair.key().as_ref()],
+        bump = observations.load()?.bump,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+}
+
+impl<'info> GetTwap<'info> {
+    /// Averages the pair price between the samples in slots `start_index`
+    /// and `end_index` of the pair's observation buffer. Without an
+    /// `end_index` the window ends now, at the pair's accumulators extended
+    /// to the current time.
+    pub fn get_twap(&self, start_index: u16, end_index: Option<u16>) -> Result<TwapResult> {
+        let info = self.observations.to_account_info();
+        let data = info.try_borrow_data()?;
+        let (observations, slots) = Observations::read(&data);
+        let start = PriceObservation::from(&observations.sample(slots, start_index)?);
+
+        match end_index {
+            Some(end_index) => {
+                let end = PriceObservation::from(&observations.sample(slots, end_index)?);
+                twap_between(&start, &end)
+            }
+            None => twap_since(&self.pair, &start, Clock::get()?.unix_timestamp),
+        }
+    }
+}
+
+/// Average prices between two snapshots of a pair's accumulators. `start`
+/// must be the older one.
+pub fn twap_between(start: &PriceObservation, end: &PriceObservation) -> Result<TwapResult> {
+    let window_seconds = end
+        .timestamp
+        .checked_sub(start.timestamp)
+        .ok_or(AmmError::MathOverflow)?;
+    require!(window_seconds > 0, AmmError::InvalidTwapWindow);
+
+    Ok(TwapResult {
+        base_price_x64: end
+            .base_price_cumulative
+            .wrapping_sub(start.base_price_cumulative)
+            / window_seconds as u128,
+        paired_price_x64: end
+            .paired_price_cumulative
+            .wrapping_sub(start.paired_price_cumulative)
+            / window_seconds as u128,
+        window_seconds,
+    })
+}
+
+/// Average prices from `since` to `now`. The program only starts a window
+/// from a stored observation; off-chain callers can pass their own snapshot.
+pub fn twap_since(pair: &Pair, since: &PriceObservation, now: i64) -> Result<TwapResult> {
+    let (base_price_cumulative, paired_price_cumulative) = pair.price_cumulatives_at(now)?;
+    let end = PriceObservation {
+        timestamp: now,
+        base_price_cumulative,
+        paired_price_cumulative,
+    };
+    twap_between(since, &end)
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError,
+    state::{Observations, Pair},
+    MAX_OBSERVATION_CAPACITY,
+};
+
+#[derive(Accounts)]
+#[instruction(new_capacity: u16)]
+pub struct GrowObservations<'info> {
+    /// Anyone may pay to enlarge the buffer, up to the maximum size. That is
+    /// checked here so it fails before the buffer is reallocated.
+    #[account(
+        mut,
+        constraint = new_capacity <= MAX_OBSERVATION_CAPACITY @ AmmError::ObservationCapacityTooLarge,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"pair", pair.base_token_mint.as_ref(), pair.paired_token_mint.as_ref()],
+        bump = pair.bump,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    #[account(
+        mut,
+        seeds = [b"observations", pair.key().as_ref()],
+        bump = observations.load()?.bump,
+        has_one = pair,
+        realloc = Observations::space(new_capacity as usize),
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> GrowObservations<'info> {
+    pub fn grow_observations(&mut self, new_capacity: u16) -> Result<()> {
+        let info = self.observations.to_account_info();
+        let mut data = info.try_borrow_mut_data()?;
+        let (observations, slots) = Observations::split_mut(&mut data);
+        require!(
+            new_capacity > observations.capacity,
+            AmmError::InvalidAmount
+        );
+        observations.grow(slots, new_capacity);
+
+        msg!("Observation capacity grown to: {}", new_capacity);
+
+        Ok(())
+    }
+}
@@ -5,8 +5,12 @@ use anchor_spl::{
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::state::PlatformState;
+use crate::{
+    error::AmmError, events::emit_event, state::PlatformState, PlatformInitialized,
+    DEFAULT_FEE_CHANGE_DELAY, DEFAULT_OBSERVATION_INTERVAL, MAX_PROTOCOL_FEE_RATE,
+};
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct InitializePlatform<'info> {
     #[account(mut)]
@@ -43,6 +47,11 @@ impl<'info> InitializePlatform<'info> {
         protocol_fee_rate: u16,
         bumps: &InitializePlatformBumps,
     ) -> Result<()> {
+        require!(
+            protocol_fee_rate <= MAX_PROTOCOL_FEE_RATE,
+            AmmError::ProtocolFeeTooHigh
+        );
+
         self.platform_state.set_inner(PlatformState {
             base_token_mint: self.base_token_mint.key(),
             admin: self.admin.key(),
@@ -50,7 +59,21 @@ impl<'info> InitializePlatform<'info> {
             is_paused: false,
             protocol_fee_rate,
             bump: bumps.platform_state,
+            swap_cooldown_seconds: 0,
+            observation_interval_seconds: DEFAULT_OBSERVATION_INTERVAL,
+            fee_change_delay_seconds: DEFAULT_FEE_CHANGE_DELAY,
         });
+
+        emit_event!(
+            self,
+            PlatformInitialized {
+                admin: self.admin.key(),
+                base_token_mint: self.base_token_mint.key(),
+                fee_collector: self.platform_treasury.key(),
+                protocol_fee_rate,
+                timestamp: Clock::get()?.unix_timestamp,
+            }
+        );
         Ok(())
     }
 }
@@ -1,19 +1,43 @@
 pub mod initialize_platform;
 pub mod add_liquidity;
+pub mod add_liquidity_single_sided;
 pub mod create_pair;
 pub mod remove_liquidity;
+pub mod remove_liquidity_single_sided;
 pub mod swap;
+pub mod swap_route;
+pub mod quote_swap;
 pub mod withdraw_platform_fees;
 pub mod pause_plaform;
-pub mod update_fee_rate;
+pub mod propose_fee_change;
+pub mod execute_fee_change;
+pub mod cancel_fee_change;
 pub mod resume_platform;
+pub mod update_swap_cooldown;
+pub mod update_pair_swap_cooldown;
+pub mod get_twap;
+pub mod grow_observations;
+pub mod update_observation_interval;
+pub mod update_fee_change_delay;
 
 pub use initialize_platform::*;
 pub use add_liquidity::*;
+pub use add_liquidity_single_sided::*;
 pub use create_pair::*;
 pub use remove_liquidity::*;
+pub use remove_liquidity_single_sided::*;
 pub use swap::*;
+pub use swap_route::*;
+pub use quote_swap::*;
 pub use withdraw_platform_fees::*;
 pub use pause_plaform::*;
-pub use update_fee_rate::*;
-pub use resume_platform::*;
\ No newline at end of file
+pub use propose_fee_change::*;
+pub use execute_fee_change::*;
+pub use cancel_fee_change::*;
+pub use resume_platform::*;
+pub use update_swap_cooldown::*;
+pub use update_pair_swap_cooldown::*;
+pub use get_twap::*;
+pub use grow_observations::*;
+pub use update_observation_interval::*;
+pub use update_fee_change_delay::*;
\ No newline at end of file
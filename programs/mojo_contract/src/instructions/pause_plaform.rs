@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::{error::AmmError, PlatformPauseChanged, PlatformState};
+use crate::{error::AmmError, events::emit_event, PlatformPauseChanged, PlatformState};
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct PausePlatform<'info> {
     /// Platform configuration (PDA)
@@ -41,7 +42,7 @@ impl<'info> PausePlatform<'info> {
         platform.is_paused = pause;
 
         // Emit event if using event system
-        emit!(PlatformPauseChanged {
+        emit_event!(self, PlatformPauseChanged {
             admin: self.admin.key(),
             paused: pause,
             timestamp: Clock::get()?.unix_timestamp
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError,
+    events::emit_event,
+    state::{FeeChangeProposed, PendingFeeChange, PlatformState},
+    MAX_PROTOCOL_FEE_RATE,
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ProposeFeeChange<'info> {
+    #[account(
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    /// A new proposal replaces any pending one
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + PendingFeeChange::INIT_SPACE,
+        seeds = [b"pending-fee-change"],
+        bump,
+    )]
+    pub pending_fee_change: Account<'info, PendingFeeChange>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ProposeFeeChange<'info> {
+    /// Announces `new_fee_rate`, which `execute_fee_change` can apply once
+    /// the platform's fee change delay has passed. The delay is fixed at
+    /// proposal time, so shortening it later does not bring this change
+    /// forward.
+    pub fn propose_fee_change(
+        &mut self,
+        new_fee_rate: u16,
+        bumps: &ProposeFeeChangeBumps,
+    ) -> Result<()> {
+        require!(
+            new_fee_rate <= MAX_PROTOCOL_FEE_RATE,
+            AmmError::ProtocolFeeTooHigh
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let effective_at = now
+            .checked_add(self.platform_state.fee_change_delay_seconds)
+            .ok_or(AmmError::MathOverflow)?;
+
+        self.pending_fee_change.set_inner(PendingFeeChange {
+            new_fee_rate,
+            proposed_at: now,
+            effective_at,
+            bump: bumps.pending_fee_change,
+        });
+
+        msg!(
+            "Protocol fee rate change to {}bps proposed, effective at {}",
+            new_fee_rate,
+            effective_at
+        );
+
+        emit_event!(
+            self,
+            FeeChangeProposed {
+                admin: self.admin.key(),
+                current_fee_rate: self.platform_state.protocol_fee_rate,
+                new_fee_rate,
+                effective_at,
+                timestamp: now,
+            }
+        );
+        Ok(())
+    }
+}
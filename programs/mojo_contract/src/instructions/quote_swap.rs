@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    instructions::swap::{quote_exact_in_after_transfer_fees, SwapQuote},
+    Pair, PlatformState,
+};
+
+/// Read-only preview of `swap`, meant to be run through `simulateTransaction`.
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    #[account(
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        seeds = [b"pair", pair.base_token_mint.as_ref(), pair.paired_token_mint.as_ref()],
+        bump = pair.bump,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    /// Base token mint (MOJO), read for its transfer fee
+    #[account(address = pair.base_token_mint)]
+    pub base_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Paired token mint, read for its transfer fee
+    #[account(address = pair.paired_token_mint)]
+    pub paired_token_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+impl<'info> QuoteSwap<'info> {
+    pub fn quote_swap(&self, amount_in: u64, is_base_input: bool) -> Result<SwapQuote> {
+        let base = self.base_token_mint.to_account_info();
+        let paired = self.paired_token_mint.to_account_info();
+        let (input_mint, output_mint) = if is_base_input {
+            (&base, &paired)
+        } else {
+            (&paired, &base)
+        };
+
+        quote_exact_in_after_transfer_fees(
+            &self.pair,
+            self.platform_state.protocol_fee_rate,
+            amount_in,
+            is_base_input,
+            input_mint,
+            output_mint,
+        )
+    }
+}
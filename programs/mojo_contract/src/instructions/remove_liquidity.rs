@@ -1,11 +1,21 @@
-use crate::{error::AmmError, state::Pair};
+use crate::{
+    curve::lp_to_burn_amounts,
+    error::AmmError,
+    events::emit_event,
+    instructions::add_liquidity::LiquidityAccounts,
+    native_sol::{native_sol, native_sol_out, require_native_sol_pair},
+    state::{LiquidityRemoved, Pair},
+    transfer_fee::amount_after_transfer_fee,
+    transfer_hook::transfer_checked,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token::{burn, transfer, Burn, Transfer},
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    associated_token::AssociatedToken,
+    token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
 /// Instruction to remove liquidity from a MOJO/paired-token pool
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 #[instruction(lp_amount: u64, min_base: u64, min_paired: u64)]
 pub struct RemoveLiquidity<'info> {
@@ -24,6 +34,20 @@ pub struct RemoveLiquidity<'info> {
     )]
     pub pair: Account<'info, Pair>,
 
+    /// Base token mint (MOJO)
+    #[account(
+        address = pair.base_token_mint,
+        mint::token_program = token_program,
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Paired token mint
+    #[account(
+        address = pair.paired_token_mint,
+        mint::token_program = paired_token_program,
+    )]
+    pub paired_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
     /// Vault holding base token (MOJO)
     #[account(mut, address = pair.base_vault)]
     pub base_vault: Box<InterfaceAccount<'info, TokenAccount>>,
@@ -33,6 +57,7 @@ pub struct RemoveLiquidity<'info> {
         mut,
         associated_token::mint = pair.base_token_mint,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -40,11 +65,13 @@ pub struct RemoveLiquidity<'info> {
     #[account(mut, address = pair.paired_vault)]
     pub paired_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// User's paired token ATA
+    /// User's paired token ATA, created on demand for native SOL withdrawals
     #[account(
-        mut,
-        associated_token::mint = pair.paired_token_mint,
+        init_if_needed,
+        payer = user,
+        associated_token::mint = paired_token_mint,
         associated_token::authority = user,
+        associated_token::token_program = paired_token_program,
     )]
     pub user_paired_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -57,40 +84,115 @@ pub struct RemoveLiquidity<'info> {
         mut,
         associated_token::mint = pair.lp_mint,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// Token program
+    /// Temporary WSOL account the `*_sol` variants unwrap through, created
+    /// and closed within the instruction. Other instructions leave it out.
+    /// CHECK: never read; `NativeSol::unwrap` creates it at this address
+    #[account(mut, seeds = [b"native-sol", user.key().as_ref()], bump)]
+    pub native_sol_account: Option<UncheckedAccount<'info>>,
+
+    /// Token program of the base token (MOJO) and LP mint
     pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the paired token, SPL Token or Token-2022
+    pub paired_token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 impl<'info> RemoveLiquidity<'info> {
+    /// See [`LiquidityAccounts::remove_liquidity`].
     pub fn remove_liquidity(
         &mut self,
         lp_amount: u64,
         min_base: u64,
         min_paired: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<(u64, u64)> {
+        LiquidityAccounts {
+            user: &self.user,
+            pair: &mut self.pair,
+            base_token_mint: &self.base_token_mint,
+            paired_token_mint: &self.paired_token_mint,
+            base_vault: &mut self.base_vault,
+            paired_vault: &mut self.paired_vault,
+            user_base_ata: &self.user_base_ata,
+            user_paired_ata: &self.user_paired_ata,
+            lp_mint: &self.lp_mint,
+            user_lp_ata: &self.user_lp_ata,
+            token_program: &self.token_program,
+            paired_token_program: &self.paired_token_program,
+            #[cfg(feature = "event-cpi")]
+            event_authority: &self.event_authority,
+        }
+        .remove_liquidity(lp_amount, min_base, min_paired, remaining_accounts)
+    }
+
+    /// `remove_liquidity` for a wrapped-SOL pair, paying the paired side out
+    /// as lamports.
+    pub fn remove_liquidity_sol(
+        &mut self,
+        lp_amount: u64,
+        min_base: u64,
+        min_paired: u64,
+        bumps: &RemoveLiquidityBumps,
+        remaining_accounts: &[AccountInfo<'info>],
     ) -> Result<()> {
+        require_native_sol_pair(&self.pair)?;
+        let held = self.user_paired_ata.amount;
+
+        self.remove_liquidity(lp_amount, min_base, min_paired, remaining_accounts)?;
+
+        let amount_out = native_sol_out(&mut self.user_paired_ata, held)?;
+        native_sol(
+            &self.user,
+            &self.user_paired_ata,
+            &self.paired_token_mint,
+            &self.native_sol_account,
+            bumps.native_sol_account,
+            &self.paired_token_program,
+            &self.system_program,
+        )?
+        .unwrap(amount_out)
+    }
+}
+
+impl<'info> LiquidityAccounts<'_, 'info> {
+    /// Returns the base and paired amounts that reach the user.
+    ///
+    /// `remaining_accounts` carries the extra accounts of any Token-2022
+    /// transfer hook and is forwarded to both withdrawals.
+    pub fn remove_liquidity(
+        &mut self,
+        lp_amount: u64,
+        min_base: u64,
+        min_paired: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<(u64, u64)> {
         require!(lp_amount > 0, AmmError::InvalidAmount);
 
+        self.pair
+            .update_price_cumulatives(Clock::get()?.unix_timestamp)?;
+
         let base_reserve = self.pair.base_reserve;
         let paired_reserve = self.pair.paired_reserve;
         let total_lp = self.pair.total_liquidity;
 
         // Calculate amounts to return
-        let base_amount = (lp_amount as u128)
-            .checked_mul(base_reserve as u128)
-            .ok_or(AmmError::MathOverflow)?
-            .checked_div(total_lp as u128)
-            .ok_or(AmmError::MathOverflow)? as u64;
-        let paired_amount = (lp_amount as u128)
-            .checked_mul(paired_reserve as u128)
-            .ok_or(AmmError::MathOverflow)?
-            .checked_div(total_lp as u128)
-            .ok_or(AmmError::MathOverflow)? as u64;
-
-        require!(base_amount >= min_base, AmmError::SlippageExceeded);
-        require!(paired_amount >= min_paired, AmmError::SlippageExceeded);
+        let (base_amount, paired_amount) =
+            lp_to_burn_amounts(lp_amount, base_reserve, paired_reserve, total_lp)?;
+
+        // Slippage is checked against what reaches the user
+        let base_received =
+            amount_after_transfer_fee(&self.base_token_mint.to_account_info(), base_amount)?;
+        let paired_received =
+            amount_after_transfer_fee(&self.paired_token_mint.to_account_info(), paired_amount)?;
+
+        require!(base_received >= min_base, AmmError::SlippageExceeded);
+        require!(paired_received >= min_paired, AmmError::SlippageExceeded);
 
         // Burn LP tokens from user
         let cpi_program = self.token_program.to_account_info();
@@ -115,30 +217,36 @@ impl<'info> RemoveLiquidity<'info> {
         ];
         let signer = &[&seeds[..]];
         // Base
-        transfer(
+        transfer_checked(
             CpiContext::new_with_signer(
                 cpi_program.clone(),
-                Transfer {
+                TransferChecked {
                     from: self.base_vault.to_account_info(),
+                    mint: self.base_token_mint.to_account_info(),
                     to: self.user_base_ata.to_account_info(),
                     authority: self.pair.to_account_info(),
                 },
                 signer,
-            ),
+            )
+            .with_remaining_accounts(remaining_accounts.to_vec()),
             base_amount,
+            self.base_token_mint.decimals,
         )?;
         // Paired
-        transfer(
+        transfer_checked(
             CpiContext::new_with_signer(
-                cpi_program.clone(),
-                Transfer {
+                self.paired_token_program.to_account_info(),
+                TransferChecked {
                     from: self.paired_vault.to_account_info(),
+                    mint: self.paired_token_mint.to_account_info(),
                     to: self.user_paired_ata.to_account_info(),
                     authority: self.pair.to_account_info(),
                 },
                 signer,
-            ),
+            )
+            .with_remaining_accounts(remaining_accounts.to_vec()),
             paired_amount,
+            self.paired_token_mint.decimals,
         )?;
 
         // Update reserves and total liquidity
@@ -152,6 +260,27 @@ impl<'info> RemoveLiquidity<'info> {
             .checked_sub(lp_amount)
             .ok_or(AmmError::MathOverflow)?;
 
-        Ok(())
+        // Withdrawals shrink k by design, so only vault backing is checked
+        self.base_vault.reload()?;
+        self.paired_vault.reload()?;
+        self.pair
+            .check_invariant(self.base_vault.amount, self.paired_vault.amount, None)?;
+
+        emit_event!(
+            self,
+            LiquidityRemoved {
+                pair: self.pair.key(),
+                user: self.user.key(),
+                base_amount,
+                paired_amount,
+                lp_burned: lp_amount,
+                base_reserve: self.pair.base_reserve,
+                paired_reserve: self.pair.paired_reserve,
+                total_liquidity: self.pair.total_liquidity,
+                timestamp: Clock::get()?.unix_timestamp,
+            }
+        );
+
+        Ok((base_received, paired_received))
     }
 }
@@ -0,0 +1,236 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::AmmError,
+    instructions::{
+        add_liquidity::LiquidityAccounts,
+        swap::{quote_exact_in_after_transfer_fees, SwapAccounts},
+    },
+    Observations, Pair, PlatformState, TraderState,
+};
+
+/// Accounts of `RemoveLiquidity` and `Swap` together, so a single-sided
+/// withdrawal can run both in one instruction through `LiquidityAccounts`
+/// and `SwapAccounts`.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct RemoveLiquiditySingleSided<'info> {
+    /// The platform state contains global configuration
+    #[account(
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        constraint = !platform_state.is_paused @ AmmError::TradingPaused,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    /// The user removing liquidity
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// The Pair state for the MOJO/paired token pool
+    #[account(
+        mut,
+        seeds = [b"pair", pair.base_token_mint.as_ref(), pair.paired_token_mint.as_ref()],
+        bump = pair.bump,
+        has_one = base_vault,
+        has_one = paired_vault,
+        has_one = lp_mint,
+        constraint = pair.total_liquidity > 0 @ AmmError::InsufficientLiquidity,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    /// Base token mint (MOJO)
+    #[account(
+        address = pair.base_token_mint,
+        mint::token_program = token_program,
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Paired token mint
+    #[account(
+        address = pair.paired_token_mint,
+        mint::token_program = paired_token_program,
+    )]
+    pub paired_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Vault holding base token (MOJO)
+    #[account(mut, address = pair.base_vault)]
+    pub base_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault holding paired token
+    #[account(mut, address = pair.paired_vault)]
+    pub paired_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User's base token ATA
+    #[account(
+        mut,
+        associated_token::mint = base_token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User's paired token ATA, created on demand for MOJO-only withdrawals
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = paired_token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = paired_token_program,
+    )]
+    pub user_paired_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// LP token mint for this pool
+    #[account(mut, address = pair.lp_mint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// User's LP token ATA
+    #[account(
+        mut,
+        associated_token::mint = pair.lp_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Platform fee collector account
+    #[account(
+        mut,
+        address = platform_state.fee_collector,
+    )]
+    pub fee_collector: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Tracks the user's last swap on this pair for the cooldown
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + TraderState::INIT_SPACE,
+        seeds = [b"trader", pair.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub trader_state: Box<Account<'info, TraderState>>,
+
+    /// Price sample ring buffer for this pair
+    #[account(
+        mut,
+        seeds = [b"observations", pair.key().as_ref()],
+        bump = observations.load()?.bump,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+
+    /// Token program of the base token (MOJO) and LP mint
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the paired token, SPL Token or Token-2022
+    pub paired_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RemoveLiquiditySingleSided<'info> {
+    /// Burns `lp_amount` like `remove_liquidity` and swaps the side the user
+    /// does not want back through the same pool, so only MOJO (`want_base`)
+    /// or only the paired token is left. `min_out` bounds the total of that
+    /// token received across both steps.
+    ///
+    /// The swap counts towards the user's swap cooldown like any other.
+    pub fn remove_liquidity_single_sided(
+        &mut self,
+        lp_amount: u64,
+        want_base: bool,
+        min_out: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let (base_received, paired_received) =
+            self.liquidity_accounts()
+                .remove_liquidity(lp_amount, 0, 0, remaining_accounts)?;
+
+        // Swap the unwanted side back into the wanted one
+        let is_base_input = !want_base;
+        let (kept, swap_amount, input_mint, output_mint) = if want_base {
+            (
+                base_received,
+                paired_received,
+                self.paired_token_mint.to_account_info(),
+                self.base_token_mint.to_account_info(),
+            )
+        } else {
+            (
+                paired_received,
+                base_received,
+                self.base_token_mint.to_account_info(),
+                self.paired_token_mint.to_account_info(),
+            )
+        };
+
+        let swapped = if swap_amount > 0 {
+            let quote = quote_exact_in_after_transfer_fees(
+                &self.pair,
+                self.platform_state.protocol_fee_rate,
+                swap_amount,
+                is_base_input,
+                &input_mint,
+                &output_mint,
+            )?;
+
+            self.swap_accounts().swap(
+                swap_amount,
+                quote.amount_received,
+                is_base_input,
+                remaining_accounts,
+            )?;
+
+            quote.amount_received
+        } else {
+            0
+        };
+
+        let amount_out = kept.checked_add(swapped).ok_or(AmmError::MathOverflow)?;
+        require!(amount_out >= min_out, AmmError::SlippageExceeded);
+
+        Ok(())
+    }
+
+    fn liquidity_accounts(&mut self) -> LiquidityAccounts<'_, 'info> {
+        LiquidityAccounts {
+            user: &self.user,
+            pair: &mut self.pair,
+            base_token_mint: &self.base_token_mint,
+            paired_token_mint: &self.paired_token_mint,
+            base_vault: &mut self.base_vault,
+            paired_vault: &mut self.paired_vault,
+            user_base_ata: &self.user_base_ata,
+            user_paired_ata: &self.user_paired_ata,
+            lp_mint: &self.lp_mint,
+            user_lp_ata: &self.user_lp_ata,
+            token_program: &self.token_program,
+            paired_token_program: &self.paired_token_program,
+            #[cfg(feature = "event-cpi")]
+            event_authority: &self.event_authority,
+        }
+    }
+
+    fn swap_accounts(&mut self) -> SwapAccounts<'_, 'info> {
+        SwapAccounts {
+            platform_state: &self.platform_state,
+            user: &self.user,
+            pair: &mut self.pair,
+            base_token_mint: &self.base_token_mint,
+            paired_token_mint: &self.paired_token_mint,
+            base_vault: &mut self.base_vault,
+            paired_vault: &mut self.paired_vault,
+            user_base_ata: &self.user_base_ata,
+            user_paired_ata: &self.user_paired_ata,
+            fee_collector: &self.fee_collector,
+            trader_state: &mut self.trader_state,
+            observations: &self.observations,
+            token_program: &self.token_program,
+            paired_token_program: &self.paired_token_program,
+            #[cfg(feature = "event-cpi")]
+            event_authority: &self.event_authority,
+        }
+    }
+}
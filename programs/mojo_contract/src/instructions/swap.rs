@@ -2,12 +2,23 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{transfer, Transfer},
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::{error::AmmError, Pair, PlatformState};
+use crate::{
+    curve::{after_fee, fee_amount, get_amount_in, get_amount_out, gross_up},
+    error::AmmError,
+    events::emit_event,
+    native_sol::{native_sol, native_sol_out, require_native_sol_pair},
+    transfer_fee::{
+        amount_after_epoch_transfer_fee, amount_before_epoch_transfer_fee,
+        mint_transfer_fee_config, TransferFees,
+    },
+    transfer_hook::transfer_checked,
+    record_observation, Observations, Pair, PlatformState, Swapped, TraderState,
+};
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct Swap<'info> {
     /// The platform state contains global configuration
@@ -34,9 +45,17 @@ pub struct Swap<'info> {
     pub pair: Account<'info, Pair>,
 
     /// Base token mint (MOJO)
+    #[account(
+        address = pair.base_token_mint,
+        mint::token_program = token_program,
+    )]
     pub base_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// Paired token mint
+    #[account(
+        address = pair.paired_token_mint,
+        mint::token_program = paired_token_program,
+    )]
     pub paired_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// Vault holding base tokens (MOJO)
@@ -60,14 +79,17 @@ pub struct Swap<'info> {
         mut,
         associated_token::mint = base_token_mint,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
     pub user_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// User's paired token account
+    /// User's paired token account, created on demand for native SOL swaps
     #[account(
-        mut,
+        init_if_needed,
+        payer = user,
         associated_token::mint = paired_token_mint,
         associated_token::authority = user,
+        associated_token::token_program = paired_token_program,
     )]
     pub user_paired_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -78,230 +100,622 @@ pub struct Swap<'info> {
     )]
     pub fee_collector: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Tracks the user's last swap on this pair for the cooldown
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + TraderState::INIT_SPACE,
+        seeds = [b"trader", pair.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub trader_state: Box<Account<'info, TraderState>>,
+
+    /// Price sample ring buffer for this pair
+    #[account(
+        mut,
+        seeds = [b"observations", pair.key().as_ref()],
+        bump = observations.load()?.bump,
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+
+    /// Temporary WSOL account the `*_sol` variants unwrap through, created
+    /// and closed within the instruction. Other instructions leave it out.
+    /// CHECK: never read; `NativeSol::unwrap` creates it at this address
+    #[account(mut, seeds = [b"native-sol", user.key().as_ref()], bump)]
+    pub native_sol_account: Option<UncheckedAccount<'info>>,
+
+    /// Token program of the base token (MOJO)
     pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the paired token, SPL Token or Token-2022
+    pub paired_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Swap<'info> {
-    pub fn swap(&mut self, amount_in: u64, min_amount_out: u64, is_base_input: bool) -> Result<()> {
-        require!(
-            self.pair.total_liquidity > 0,
-            AmmError::InsufficientLiquidity
-        );
-        require!(amount_in > 0, AmmError::ZeroAmount);
-        require!(!self.platform_state.is_paused, AmmError::TradingPaused);
-
-        let clock = Clock::get()?;
-        self.pair.last_swap_time = clock.unix_timestamp;
+    pub fn swap(
+        &mut self,
+        amount_in: u64,
+        min_amount_out: u64,
+        is_base_input: bool,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.swap_accounts()
+            .swap(amount_in, min_amount_out, is_base_input, remaining_accounts)
+    }
 
-        let base_reserve = self.pair.base_reserve;
-        let paired_reserve = self.pair.paired_reserve;
+    /// See [`SwapAccounts::swap_exact_out`].
+    pub fn swap_exact_out(
+        &mut self,
+        amount_out: u64,
+        max_amount_in: u64,
+        is_base_input: bool,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.swap_accounts().swap_exact_out(
+            amount_out,
+            max_amount_in,
+            is_base_input,
+            remaining_accounts,
+        )
+    }
 
-        let (
-            input_reserve,
-            output_reserve,
-            input_vault,
-            output_vault,
-            input_account,
-            output_account,
-        ) = if is_base_input {
-            (
-                base_reserve,
-                paired_reserve,
-                &self.base_vault,
-                &self.paired_vault,
-                &self.user_base_ata,
-                &self.user_paired_ata,
-            )
-        } else {
-            (
-                paired_reserve,
-                base_reserve,
-                &self.paired_vault,
-                &self.base_vault,
+    /// `swap` against a wrapped-SOL pair using native SOL. SOL going in is
+    /// wrapped into the user's WSOL account first, and SOL coming out is
+    /// unwrapped afterwards.
+    pub fn swap_sol(
+        &mut self,
+        amount_in: u64,
+        min_amount_out: u64,
+        is_base_input: bool,
+        bumps: &SwapBumps,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require_native_sol_pair(&self.pair)?;
+        let held = self.user_paired_ata.amount;
+
+        if !is_base_input {
+            native_sol(
+                &self.user,
                 &self.user_paired_ata,
-                &self.user_base_ata,
-            )
-        };
+                &self.paired_token_mint,
+                &self.native_sol_account,
+                bumps.native_sol_account,
+                &self.paired_token_program,
+                &self.system_program,
+            )?
+            .wrap(amount_in)?;
+        }
 
-        // --- MOJO -> Paired Token swap ---
-        if is_base_input {
-            let protocol_fee = amount_in
-                .checked_mul(self.platform_state.protocol_fee_rate as u64)
-                .ok_or(AmmError::MathOverflow)?
-                .checked_div(10_000)
-                .ok_or(AmmError::MathOverflow)?;
+        self.swap(amount_in, min_amount_out, is_base_input, remaining_accounts)?;
+
+        let amount_out = native_sol_out(&mut self.user_paired_ata, held)?;
+        native_sol(
+            &self.user,
+            &self.user_paired_ata,
+            &self.paired_token_mint,
+            &self.native_sol_account,
+            bumps.native_sol_account,
+            &self.paired_token_program,
+            &self.system_program,
+        )?
+        .unwrap(amount_out)
+    }
 
-            let amount_in_after_fee = amount_in
-                .checked_sub(protocol_fee)
-                .ok_or(AmmError::MathOverflow)?;
+    fn swap_accounts(&mut self) -> SwapAccounts<'_, 'info> {
+        SwapAccounts {
+            platform_state: &self.platform_state,
+            user: &self.user,
+            pair: &mut self.pair,
+            base_token_mint: &self.base_token_mint,
+            paired_token_mint: &self.paired_token_mint,
+            base_vault: &mut self.base_vault,
+            paired_vault: &mut self.paired_vault,
+            user_base_ata: &self.user_base_ata,
+            user_paired_ata: &self.user_paired_ata,
+            fee_collector: &self.fee_collector,
+            trader_state: &mut self.trader_state,
+            observations: &self.observations,
+            token_program: &self.token_program,
+            paired_token_program: &self.paired_token_program,
+            #[cfg(feature = "event-cpi")]
+            event_authority: &self.event_authority,
+        }
+    }
+}
+
+/// The accounts a swap against one pair reads and writes, borrowed from the
+/// instruction running it. `Swap` and the single-sided liquidity
+/// instructions all swap through here.
+pub struct SwapAccounts<'a, 'info> {
+    pub platform_state: &'a Account<'info, PlatformState>,
+    pub user: &'a Signer<'info>,
+    pub pair: &'a mut Account<'info, Pair>,
+    pub base_token_mint: &'a InterfaceAccount<'info, Mint>,
+    pub paired_token_mint: &'a InterfaceAccount<'info, Mint>,
+    pub base_vault: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub paired_vault: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub user_base_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    pub user_paired_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    pub fee_collector: &'a InterfaceAccount<'info, TokenAccount>,
+    pub trader_state: &'a mut Account<'info, TraderState>,
+    pub observations: &'a AccountLoader<'info, Observations>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub paired_token_program: &'a Interface<'info, TokenInterface>,
+    #[cfg(feature = "event-cpi")]
+    pub event_authority: &'a AccountInfo<'info>,
+}
+
+impl<'info> SwapAccounts<'_, 'info> {
+    pub fn swap(
+        &mut self,
+        amount_in: u64,
+        min_amount_out: u64,
+        is_base_input: bool,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(!self.platform_state.is_paused, AmmError::TradingPaused);
 
-            let k = (input_reserve as u128)
-                .checked_mul(output_reserve as u128)
-                .ok_or(AmmError::MathOverflow)?;
+        let (input_mint, output_mint) = self.directional_mints(is_base_input);
+        let quote = quote_exact_in_after_transfer_fees(
+            self.pair,
+            self.platform_state.protocol_fee_rate,
+            amount_in,
+            is_base_input,
+            &input_mint,
+            &output_mint,
+        )?;
 
-            let new_input_reserve = (input_reserve as u128)
-                .checked_add(amount_in_after_fee as u128)
-                .ok_or(AmmError::MathOverflow)?;
+        require!(
+            quote.amount_received >= min_amount_out,
+            AmmError::SlippageExceeded
+        );
 
-            let new_output_reserve = k
-                .checked_div(new_input_reserve)
-                .ok_or(AmmError::MathOverflow)?;
+        self.settle(&quote, is_base_input, remaining_accounts)
+    }
 
-            let output_amount = (output_reserve as u128)
-                .checked_sub(new_output_reserve)
-                .ok_or(AmmError::MathOverflow)? as u64;
+    /// Swaps for exactly `amount_out` tokens, spending at most `max_amount_in`.
+    ///
+    /// The required input is rounded up so the pool never gives away more than
+    /// the curve allows, and the protocol and LP fees are charged just like
+    /// `swap`.
+    pub fn swap_exact_out(
+        &mut self,
+        amount_out: u64,
+        max_amount_in: u64,
+        is_base_input: bool,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(!self.platform_state.is_paused, AmmError::TradingPaused);
 
-            require!(output_amount >= min_amount_out, AmmError::SlippageExceeded);
+        let (input_mint, output_mint) = self.directional_mints(is_base_input);
+        let quote = quote_exact_out_after_transfer_fees(
+            self.pair,
+            self.platform_state.protocol_fee_rate,
+            amount_out,
+            is_base_input,
+            &input_mint,
+            &output_mint,
+        )?;
 
-            // Transfer input MOJO from user → base_vault
-            transfer(
-                CpiContext::new(
-                    self.token_program.to_account_info(),
-                    Transfer {
-                        from: input_account.to_account_info(),
-                        to: input_vault.to_account_info(),
-                        authority: self.user.to_account_info(),
-                    },
-                ),
-                amount_in,
-            )?;
+        require!(
+            quote.amount_in <= max_amount_in,
+            AmmError::ExcessiveInputAmount
+        );
+
+        self.settle(&quote, is_base_input, remaining_accounts)
+    }
 
-            // Transfer output tokens from vault → user
-            let signer_seeds = &[
-                b"pair",
-                self.pair.base_token_mint.as_ref(),
-                self.pair.paired_token_mint.as_ref(),
-                &[self.pair.bump],
-            ];
-            transfer(
+    /// Returns `(input_mint, output_mint)` for the given direction.
+    fn directional_mints(&self, is_base_input: bool) -> (AccountInfo<'info>, AccountInfo<'info>) {
+        let base = self.base_token_mint.to_account_info();
+        let paired = self.paired_token_mint.to_account_info();
+        if is_base_input {
+            (base, paired)
+        } else {
+            (paired, base)
+        }
+    }
+
+    /// Moves tokens for a priced swap and applies the quoted reserves.
+    ///
+    /// The protocol fee is always paid out of the base (MOJO) vault, so it is
+    /// deducted from the input side for base-in swaps and from the output side
+    /// for paired-in swaps.
+    ///
+    /// `remaining_accounts` carries the extra accounts of any Token-2022
+    /// transfer hook and is forwarded to every transfer.
+    fn settle(
+        &mut self,
+        quote: &SwapQuote,
+        is_base_input: bool,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let k_before = self.pair.k();
+        let cooldown = self.pair.swap_cooldown(self.platform_state);
+        self.trader_state.record_swap(
+            self.user.key(),
+            self.pair.key(),
+            clock.unix_timestamp,
+            cooldown,
+        )?;
+        self.pair.update_price_cumulatives(clock.unix_timestamp)?;
+        record_observation(
+            self.observations,
+            self.pair,
+            self.platform_state.observation_interval_seconds,
+        )?;
+
+        let base_side = (
+            &self.base_vault,
+            &self.user_base_ata,
+            &self.base_token_mint,
+            &self.token_program,
+        );
+        let paired_side = (
+            &self.paired_vault,
+            &self.user_paired_ata,
+            &self.paired_token_mint,
+            &self.paired_token_program,
+        );
+        let (
+            (input_vault, input_account, input_mint, input_program),
+            (output_vault, output_account, output_mint, output_program),
+        ) = if is_base_input {
+            (base_side, paired_side)
+        } else {
+            (paired_side, base_side)
+        };
+
+        // Transfer input tokens from user → input vault
+        transfer_checked(
+            CpiContext::new(
+                input_program.to_account_info(),
+                TransferChecked {
+                    from: input_account.to_account_info(),
+                    mint: input_mint.to_account_info(),
+                    to: input_vault.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(remaining_accounts.to_vec()),
+            quote.amount_in,
+            input_mint.decimals,
+        )?;
+
+        // Transfer output tokens from vault → user
+        let signer_seeds = &[
+            b"pair",
+            self.pair.base_token_mint.as_ref(),
+            self.pair.paired_token_mint.as_ref(),
+            &[self.pair.bump],
+        ];
+        transfer_checked(
+            CpiContext::new_with_signer(
+                output_program.to_account_info(),
+                TransferChecked {
+                    from: output_vault.to_account_info(),
+                    mint: output_mint.to_account_info(),
+                    to: output_account.to_account_info(),
+                    authority: self.pair.to_account_info(),
+                },
+                &[signer_seeds],
+            )
+            .with_remaining_accounts(remaining_accounts.to_vec()),
+            quote.amount_out,
+            output_mint.decimals,
+        )?;
+
+        // Transfer MOJO fee to fee_collector
+        if quote.protocol_fee > 0 {
+            transfer_checked(
                 CpiContext::new_with_signer(
                     self.token_program.to_account_info(),
-                    Transfer {
-                        from: output_vault.to_account_info(),
-                        to: output_account.to_account_info(),
+                    TransferChecked {
+                        from: self.base_vault.to_account_info(),
+                        mint: self.base_token_mint.to_account_info(),
+                        to: self.fee_collector.to_account_info(),
                         authority: self.pair.to_account_info(),
                     },
                     &[signer_seeds],
-                ),
-                output_amount,
+                )
+                .with_remaining_accounts(remaining_accounts.to_vec()),
+                quote.protocol_fee,
+                self.base_token_mint.decimals,
             )?;
+        }
 
-            // Transfer MOJO fee to fee_collector
-            if protocol_fee > 0 {
-                transfer(
-                    CpiContext::new_with_signer(
-                        self.token_program.to_account_info(),
-                        Transfer {
-                            from: input_vault.to_account_info(),
-                            to: self.fee_collector.to_account_info(),
-                            authority: self.pair.to_account_info(),
-                        },
-                        &[signer_seeds],
-                    ),
-                    protocol_fee,
-                )?;
+        self.pair.base_reserve = quote.new_base_reserve;
+        self.pair.paired_reserve = quote.new_paired_reserve;
+
+        self.base_vault.reload()?;
+        self.paired_vault.reload()?;
+        self.pair.check_invariant(
+            self.base_vault.amount,
+            self.paired_vault.amount,
+            Some(k_before),
+        )?;
+
+        emit_event!(
+            self,
+            Swapped {
+                pair: self.pair.key(),
+                user: self.user.key(),
+                is_base_input,
+                amount_in: quote.amount_in,
+                amount_out: quote.amount_out,
+                protocol_fee: quote.protocol_fee,
+                base_reserve: self.pair.base_reserve,
+                paired_reserve: self.pair.paired_reserve,
+                timestamp: clock.unix_timestamp,
             }
+        );
 
-            self.pair.base_reserve = base_reserve
-                .checked_add(amount_in_after_fee)
-                .ok_or(AmmError::MathOverflow)?;
-            self.pair.paired_reserve = paired_reserve
-                .checked_sub(output_amount)
-                .ok_or(AmmError::MathOverflow)?;
-        }
-        // --- Paired Token -> MOJO swap ---
-        else {
-            let k = (input_reserve as u128)
-                .checked_mul(output_reserve as u128)
-                .ok_or(AmmError::MathOverflow)?;
-
-            let new_input_reserve = (input_reserve as u128)
-                .checked_add(amount_in as u128)
-                .ok_or(AmmError::MathOverflow)?;
-
-            let new_output_reserve = k
-                .checked_div(new_input_reserve)
-                .ok_or(AmmError::MathOverflow)?;
-
-            let gross_output_amount = (output_reserve as u128)
-                .checked_sub(new_output_reserve)
-                .ok_or(AmmError::MathOverflow)? as u64;
-
-            let protocol_fee = gross_output_amount
-                .checked_mul(self.platform_state.protocol_fee_rate as u64)
-                .ok_or(AmmError::MathOverflow)?
-                .checked_div(10_000)
-                .ok_or(AmmError::MathOverflow)?;
+        Ok(())
+    }
+}
 
-            let amount_out_after_fee = gross_output_amount
-                .checked_sub(protocol_fee)
-                .ok_or(AmmError::MathOverflow)?;
+/// The priced outcome of a swap against a pair, before any tokens move.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64,       // What the user sends
+    pub amount_out: u64,      // What the pool sends the user, after fees
+    pub amount_received: u64, // What reaches the user, after transfer fees
+    pub protocol_fee: u64,
+    pub price_impact_bps: u16, // Impact of the curve alone, excluding fees
+    pub new_base_reserve: u64,
+    pub new_paired_reserve: u64,
+}
 
-            require!(
-                amount_out_after_fee >= min_amount_out,
-                AmmError::SlippageExceeded
-            );
+/// Prices a swap of exactly `amount_in`. Both `swap` and `quote_swap` go
+/// through here so previews always match execution.
+pub fn quote_exact_in(
+    pair: &Pair,
+    protocol_fee_rate: u16,
+    amount_in: u64,
+    is_base_input: bool,
+) -> Result<SwapQuote> {
+    require!(pair.total_liquidity > 0, AmmError::InsufficientLiquidity);
+    require!(amount_in > 0, AmmError::ZeroAmount);
+
+    let (input_reserve, output_reserve) = directional_reserves(pair, is_base_input);
+
+    // --- MOJO -> Paired Token swap ---
+    // The protocol fee is taken from the MOJO going in.
+    if is_base_input {
+        let protocol_fee = fee_amount(amount_in, protocol_fee_rate)?;
+
+        let amount_in_after_fee = amount_in
+            .checked_sub(protocol_fee)
+            .ok_or(AmmError::MathOverflow)?;
+
+        let curve_amount_in = after_fee(amount_in_after_fee, pair.lp_fee_rate)?;
+        let output_amount = get_amount_out(curve_amount_in, input_reserve, output_reserve)?;
+
+        build_quote(
+            pair,
+            is_base_input,
+            amount_in,
+            output_amount,
+            protocol_fee,
+            curve_amount_in,
+        )
+    }
+    // --- Paired Token -> MOJO swap ---
+    // The protocol fee is taken from the MOJO coming out.
+    else {
+        let curve_amount_in = after_fee(amount_in, pair.lp_fee_rate)?;
+        let gross_output_amount = get_amount_out(curve_amount_in, input_reserve, output_reserve)?;
+
+        let protocol_fee = fee_amount(gross_output_amount, protocol_fee_rate)?;
+
+        let amount_out_after_fee = gross_output_amount
+            .checked_sub(protocol_fee)
+            .ok_or(AmmError::MathOverflow)?;
+
+        build_quote(
+            pair,
+            is_base_input,
+            amount_in,
+            amount_out_after_fee,
+            protocol_fee,
+            curve_amount_in,
+        )
+    }
+}
 
-            // Transfer input paired token from user → paired_vault
-            transfer(
-                CpiContext::new(
-                    self.token_program.to_account_info(),
-                    Transfer {
-                        from: input_account.to_account_info(),
-                        to: input_vault.to_account_info(),
-                        authority: self.user.to_account_info(),
-                    },
-                ),
-                amount_in,
-            )?;
+/// Prices a swap that delivers exactly `amount_out` to the user.
+pub fn quote_exact_out(
+    pair: &Pair,
+    protocol_fee_rate: u16,
+    amount_out: u64,
+    is_base_input: bool,
+) -> Result<SwapQuote> {
+    require!(pair.total_liquidity > 0, AmmError::InsufficientLiquidity);
+    require!(amount_out > 0, AmmError::ZeroAmount);
+
+    let (input_reserve, output_reserve) = directional_reserves(pair, is_base_input);
+
+    // --- MOJO -> Paired Token swap ---
+    // Gross up the MOJO needed by the curve so that it still covers the
+    // curve after the LP and protocol fees are taken from it.
+    let (amount_in, protocol_fee, curve_amount_in) = if is_base_input {
+        let curve_amount_in = get_amount_in(amount_out, input_reserve, output_reserve)?;
+
+        let amount_in_after_fee = gross_up(curve_amount_in, pair.lp_fee_rate)?;
+        let amount_in = gross_up(amount_in_after_fee, protocol_fee_rate)?;
+        let protocol_fee = fee_amount(amount_in, protocol_fee_rate)?;
+
+        (amount_in, protocol_fee, curve_amount_in)
+    }
+    // --- Paired Token -> MOJO swap ---
+    // Pull enough MOJO out of the pool to pay both the user and the fee.
+    else {
+        let gross_output_amount = gross_up(amount_out, protocol_fee_rate)?;
+
+        let protocol_fee = gross_output_amount
+            .checked_sub(amount_out)
+            .ok_or(AmmError::MathOverflow)?;
+
+        let curve_amount_in = get_amount_in(gross_output_amount, input_reserve, output_reserve)?;
+        let amount_in = gross_up(curve_amount_in, pair.lp_fee_rate)?;
+
+        (amount_in, protocol_fee, curve_amount_in)
+    };
+
+    require!(amount_in > 0, AmmError::ZeroAmount);
+
+    build_quote(
+        pair,
+        is_base_input,
+        amount_in,
+        amount_out,
+        protocol_fee,
+        curve_amount_in,
+    )
+}
 
-            let signer_seeds = &[
-                b"pair",
-                self.pair.base_token_mint.as_ref(),
-                self.pair.paired_token_mint.as_ref(),
-                &[self.pair.bump],
-            ];
+/// `quote_exact_in` for tokens with Token-2022 transfer fees: the pool is
+/// priced on what reaches the input vault, and `amount_received` is what is
+/// left of `amount_out` once it reaches the user.
+pub fn quote_exact_in_with_transfer_fees(
+    pair: &Pair,
+    protocol_fee_rate: u16,
+    amount_in: u64,
+    is_base_input: bool,
+    fees: &TransferFees,
+) -> Result<SwapQuote> {
+    let net_amount_in = amount_after_epoch_transfer_fee(fees.input, fees.epoch, amount_in)?;
+
+    let mut quote = quote_exact_in(pair, protocol_fee_rate, net_amount_in, is_base_input)?;
+    quote.amount_in = amount_in;
+    quote.amount_received =
+        amount_after_epoch_transfer_fee(fees.output, fees.epoch, quote.amount_out)?;
+
+    Ok(quote)
+}
 
-            // Transfer MOJO to user (minus fee)
-            transfer(
-                CpiContext::new_with_signer(
-                    self.token_program.to_account_info(),
-                    Transfer {
-                        from: output_vault.to_account_info(),
-                        to: output_account.to_account_info(),
-                        authority: self.pair.to_account_info(),
-                    },
-                    &[signer_seeds],
-                ),
-                amount_out_after_fee,
-            )?;
+/// `quote_exact_out` for tokens with Token-2022 transfer fees: the pool
+/// sends enough that exactly `amount_out` reaches the user, and the user
+/// sends enough that the required input reaches the vault.
+pub fn quote_exact_out_with_transfer_fees(
+    pair: &Pair,
+    protocol_fee_rate: u16,
+    amount_out: u64,
+    is_base_input: bool,
+    fees: &TransferFees,
+) -> Result<SwapQuote> {
+    let gross_amount_out = amount_before_epoch_transfer_fee(fees.output, fees.epoch, amount_out)?;
+
+    let mut quote = quote_exact_out(pair, protocol_fee_rate, gross_amount_out, is_base_input)?;
+    quote.amount_received = amount_out;
+    quote.amount_in = amount_before_epoch_transfer_fee(fees.input, fees.epoch, quote.amount_in)?;
+
+    Ok(quote)
+}
 
-            // Transfer MOJO fee to fee_collector
-            if protocol_fee > 0 {
-                transfer(
-                    CpiContext::new_with_signer(
-                        self.token_program.to_account_info(),
-                        Transfer {
-                            from: output_vault.to_account_info(),
-                            to: self.fee_collector.to_account_info(),
-                            authority: self.pair.to_account_info(),
-                        },
-                        &[signer_seeds],
-                    ),
-                    protocol_fee,
-                )?;
-            }
+/// [`quote_exact_in_with_transfer_fees`] with the fees of `input_mint` and
+/// `output_mint` in the current epoch.
+pub fn quote_exact_in_after_transfer_fees(
+    pair: &Pair,
+    protocol_fee_rate: u16,
+    amount_in: u64,
+    is_base_input: bool,
+    input_mint: &AccountInfo,
+    output_mint: &AccountInfo,
+) -> Result<SwapQuote> {
+    let (input, output) = (
+        mint_transfer_fee_config(input_mint)?,
+        mint_transfer_fee_config(output_mint)?,
+    );
+    let fees = TransferFees {
+        input: input.as_ref(),
+        output: output.as_ref(),
+        epoch: Clock::get()?.epoch,
+    };
+    quote_exact_in_with_transfer_fees(pair, protocol_fee_rate, amount_in, is_base_input, &fees)
+}
 
-            self.pair.paired_reserve = paired_reserve
-                .checked_add(amount_in)
-                .ok_or(AmmError::MathOverflow)?;
-            self.pair.base_reserve = base_reserve
-                .checked_sub(gross_output_amount)
-                .ok_or(AmmError::MathOverflow)?;
-        }
+/// [`quote_exact_out_with_transfer_fees`] with the fees of `input_mint` and
+/// `output_mint` in the current epoch.
+pub fn quote_exact_out_after_transfer_fees(
+    pair: &Pair,
+    protocol_fee_rate: u16,
+    amount_out: u64,
+    is_base_input: bool,
+    input_mint: &AccountInfo,
+    output_mint: &AccountInfo,
+) -> Result<SwapQuote> {
+    let (input, output) = (
+        mint_transfer_fee_config(input_mint)?,
+        mint_transfer_fee_config(output_mint)?,
+    );
+    let fees = TransferFees {
+        input: input.as_ref(),
+        output: output.as_ref(),
+        epoch: Clock::get()?.epoch,
+    };
+    quote_exact_out_with_transfer_fees(pair, protocol_fee_rate, amount_out, is_base_input, &fees)
+}
 
-        Ok(())
+/// Returns `(input_reserve, output_reserve)` for the given direction.
+fn directional_reserves(pair: &Pair, is_base_input: bool) -> (u64, u64) {
+    if is_base_input {
+        (pair.base_reserve, pair.paired_reserve)
+    } else {
+        (pair.paired_reserve, pair.base_reserve)
     }
 }
+
+/// Fills in the reserves after the swap and the curve price impact.
+fn build_quote(
+    pair: &Pair,
+    is_base_input: bool,
+    amount_in: u64,
+    amount_out: u64,
+    protocol_fee: u64,
+    curve_amount_in: u64,
+) -> Result<SwapQuote> {
+    let (new_base_reserve, new_paired_reserve) = if is_base_input {
+        (
+            pair.base_reserve
+                .checked_add(amount_in)
+                .ok_or(AmmError::MathOverflow)?
+                .checked_sub(protocol_fee)
+                .ok_or(AmmError::MathOverflow)?,
+            pair.paired_reserve
+                .checked_sub(amount_out)
+                .ok_or(AmmError::MathOverflow)?,
+        )
+    } else {
+        (
+            pair.base_reserve
+                .checked_sub(amount_out)
+                .ok_or(AmmError::MathOverflow)?
+                .checked_sub(protocol_fee)
+                .ok_or(AmmError::MathOverflow)?,
+            pair.paired_reserve
+                .checked_add(amount_in)
+                .ok_or(AmmError::MathOverflow)?,
+        )
+    };
+
+    // On x * y = k the execution price is worse than spot by dx / (x + dx)
+    let (input_reserve, _) = directional_reserves(pair, is_base_input);
+    let price_impact_bps = (curve_amount_in as u128)
+        .checked_mul(10_000)
+        .ok_or(AmmError::MathOverflow)?
+        .checked_div(input_reserve as u128 + curve_amount_in as u128)
+        .ok_or(AmmError::MathOverflow)? as u16;
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        amount_received: amount_out,
+        protocol_fee,
+        price_impact_bps,
+        new_base_reserve,
+        new_paired_reserve,
+    })
+}
@@ -0,0 +1,373 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{
+    error::AmmError, events::emit_event, instructions::swap::quote_exact_in_after_transfer_fees,
+    record_observation, transfer_hook::transfer_checked, Observations, Pair, PlatformState,
+    Swapped, TraderState,
+};
+
+/// Swaps one player token for another by routing through MOJO:
+/// paired → MOJO in `pair_in`, then MOJO → paired in `pair_out`.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    /// The platform state contains global configuration
+    #[account(
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        constraint = !platform_state.is_paused @ AmmError::TradingPaused,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    /// The user performing the swap
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// First hop: the pool the user sells their player token into
+    #[account(
+        mut,
+        seeds = [b"pair", pair_in.base_token_mint.as_ref(), pair_in.paired_token_mint.as_ref()],
+        bump = pair_in.bump,
+        constraint = pair_in.total_liquidity > 0 @ AmmError::InsufficientLiquidity,
+    )]
+    pub pair_in: Box<Account<'info, Pair>>,
+
+    /// Second hop: the pool the user buys their player token from
+    #[account(
+        mut,
+        seeds = [b"pair", pair_out.base_token_mint.as_ref(), pair_out.paired_token_mint.as_ref()],
+        bump = pair_out.bump,
+        constraint = pair_out.total_liquidity > 0 @ AmmError::InsufficientLiquidity,
+        constraint = pair_out.key() != pair_in.key() @ AmmError::InvalidPair,
+        constraint = pair_out.base_token_mint == pair_in.base_token_mint @ AmmError::InvalidBaseToken,
+    )]
+    pub pair_out: Box<Account<'info, Pair>>,
+
+    /// Base token mint (MOJO), shared by both pools
+    #[account(
+        address = pair_in.base_token_mint,
+        mint::token_program = token_program,
+    )]
+    pub base_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Mint of the player token being sold
+    #[account(
+        address = pair_in.paired_token_mint,
+        mint::token_program = input_token_program,
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Mint of the player token being bought
+    #[account(
+        address = pair_out.paired_token_mint,
+        mint::token_program = output_token_program,
+    )]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// MOJO vault of the first pool
+    #[account(mut, address = pair_in.base_vault)]
+    pub pair_in_base_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Player token vault of the first pool
+    #[account(mut, address = pair_in.paired_vault)]
+    pub pair_in_paired_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// MOJO vault of the second pool
+    #[account(mut, address = pair_out.base_vault)]
+    pub pair_out_base_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Player token vault of the second pool
+    #[account(mut, address = pair_out.paired_vault)]
+    pub pair_out_paired_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User's account for the player token being sold
+    #[account(
+        mut,
+        associated_token::mint = pair_in.paired_token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = input_token_program,
+    )]
+    pub user_input_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// User's account for the player token being bought
+    #[account(
+        mut,
+        associated_token::mint = pair_out.paired_token_mint,
+        associated_token::authority = user,
+        associated_token::token_program = output_token_program,
+    )]
+    pub user_output_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Platform fee collector account
+    #[account(
+        mut,
+        address = platform_state.fee_collector,
+    )]
+    pub fee_collector: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Tracks the user's last swap on the first pool for the cooldown
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + TraderState::INIT_SPACE,
+        seeds = [b"trader", pair_in.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub pair_in_trader_state: Box<Account<'info, TraderState>>,
+
+    /// Tracks the user's last swap on the second pool for the cooldown
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + TraderState::INIT_SPACE,
+        seeds = [b"trader", pair_out.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub pair_out_trader_state: Box<Account<'info, TraderState>>,
+
+    /// Price sample ring buffer for the first pool
+    #[account(
+        mut,
+        seeds = [b"observations", pair_in.key().as_ref()],
+        bump = pair_in_observations.load()?.bump,
+    )]
+    pub pair_in_observations: AccountLoader<'info, Observations>,
+
+    /// Price sample ring buffer for the second pool
+    #[account(
+        mut,
+        seeds = [b"observations", pair_out.key().as_ref()],
+        bump = pair_out_observations.load()?.bump,
+    )]
+    pub pair_out_observations: AccountLoader<'info, Observations>,
+
+    /// Token program of the base token (MOJO)
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Token program of the player token being sold
+    pub input_token_program: Interface<'info, TokenInterface>,
+    /// Token program of the player token being bought
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SwapRoute<'info> {
+    /// `remaining_accounts` carries the extra accounts of the transfer hooks
+    /// of either player token and is forwarded to every transfer.
+    pub fn swap_route(
+        &mut self,
+        amount_in: u64,
+        min_amount_out: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(!self.platform_state.is_paused, AmmError::TradingPaused);
+
+        let fee_rate = self.platform_state.protocol_fee_rate;
+
+        // --- Hop 1: Paired Token -> MOJO ---
+        // The protocol fee is taken from the MOJO coming out.
+        let hop_in = quote_exact_in_after_transfer_fees(
+            &self.pair_in,
+            fee_rate,
+            amount_in,
+            false,
+            &self.input_token_mint.to_account_info(),
+            &self.base_token_mint.to_account_info(),
+        )?;
+
+        // --- Hop 2: MOJO -> Paired Token ---
+        // The protocol fee is taken from the MOJO going in.
+        // The second pool is credited with the MOJO that survives the transfer.
+        let hop_out = quote_exact_in_after_transfer_fees(
+            &self.pair_out,
+            fee_rate,
+            hop_in.amount_out,
+            true,
+            &self.base_token_mint.to_account_info(),
+            &self.output_token_mint.to_account_info(),
+        )?;
+        let k_in_before = self.pair_in.k();
+        let k_out_before = self.pair_out.k();
+
+        let amount_out = hop_out.amount_out;
+        require!(amount_out > 0, AmmError::ZeroAmount);
+        require!(
+            hop_out.amount_received >= min_amount_out,
+            AmmError::SlippageExceeded
+        );
+
+        let clock = Clock::get()?;
+        self.pair_in_trader_state.record_swap(
+            self.user.key(),
+            self.pair_in.key(),
+            clock.unix_timestamp,
+            self.pair_in.swap_cooldown(&self.platform_state),
+        )?;
+        self.pair_out_trader_state.record_swap(
+            self.user.key(),
+            self.pair_out.key(),
+            clock.unix_timestamp,
+            self.pair_out.swap_cooldown(&self.platform_state),
+        )?;
+        self.pair_in
+            .update_price_cumulatives(clock.unix_timestamp)?;
+        self.pair_out
+            .update_price_cumulatives(clock.unix_timestamp)?;
+        let interval = self.platform_state.observation_interval_seconds;
+        record_observation(&self.pair_in_observations, &self.pair_in, interval)?;
+        record_observation(&self.pair_out_observations, &self.pair_out, interval)?;
+
+        let pair_in_seeds = &[
+            b"pair",
+            self.pair_in.base_token_mint.as_ref(),
+            self.pair_in.paired_token_mint.as_ref(),
+            &[self.pair_in.bump],
+        ];
+        let pair_out_seeds = &[
+            b"pair",
+            self.pair_out.base_token_mint.as_ref(),
+            self.pair_out.paired_token_mint.as_ref(),
+            &[self.pair_out.bump],
+        ];
+
+        // Transfer input paired token from user → first pool
+        transfer_checked(
+            CpiContext::new(
+                self.input_token_program.to_account_info(),
+                TransferChecked {
+                    from: self.user_input_ata.to_account_info(),
+                    mint: self.input_token_mint.to_account_info(),
+                    to: self.pair_in_paired_vault.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(remaining_accounts.to_vec()),
+            amount_in,
+            self.input_token_mint.decimals,
+        )?;
+
+        // Transfer first hop MOJO fee to fee_collector
+        if hop_in.protocol_fee > 0 {
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: self.pair_in_base_vault.to_account_info(),
+                        mint: self.base_token_mint.to_account_info(),
+                        to: self.fee_collector.to_account_info(),
+                        authority: self.pair_in.to_account_info(),
+                    },
+                    &[pair_in_seeds],
+                )
+                .with_remaining_accounts(remaining_accounts.to_vec()),
+                hop_in.protocol_fee,
+                self.base_token_mint.decimals,
+            )?;
+        }
+
+        // Move the intermediate MOJO straight from the first pool to the second
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.pair_in_base_vault.to_account_info(),
+                    mint: self.base_token_mint.to_account_info(),
+                    to: self.pair_out_base_vault.to_account_info(),
+                    authority: self.pair_in.to_account_info(),
+                },
+                &[pair_in_seeds],
+            )
+            .with_remaining_accounts(remaining_accounts.to_vec()),
+            hop_in.amount_out,
+            self.base_token_mint.decimals,
+        )?;
+
+        // Transfer second hop MOJO fee to fee_collector
+        if hop_out.protocol_fee > 0 {
+            transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: self.pair_out_base_vault.to_account_info(),
+                        mint: self.base_token_mint.to_account_info(),
+                        to: self.fee_collector.to_account_info(),
+                        authority: self.pair_out.to_account_info(),
+                    },
+                    &[pair_out_seeds],
+                )
+                .with_remaining_accounts(remaining_accounts.to_vec()),
+                hop_out.protocol_fee,
+                self.base_token_mint.decimals,
+            )?;
+        }
+
+        // Transfer output paired token from second pool → user
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.output_token_program.to_account_info(),
+                TransferChecked {
+                    from: self.pair_out_paired_vault.to_account_info(),
+                    mint: self.output_token_mint.to_account_info(),
+                    to: self.user_output_ata.to_account_info(),
+                    authority: self.pair_out.to_account_info(),
+                },
+                &[pair_out_seeds],
+            )
+            .with_remaining_accounts(remaining_accounts.to_vec()),
+            amount_out,
+            self.output_token_mint.decimals,
+        )?;
+
+        self.pair_in.base_reserve = hop_in.new_base_reserve;
+        self.pair_in.paired_reserve = hop_in.new_paired_reserve;
+        self.pair_out.base_reserve = hop_out.new_base_reserve;
+        self.pair_out.paired_reserve = hop_out.new_paired_reserve;
+
+        self.pair_in_base_vault.reload()?;
+        self.pair_in_paired_vault.reload()?;
+        self.pair_out_base_vault.reload()?;
+        self.pair_out_paired_vault.reload()?;
+        self.pair_in.check_invariant(
+            self.pair_in_base_vault.amount,
+            self.pair_in_paired_vault.amount,
+            Some(k_in_before),
+        )?;
+        self.pair_out.check_invariant(
+            self.pair_out_base_vault.amount,
+            self.pair_out_paired_vault.amount,
+            Some(k_out_before),
+        )?;
+
+        // One event per hop, as if each had been a separate swap
+        emit_event!(
+            self,
+            Swapped {
+                pair: self.pair_in.key(),
+                user: self.user.key(),
+                is_base_input: false,
+                amount_in: hop_in.amount_in,
+                amount_out: hop_in.amount_out,
+                protocol_fee: hop_in.protocol_fee,
+                base_reserve: self.pair_in.base_reserve,
+                paired_reserve: self.pair_in.paired_reserve,
+                timestamp: clock.unix_timestamp,
+            }
+        );
+        emit_event!(
+            self,
+            Swapped {
+                pair: self.pair_out.key(),
+                user: self.user.key(),
+                is_base_input: true,
+                amount_in: hop_out.amount_in,
+                amount_out: hop_out.amount_out,
+                protocol_fee: hop_out.protocol_fee,
+                base_reserve: self.pair_out.base_reserve,
+                paired_reserve: self.pair_out.paired_reserve,
+                timestamp: clock.unix_timestamp,
+            }
+        );
+
+        Ok(())
+    }
+}
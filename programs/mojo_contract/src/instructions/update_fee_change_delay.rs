@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, state::PlatformState, MIN_FEE_CHANGE_DELAY};
+
+#[derive(Accounts)]
+pub struct UpdateFeeChangeDelay<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+impl<'info> UpdateFeeChangeDelay<'info> {
+    /// Sets the notice later fee change proposals give, at least
+    /// `MIN_FEE_CHANGE_DELAY`. A pending change keeps the `effective_at` it
+    /// was proposed with.
+    pub fn update_fee_change_delay(&mut self, delay_seconds: i64) -> Result<()> {
+        require!(
+            delay_seconds >= MIN_FEE_CHANGE_DELAY,
+            AmmError::FeeChangeDelayTooShort
+        );
+
+        self.platform_state.fee_change_delay_seconds = delay_seconds;
+
+        msg!("Fee change delay updated to: {}s", delay_seconds);
+
+        Ok(())
+    }
+}
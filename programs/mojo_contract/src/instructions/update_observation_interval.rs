@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, state::PlatformState};
+
+#[derive(Accounts)]
+pub struct UpdateObservationInterval<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+impl<'info> UpdateObservationInterval<'info> {
+    pub fn update_observation_interval(&mut self, interval_seconds: i64) -> Result<()> {
+        require!(interval_seconds >= 0, AmmError::InvalidAmount);
+
+        self.platform_state.observation_interval_seconds = interval_seconds;
+
+        msg!("Observation interval updated to: {}s", interval_seconds);
+
+        Ok(())
+    }
+}
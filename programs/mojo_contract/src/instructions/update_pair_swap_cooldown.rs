@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::AmmError,
+    state::{Pair, PlatformState},
+};
+
+#[derive(Accounts)]
+pub struct UpdatePairSwapCooldown<'info> {
+    #[account(
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    #[account(
+        mut,
+        seeds = [b"pair", pair.base_token_mint.as_ref(), pair.paired_token_mint.as_ref()],
+        bump = pair.bump,
+    )]
+    pub pair: Account<'info, Pair>,
+
+    pub admin: Signer<'info>,
+}
+
+impl<'info> UpdatePairSwapCooldown<'info> {
+    /// Sets a per-pair cooldown, or clears it with `None` to fall back to the
+    /// platform default.
+    pub fn update_pair_swap_cooldown(&mut self, cooldown_seconds: Option<i64>) -> Result<()> {
+        if let Some(cooldown_seconds) = cooldown_seconds {
+            require!(cooldown_seconds >= 0, AmmError::InvalidAmount);
+        }
+
+        self.pair.swap_cooldown_seconds = cooldown_seconds;
+
+        msg!("Pair swap cooldown updated to: {:?}", cooldown_seconds);
+
+        Ok(())
+    }
+}
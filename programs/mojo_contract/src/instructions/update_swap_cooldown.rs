@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, state::PlatformState};
+
+#[derive(Accounts)]
+pub struct UpdateSwapCooldown<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        has_one = admin @ AmmError::Unauthorized,
+    )]
+    pub platform_state: Account<'info, PlatformState>,
+
+    pub admin: Signer<'info>,
+}
+
+impl<'info> UpdateSwapCooldown<'info> {
+    pub fn update_swap_cooldown(&mut self, cooldown_seconds: i64) -> Result<()> {
+        require!(cooldown_seconds >= 0, AmmError::InvalidAmount);
+
+        self.platform_state.swap_cooldown_seconds = cooldown_seconds;
+
+        msg!("Swap cooldown updated to: {}s", cooldown_seconds);
+
+        Ok(())
+    }
+}
@@ -1,32 +1,33 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token::{transfer_checked, TransferChecked}, token_interface::{Mint, TokenAccount, TokenInterface}};
-use crate::{error::AmmError, state::PlatformState};
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+use crate::{error::AmmError, events::emit_event, state::{FeesWithdrawn, PlatformState}};
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 pub struct WithdrawPlatformFees<'info> {
     #[account(constraint = admin.key() == platform_state.admin @ AmmError::Unauthorized)]
     pub admin: Signer<'info>,
 
     #[account(
-        has_one = admin
+        seeds = [b"platform-state"],
+        bump = platform_state.bump,
+        has_one = admin,
+        has_one = fee_collector,
     )]
     pub platform_state: Account<'info, PlatformState>,
 
-    // The protocol fee vault that holds the fees
-    #[account(
-        mut,
-        seeds = [b"protocol_fee", token_mint.key().as_ref()],
-        bump,
-    )]
-    pub protocol_fee_vault: Box<InterfaceAccount<'info, TokenAccount>>,
-    
+    // The platform's fee collector, which swaps pay the protocol fee into
+    #[account(mut)]
+    pub fee_collector: Box<InterfaceAccount<'info, TokenAccount>>,
+
     // The token mint for which we're withdrawing fees
+    #[account(address = fee_collector.mint @ AmmError::TokenMintMismatch)]
     pub token_mint: InterfaceAccount<'info, Mint>,
 
     // The destination for the fee withdrawal
     #[account(
         mut,
-        constraint = fee_destination.mint == protocol_fee_vault.mint @ AmmError::TokenMintMismatch
+        constraint = fee_destination.mint == fee_collector.mint @ AmmError::TokenMintMismatch
     )]
     pub fee_destination: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -37,36 +38,40 @@ pub struct WithdrawPlatformFees<'info> {
 impl<'info> WithdrawPlatformFees<'info> {
     pub fn withdraw_fees(&mut self, amount: u64) -> Result<()> {
         // Ensure the amount is valid
-        if amount <= 0 {
+        if amount == 0 {
             return Err(AmmError::InvalidAmount.into());
         }
 
-        // Transfer the fees from the protocol fee vault to the fee destination
+        // Transfer the fees from the fee collector to the fee destination.
+        // The collector is the platform state's token account, so the
+        // platform state signs.
         let cpi_accounts = TransferChecked {
-                    from: self.protocol_fee_vault.to_account_info(),
-                    mint: self.token_mint.to_account_info(),
-                    to: self.fee_destination.to_account_info(),
-                    authority: self.protocol_fee_vault.to_account_info(),
-                };
-
+            from: self.fee_collector.to_account_info(),
+            mint: self.token_mint.to_account_info(),
+            to: self.fee_destination.to_account_info(),
+            authority: self.platform_state.to_account_info(),
+        };
 
-        let vault_mint = &self.protocol_fee_vault.mint;
         let seeds = [
-            b"protocol_fee",
-            vault_mint.as_ref(),
+            b"platform-state".as_ref(),
+            &[self.platform_state.bump],
         ];
         let signer = &[&seeds[..]];
 
         let cpi_program = self.token_program.to_account_info();
-        
-        let cpi_ctx = CpiContext::new_with_signer(
-                cpi_program,
-                cpi_accounts,
-                signer,
-            );
-        
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+
         transfer_checked(cpi_ctx, amount, self.token_mint.decimals)?;
 
+        emit_event!(self, FeesWithdrawn {
+            admin: self.admin.key(),
+            token_mint: self.token_mint.key(),
+            destination: self.fee_destination.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
-}
\ No newline at end of file
+}
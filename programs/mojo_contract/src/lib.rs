@@ -1,11 +1,18 @@
 use anchor_lang::prelude::*;
 
 pub mod constants;
+pub mod curve;
+pub mod deadline;
 pub mod error;
+pub mod events;
 pub mod instructions;
+pub mod native_sol;
 pub mod state;
+pub mod transfer_fee;
+pub mod transfer_hook;
 
 pub use constants::*;
+use deadline::check_deadline;
 pub use instructions::*;
 pub use state::*;
 
@@ -15,17 +22,78 @@ declare_id!("AMGoaF1FYy6qijdYnLwqQmpxe7eQTVwAy2SvTQbHQcER");
 pub mod mojo_contract {
     use super::*;
 
-    pub fn swap(
-        ctx: Context<Swap>,
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
         input_amount: u64,
         min_output_amount: u64,
         is_base_input: bool,
+        deadline: Option<i64>,
     ) -> Result<()> {
+        check_deadline(deadline)?;
+        ctx.accounts.swap(
+            input_amount,
+            min_output_amount,
+            is_base_input,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    pub fn swap_exact_out<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        amount_out: u64,
+        max_amount_in: u64,
+        is_base_input: bool,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
+        ctx.accounts.swap_exact_out(
+            amount_out,
+            max_amount_in,
+            is_base_input,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    pub fn swap_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        input_amount: u64,
+        min_output_amount: u64,
+        is_base_input: bool,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
+        ctx.accounts.swap_sol(
+            input_amount,
+            min_output_amount,
+            is_base_input,
+            &ctx.bumps,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
         ctx.accounts
-            .swap(input_amount, min_output_amount, is_base_input)?;
+            .swap_route(amount_in, min_amount_out, ctx.remaining_accounts)?;
         Ok(())
     }
 
+    pub fn quote_swap(
+        ctx: Context<QuoteSwap>,
+        amount_in: u64,
+        is_base_input: bool,
+    ) -> Result<SwapQuote> {
+        ctx.accounts.quote_swap(amount_in, is_base_input)
+    }
+
     pub fn initialize_platform(
         ctx: Context<InitializePlatform>,
         protocol_fee_rate: u16,
@@ -39,29 +107,121 @@ pub mod mojo_contract {
         Ok(())
     }
 
-    pub fn create_pair(ctx: Context<CreatePair>) -> Result<()> {
-        ctx.accounts.create_pair(ctx.bumps.pair)?;
+    pub fn create_pair(ctx: Context<CreatePair>, lp_fee_rate: u16) -> Result<()> {
+        ctx.accounts.create_pair(lp_fee_rate, &ctx.bumps)?;
         Ok(())
     }
 
-    pub fn add_liquidity(
-        ctx: Context<AddLiquidity>,
-        base_amount: u64,
-        paired_amount: u64,
+    pub fn add_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddLiquidity<'info>>,
+        base_desired: u64,
+        paired_desired: u64,
+        base_min: u64,
+        paired_min: u64,
+        min_lp_out: u64,
+        deadline: Option<i64>,
     ) -> Result<()> {
-        ctx.accounts.add_liquidity(base_amount, paired_amount)?;
+        check_deadline(deadline)?;
+        ctx.accounts.add_liquidity(
+            base_desired,
+            paired_desired,
+            base_min,
+            paired_min,
+            min_lp_out,
+            ctx.remaining_accounts,
+        )?;
 
         Ok(())
     }
 
-    pub fn remove_liquidity(
-        ctx: Context<RemoveLiquidity>,
+    pub fn add_liquidity_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddLiquidity<'info>>,
+        base_desired: u64,
+        paired_desired: u64,
+        base_min: u64,
+        paired_min: u64,
+        min_lp_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
+        ctx.accounts.add_liquidity_sol(
+            base_desired,
+            paired_desired,
+            base_min,
+            paired_min,
+            min_lp_out,
+            &ctx.bumps,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    pub fn add_liquidity_single_sided<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddLiquiditySingleSided<'info>>,
+        amount_in: u64,
+        is_base_input: bool,
+        min_lp_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
+        ctx.accounts.add_liquidity_single_sided(
+            amount_in,
+            is_base_input,
+            min_lp_out,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveLiquidity<'info>>,
         lp_amount: u64,
         min_base: u64,
         min_paired: u64,
+        deadline: Option<i64>,
     ) -> Result<()> {
-        ctx.accounts
-            .remove_liquidity(lp_amount, min_base, min_paired)?;
+        check_deadline(deadline)?;
+        ctx.accounts.remove_liquidity(
+            lp_amount,
+            min_base,
+            min_paired,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_liquidity_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveLiquidity<'info>>,
+        lp_amount: u64,
+        min_base: u64,
+        min_paired: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
+        ctx.accounts.remove_liquidity_sol(
+            lp_amount,
+            min_base,
+            min_paired,
+            &ctx.bumps,
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_liquidity_single_sided<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveLiquiditySingleSided<'info>>,
+        lp_amount: u64,
+        want_base: bool,
+        min_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        check_deadline(deadline)?;
+        ctx.accounts.remove_liquidity_single_sided(
+            lp_amount,
+            want_base,
+            min_out,
+            ctx.remaining_accounts,
+        )?;
         Ok(())
     }
 
@@ -71,8 +231,63 @@ pub mod mojo_contract {
         Ok(())
     }
 
-    pub fn update_fee_rate(ctx: Context<UpdateFeeRate>, new_fee_rate: u16) -> Result<()> {
-        ctx.accounts.update_fee_rate(new_fee_rate)?;
+    pub fn propose_fee_change(ctx: Context<ProposeFeeChange>, new_fee_rate: u16) -> Result<()> {
+        ctx.accounts.propose_fee_change(new_fee_rate, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn execute_fee_change(ctx: Context<ExecuteFeeChange>) -> Result<()> {
+        ctx.accounts.execute_fee_change()?;
+        Ok(())
+    }
+
+    pub fn cancel_fee_change(ctx: Context<CancelFeeChange>) -> Result<()> {
+        ctx.accounts.cancel_fee_change()?;
+        Ok(())
+    }
+
+    pub fn update_swap_cooldown(
+        ctx: Context<UpdateSwapCooldown>,
+        cooldown_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.update_swap_cooldown(cooldown_seconds)?;
+        Ok(())
+    }
+
+    pub fn update_pair_swap_cooldown(
+        ctx: Context<UpdatePairSwapCooldown>,
+        cooldown_seconds: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.update_pair_swap_cooldown(cooldown_seconds)?;
+        Ok(())
+    }
+
+    pub fn get_twap(
+        ctx: Context<GetTwap>,
+        start_index: u16,
+        end_index: Option<u16>,
+    ) -> Result<TwapResult> {
+        ctx.accounts.get_twap(start_index, end_index)
+    }
+
+    pub fn grow_observations(ctx: Context<GrowObservations>, new_capacity: u16) -> Result<()> {
+        ctx.accounts.grow_observations(new_capacity)?;
+        Ok(())
+    }
+
+    pub fn update_observation_interval(
+        ctx: Context<UpdateObservationInterval>,
+        interval_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.update_observation_interval(interval_seconds)?;
+        Ok(())
+    }
+
+    pub fn update_fee_change_delay(
+        ctx: Context<UpdateFeeChangeDelay>,
+        delay_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.update_fee_change_delay(delay_seconds)?;
         Ok(())
     }
 
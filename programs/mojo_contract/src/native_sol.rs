@@ -0,0 +1,212 @@
+//! Native SOL for pairs whose paired token is wrapped SOL.
+//!
+//! The `*_sol` instructions fund the user's WSOL account with lamports before
+//! the operation. Afterwards only the WSOL the operation left there beyond
+//! what the user already held is unwrapped: it moves to a temporary account
+//! that is closed to the user within the same instruction, so the user's
+//! own WSOL and their WSOL account are left alone.
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::program_pack::Pack,
+    system_program::{self, Allocate, Assign, CreateAccount, Transfer},
+};
+use anchor_spl::{
+    token::spl_token,
+    token_interface::{
+        close_account, initialize_account3, sync_native, transfer_checked, CloseAccount,
+        InitializeAccount3, Mint, SyncNative, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::{error::AmmError, Pair, WSOL_MINT};
+
+/// Fails unless the pair's paired token is wrapped SOL.
+pub fn require_native_sol_pair(pair: &Pair) -> Result<()> {
+    require_keys_eq!(
+        pair.paired_token_mint,
+        WSOL_MINT,
+        AmmError::NotNativeSolPair
+    );
+    Ok(())
+}
+
+/// The [`NativeSol`] accounts of an instruction whose temporary account is
+/// optional, failing if the instruction was sent without it.
+pub fn native_sol<'a, 'info>(
+    user: &'a Signer<'info>,
+    wsol_account: &'a InterfaceAccount<'info, TokenAccount>,
+    wsol_mint: &'a InterfaceAccount<'info, Mint>,
+    temporary_account: &'a Option<UncheckedAccount<'info>>,
+    temporary_bump: Option<u8>,
+    token_program: &'a Interface<'info, TokenInterface>,
+    system_program: &'a Program<'info, System>,
+) -> Result<NativeSol<'a, 'info>> {
+    let (Some(temporary_account), Some(temporary_bump)) = (temporary_account, temporary_bump)
+    else {
+        return err!(ErrorCode::ConstraintAccountIsNone);
+    };
+    Ok(NativeSol {
+        user,
+        wsol_account,
+        wsol_mint,
+        temporary_account,
+        temporary_bump,
+        token_program,
+        system_program,
+    })
+}
+
+/// WSOL in `wsol_account` beyond `held`, its balance before the instruction,
+/// which the `*_sol` instructions pay out as SOL.
+pub fn native_sol_out(
+    wsol_account: &mut InterfaceAccount<'_, TokenAccount>,
+    held: u64,
+) -> Result<u64> {
+    wsol_account.reload()?;
+    wsol_account
+        .amount
+        .checked_sub(held)
+        .ok_or(AmmError::MathOverflow.into())
+}
+
+/// The accounts a `*_sol` instruction wraps and unwraps SOL with.
+pub struct NativeSol<'a, 'info> {
+    pub user: &'a Signer<'info>,
+    /// The user's WSOL account the operation runs against
+    pub wsol_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub wsol_mint: &'a InterfaceAccount<'info, Mint>,
+    /// Address of the temporary account, seeded from the user
+    pub temporary_account: &'a UncheckedAccount<'info>,
+    pub temporary_bump: u8,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub system_program: &'a Program<'info, System>,
+}
+
+impl<'info> NativeSol<'_, 'info> {
+    /// Moves `lamports` from the user into their WSOL account and syncs its
+    /// token balance.
+    pub fn wrap(&self, lamports: u64) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.user.to_account_info(),
+                    to: self.wsol_account.to_account_info(),
+                },
+            ),
+            lamports,
+        )?;
+
+        sync_native(CpiContext::new(
+            self.token_program.to_account_info(),
+            SyncNative {
+                account: self.wsol_account.to_account_info(),
+            },
+        ))
+    }
+
+    /// Pays `amount` of the user's WSOL out to them as SOL through the
+    /// temporary account.
+    pub fn unwrap(&self, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        self.create_temporary_account()?;
+        initialize_account3(CpiContext::new(
+            self.token_program.to_account_info(),
+            InitializeAccount3 {
+                account: self.temporary_account.to_account_info(),
+                mint: self.wsol_mint.to_account_info(),
+                authority: self.user.to_account_info(),
+            },
+        ))?;
+
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.wsol_account.to_account_info(),
+                    mint: self.wsol_mint.to_account_info(),
+                    to: self.temporary_account.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            amount,
+            self.wsol_mint.decimals,
+        )?;
+
+        close_account(CpiContext::new(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.temporary_account.to_account_info(),
+                destination: self.user.to_account_info(),
+                authority: self.user.to_account_info(),
+            },
+        ))
+    }
+
+    /// Creates the temporary account as a token account, paid for by the
+    /// user. Lamports someone already sent to the address are kept, as with
+    /// Anchor's `init`, so they cannot block the instruction.
+    fn create_temporary_account(&self) -> Result<()> {
+        let user_key = self.user.key();
+        let seeds: &[&[u8]] = &[b"native-sol", user_key.as_ref(), &[self.temporary_bump]];
+        let space = spl_token::state::Account::LEN;
+        let rent = Rent::get()?.minimum_balance(space);
+        let system = self.system_program.to_account_info();
+        let temporary = self.temporary_account.to_account_info();
+
+        let lamports = temporary.lamports();
+        if lamports == 0 {
+            return system_program::create_account(
+                CpiContext::new_with_signer(
+                    system,
+                    CreateAccount {
+                        from: self.user.to_account_info(),
+                        to: temporary,
+                    },
+                    &[seeds],
+                ),
+                rent,
+                space as u64,
+                self.token_program.key,
+            );
+        }
+
+        let top_up = rent.saturating_sub(lamports);
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system.clone(),
+                    Transfer {
+                        from: self.user.to_account_info(),
+                        to: temporary.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system.clone(),
+                Allocate {
+                    account_to_allocate: temporary.clone(),
+                },
+                &[seeds],
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system,
+                Assign {
+                    account_to_assign: temporary,
+                },
+                &[seeds],
+            ),
+            self.token_program.key,
+        )
+    }
+}
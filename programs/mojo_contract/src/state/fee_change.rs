@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+/// A protocol fee rate change announced by the admin, which can only be
+/// applied once `effective_at` has passed. There is at most one at a time.
+#[account]
+#[derive(InitSpace)]
+pub struct PendingFeeChange {
+    pub new_fee_rate: u16,
+    pub proposed_at: i64,
+    pub effective_at: i64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct FeeChangeProposed {
+    pub admin: Pubkey,
+    pub current_fee_rate: u16,
+    pub new_fee_rate: u16,
+    pub effective_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeChangeCancelled {
+    pub admin: Pubkey,
+    pub new_fee_rate: u16,
+    pub timestamp: i64,
+}
@@ -1,5 +1,11 @@
+pub mod fee_change;
+pub mod observations;
 pub mod pair;
 pub mod platform;
+pub mod trader;
 
+pub use fee_change::*;
+pub use observations::*;
 pub use pair::*;
-pub use platform::*;
\ No newline at end of file
+pub use platform::*;
+pub use trader::*;
@@ -0,0 +1,124 @@
+use std::mem::size_of;
+
+use anchor_lang::prelude::*;
+
+use crate::{error::AmmError, state::Pair};
+
+/// A single price sample taken from a pair.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub base_price_cumulative: u128,
+    pub paired_price_cumulative: u128,
+    pub liquidity: u64, // Pair LP supply at the time of the sample
+}
+
+/// Ring buffer of price samples for one pair, seeded from the pair key.
+///
+/// The account holds this header followed by `capacity` borsh-encoded
+/// `Observation` slots. It is zero-copy so a swap reads and writes one slot
+/// instead of deserializing the whole buffer onto the heap.
+#[account(zero_copy)]
+#[derive(Debug)]
+pub struct Observations {
+    pub pair: Pubkey,
+    pub index: u16,    // Slot holding the most recent sample
+    pub count: u16,    // Number of slots written so far
+    pub capacity: u16, // Number of slots after the header
+    pub bump: u8,
+    pub padding: [u8; 1],
+}
+
+impl Observations {
+    /// Account size for a buffer holding `capacity` samples.
+    pub const fn space(capacity: usize) -> usize {
+        8 + size_of::<Observations>() + capacity * Observation::INIT_SPACE
+    }
+
+    /// Splits an observations account's data into its header and its slots.
+    /// The discriminator is not checked; `AccountLoader` already has.
+    pub fn split_mut(data: &mut [u8]) -> (&mut Self, &mut [u8]) {
+        let (header, slots) = data[8..].split_at_mut(size_of::<Self>());
+        (bytemuck::from_bytes_mut(header), slots)
+    }
+
+    /// Copies the header out of an observations account's data and returns
+    /// it with the slots. The discriminator is not checked.
+    pub fn read(data: &[u8]) -> (Self, &[u8]) {
+        let (header, slots) = data[8..].split_at(size_of::<Self>());
+        (bytemuck::pod_read_unaligned(header), slots)
+    }
+
+    /// The sample in slot `index`, which must have been written.
+    pub fn sample(&self, slots: &[u8], index: u16) -> Result<Observation> {
+        require!(index < self.count, AmmError::ObservationNotFound);
+        read_slot(slots, index as usize)
+    }
+
+    /// Writes a sample from the pair's current accumulators, unless the last
+    /// sample is less than `interval` seconds old.
+    pub fn record(&mut self, slots: &mut [u8], pair: &Pair, interval: i64) -> Result<()> {
+        let capacity = self.capacity as usize;
+        if capacity == 0 {
+            return Ok(());
+        }
+
+        let now = pair.last_swap_time;
+        if self.count > 0 {
+            let last = read_slot(slots, self.index as usize)?.timestamp;
+            if now.saturating_sub(last) < interval.max(1) {
+                return Ok(());
+            }
+        }
+
+        let next = if self.count == 0 {
+            0
+        } else {
+            (self.index as usize + 1) % capacity
+        };
+
+        let sample = Observation {
+            timestamp: now,
+            base_price_cumulative: pair.base_price_cumulative,
+            paired_price_cumulative: pair.paired_price_cumulative,
+            liquidity: pair.total_liquidity,
+        };
+        sample.serialize(&mut &mut slots[slot_range(next)])?;
+        self.index = next as u16;
+        self.count = (self.count as usize + 1).min(capacity) as u16;
+        Ok(())
+    }
+
+    /// Grows the buffer to `capacity` slots. Existing samples are rotated
+    /// oldest-first so the new empty slots sit after the latest sample.
+    pub fn grow(&mut self, slots: &mut [u8], capacity: u16) {
+        let old = self.capacity as usize;
+        if self.count as usize == old && self.count > 0 {
+            let oldest = (self.index as usize + 1) % old;
+            slots[..old * Observation::INIT_SPACE].rotate_left(oldest * Observation::INIT_SPACE);
+            self.index = self.count - 1;
+        }
+        self.capacity = capacity;
+    }
+}
+
+/// Records a sample into the observations account behind `loader`. See
+/// [`Observations::record`].
+pub fn record_observation(
+    loader: &AccountLoader<Observations>,
+    pair: &Pair,
+    interval: i64,
+) -> Result<()> {
+    let info = loader.to_account_info();
+    let mut data = info.try_borrow_mut_data()?;
+    let (observations, slots) = Observations::split_mut(&mut data);
+    observations.record(slots, pair, interval)
+}
+
+fn slot_range(index: usize) -> std::ops::Range<usize> {
+    index * Observation::INIT_SPACE..(index + 1) * Observation::INIT_SPACE
+}
+
+fn read_slot(slots: &[u8], index: usize) -> Result<Observation> {
+    Ok(Observation::deserialize(&mut &slots[slot_range(index)])?)
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::{error::AmmError, PlatformState};
+
 #[account]
 #[derive(InitSpace)]
 pub struct Pair {
@@ -11,7 +13,130 @@ pub struct Pair {
     pub total_liquidity: u64, // Total LP tokens minted
     pub bump: u8,
     pub last_swap_time: i64,
-    // pub protocol_fee_rate: u16, // Swap fee (e.g., 30 = 0.3%)
+    pub lp_fee_rate: u16,     // LP swap fee kept in the pool (e.g., 30 = 0.3%)
     pub base_vault: Pubkey,
     pub paired_vault: Pubkey,
+    pub swap_cooldown_seconds: Option<i64>, // Overrides the platform cooldown when set
+    pub base_price_cumulative: u128,   // Σ paired/base price × seconds, Q64.64
+    pub paired_price_cumulative: u128, // Σ base/paired price × seconds, Q64.64
+}
+
+impl Pair {
+    /// Cooldown between swaps by the same user, falling back to the platform
+    /// default when the pair has no override.
+    pub fn swap_cooldown(&self, platform: &PlatformState) -> i64 {
+        self.swap_cooldown_seconds
+            .unwrap_or(platform.swap_cooldown_seconds)
+    }
+
+    /// Price accumulators as they would read at `now`, extending the current
+    /// reserves over the time since the last update. Accumulators wrap on
+    /// overflow; consumers take wrapping differences, like Uniswap v2.
+    pub fn price_cumulatives_at(&self, now: i64) -> Result<(u128, u128)> {
+        let elapsed = now
+            .checked_sub(self.last_swap_time)
+            .ok_or(AmmError::MathOverflow)?;
+
+        if elapsed <= 0 || self.base_reserve == 0 || self.paired_reserve == 0 {
+            return Ok((self.base_price_cumulative, self.paired_price_cumulative));
+        }
+
+        let base_price = ((self.paired_reserve as u128) << 64) / self.base_reserve as u128;
+        let paired_price = ((self.base_reserve as u128) << 64) / self.paired_reserve as u128;
+
+        Ok((
+            self.base_price_cumulative
+                .wrapping_add(base_price.wrapping_mul(elapsed as u128)),
+            self.paired_price_cumulative
+                .wrapping_add(paired_price.wrapping_mul(elapsed as u128)),
+        ))
+    }
+
+    /// Accrues the price accumulators up to `now` using the reserves before
+    /// they change. Must run before every reserve update.
+    pub fn update_price_cumulatives(&mut self, now: i64) -> Result<()> {
+        let (base_price_cumulative, paired_price_cumulative) = self.price_cumulatives_at(now)?;
+        self.base_price_cumulative = base_price_cumulative;
+        self.paired_price_cumulative = paired_price_cumulative;
+        self.last_swap_time = now;
+        Ok(())
+    }
+
+    /// Constant product `base_reserve * paired_reserve`.
+    pub fn k(&self) -> u128 {
+        self.base_reserve as u128 * self.paired_reserve as u128
+    }
+
+    /// Checks the reserves after a state change: the vault balances must
+    /// cover what the pair has recorded, and when `k_before` is given the
+    /// constant product must not have dropped below it.
+    pub fn check_invariant(
+        &self,
+        base_vault_balance: u64,
+        paired_vault_balance: u64,
+        k_before: Option<u128>,
+    ) -> Result<()> {
+        require!(
+            base_vault_balance >= self.base_reserve
+                && paired_vault_balance >= self.paired_reserve,
+            AmmError::InvariantViolated
+        );
+        if let Some(k_before) = k_before {
+            require!(self.k() >= k_before, AmmError::InvariantViolated);
+        }
+        Ok(())
+    }
+}
+
+#[event]
+pub struct PairCreated {
+    pub pair: Pubkey,
+    pub creator: Pubkey,
+    pub base_token_mint: Pubkey,
+    pub paired_token_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub lp_fee_rate: u16,
+    pub timestamp: i64,
+}
+
+/// Amounts are what the pool was credited with, after transfer fees.
+#[event]
+pub struct LiquidityAdded {
+    pub pair: Pubkey,
+    pub user: Pubkey,
+    pub base_amount: u64,
+    pub paired_amount: u64,
+    pub lp_minted: u64,
+    pub base_reserve: u64,
+    pub paired_reserve: u64,
+    pub total_liquidity: u64,
+    pub timestamp: i64,
+}
+
+/// Amounts are what left the pool, before transfer fees.
+#[event]
+pub struct LiquidityRemoved {
+    pub pair: Pubkey,
+    pub user: Pubkey,
+    pub base_amount: u64,
+    pub paired_amount: u64,
+    pub lp_burned: u64,
+    pub base_reserve: u64,
+    pub paired_reserve: u64,
+    pub total_liquidity: u64,
+    pub timestamp: i64,
+}
+
+/// One swap against one pair, with the reserves after it.
+#[event]
+pub struct Swapped {
+    pub pair: Pubkey,
+    pub user: Pubkey,
+    pub is_base_input: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub protocol_fee: u64,
+    pub base_reserve: u64,
+    pub paired_reserve: u64,
+    pub timestamp: i64,
 }
@@ -9,6 +9,9 @@ pub struct PlatformState {
     pub is_paused: bool,         // Emergency stop  
     pub protocol_fee_rate: u16,
     pub bump: u8,
+    pub swap_cooldown_seconds: i64, // Min seconds between swaps per user and pair
+    pub observation_interval_seconds: i64, // Min seconds between price samples
+    pub fee_change_delay_seconds: i64, // Notice given before a fee change applies
 }  
 
 
@@ -17,4 +20,30 @@ pub struct PlatformPauseChanged {
     pub admin: Pubkey,
     pub paused: bool,
     pub timestamp: i64,
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct PlatformInitialized {
+    pub admin: Pubkey,
+    pub base_token_mint: Pubkey,
+    pub fee_collector: Pubkey,
+    pub protocol_fee_rate: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeRateUpdated {
+    pub admin: Pubkey,
+    pub old_fee_rate: u16,
+    pub new_fee_rate: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub admin: Pubkey,
+    pub token_mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::error::AmmError;
+
+/// Per-user, per-pair trading record used to enforce the swap cooldown.
+#[account]
+#[derive(InitSpace)]
+pub struct TraderState {
+    pub user: Pubkey,
+    pub pair: Pubkey,
+    pub last_swap_time: i64,
+}
+
+impl TraderState {
+    /// Records a swap at `now`, failing if the user's previous swap on this
+    /// pair was less than `cooldown` seconds ago.
+    pub fn record_swap(
+        &mut self,
+        user: Pubkey,
+        pair: Pubkey,
+        now: i64,
+        cooldown: i64,
+    ) -> Result<()> {
+        if cooldown > 0 && self.last_swap_time != 0 {
+            let ready_at = self
+                .last_swap_time
+                .checked_add(cooldown)
+                .ok_or(AmmError::MathOverflow)?;
+            require!(now >= ready_at, AmmError::SwapCooldown);
+        }
+
+        self.user = user;
+        self.pair = pair;
+        self.last_swap_time = now;
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! Token-2022 transfer fees.
+//!
+//! A mint with the TransferFee extension withholds part of every transfer in
+//! the recipient account, so the amount that arrives is less than the amount
+//! sent. Reserves and slippage checks must use what actually arrives.
+//!
+//! The math takes a mint's `TransferFeeConfig`, `None` for a mint without
+//! fees, and the epoch, so off-chain quotes can run it too. The functions
+//! taking a mint account read both for the current transaction.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint,
+};
+
+use crate::error::AmmError;
+
+/// Transfer fee configurations of a swap's input and output mints, and the
+/// epoch the swap's transfers happen in.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TransferFees<'a> {
+    pub input: Option<&'a TransferFeeConfig>,
+    pub output: Option<&'a TransferFeeConfig>,
+    pub epoch: u64,
+}
+
+/// Fee withheld when `amount` is transferred in `epoch`.
+pub fn epoch_transfer_fee(
+    config: Option<&TransferFeeConfig>,
+    epoch: u64,
+    amount: u64,
+) -> Result<u64> {
+    match config {
+        Some(config) => Ok(config
+            .calculate_epoch_fee(epoch, amount)
+            .ok_or(AmmError::MathOverflow)?),
+        None => Ok(0),
+    }
+}
+
+/// Fee to add on top of `net` so that `net` arrives after a transfer in
+/// `epoch`.
+pub fn inverse_epoch_transfer_fee(
+    config: Option<&TransferFeeConfig>,
+    epoch: u64,
+    net: u64,
+) -> Result<u64> {
+    match config {
+        Some(config) => Ok(config
+            .calculate_inverse_epoch_fee(epoch, net)
+            .ok_or(AmmError::MathOverflow)?),
+        None => Ok(0),
+    }
+}
+
+/// Amount that arrives when `amount` is transferred in `epoch`.
+pub fn amount_after_epoch_transfer_fee(
+    config: Option<&TransferFeeConfig>,
+    epoch: u64,
+    amount: u64,
+) -> Result<u64> {
+    Ok(amount
+        .checked_sub(epoch_transfer_fee(config, epoch, amount)?)
+        .ok_or(AmmError::MathOverflow)?)
+}
+
+/// Amount to send so that `net` arrives after a transfer in `epoch`.
+pub fn amount_before_epoch_transfer_fee(
+    config: Option<&TransferFeeConfig>,
+    epoch: u64,
+    net: u64,
+) -> Result<u64> {
+    Ok(net
+        .checked_add(inverse_epoch_transfer_fee(config, epoch, net)?)
+        .ok_or(AmmError::MathOverflow)?)
+}
+
+/// Fee withheld when `amount` of `mint` is transferred in the current epoch.
+/// Zero for SPL Token mints and Token-2022 mints without the extension.
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    epoch_transfer_fee(
+        mint_transfer_fee_config(mint)?.as_ref(),
+        Clock::get()?.epoch,
+        amount,
+    )
+}
+
+/// Amount that arrives when `amount` of `mint` is transferred.
+pub fn amount_after_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    amount_after_epoch_transfer_fee(
+        mint_transfer_fee_config(mint)?.as_ref(),
+        Clock::get()?.epoch,
+        amount,
+    )
+}
+
+/// The transfer fee configuration of a mint account, given its owner and
+/// data, if it is a Token-2022 mint with the TransferFee extension.
+pub fn transfer_fee_config(owner: &Pubkey, data: &[u8]) -> Result<Option<TransferFeeConfig>> {
+    if *owner != spl_token_2022::ID {
+        return Ok(None);
+    }
+
+    let mint = StateWithExtensions::<Mint>::unpack(data)?;
+    Ok(mint.get_extension::<TransferFeeConfig>().ok().copied())
+}
+
+/// [`transfer_fee_config`] of a mint account.
+pub fn mint_transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    transfer_fee_config(mint.owner, &mint.try_borrow_data()?)
+}
@@ -0,0 +1,31 @@
+//! Token-2022 transfer hooks.
+//!
+//! A mint with the TransferHook extension makes Token-2022 call the hook
+//! program on every transfer, with extra accounts listed in the mint's
+//! validation account. The caller passes those accounts after the named
+//! ones and they are forwarded to every transfer.
+
+use anchor_lang::prelude::*;
+use anchor_spl::{token_2022::spl_token_2022, token_interface::TransferChecked};
+
+/// `transfer_checked` that forwards `ctx.remaining_accounts` to a transfer
+/// hook. anchor-spl's version only hands Token-2022 the four named
+/// accounts, so a hooked mint could never find its extra accounts.
+pub fn transfer_checked<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
@@ -0,0 +1,109 @@
+//! Minimal Token-2022 transfer hook for the mojo_contract test suite.
+//!
+//! Every transfer of a hooked mint bumps a per-mint counter. The counter is
+//! an extra account, so a transfer only succeeds when the caller forwards
+//! the accounts listed in the mint's validation account.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+declare_id!("GuWyrzhPBbtE2B4H789RTeMRUXSLwSFixjQhyTboZHK2");
+
+#[program]
+pub mod test_transfer_hook {
+    use super::*;
+
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas()?,
+        )?;
+
+        ctx.accounts.counter.transfers = 0;
+        Ok(())
+    }
+
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+        ctx.accounts.counter.transfers += 1;
+        Ok(())
+    }
+}
+
+/// The counter PDA, seeded by the mint (account index 1 of `Execute`).
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: b"counter".to_vec(),
+            },
+            Seed::AccountKey { index: 1 },
+        ],
+        false,
+        true,
+    )?])
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Validation account, written by `ExtraAccountMetaList::init`
+    #[account(
+        init,
+        payer = payer,
+        space = ExtraAccountMetaList::size_of(1).unwrap(),
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [b"counter", mint.key().as_ref()],
+        bump,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts in the order Token-2022 passes them to `Execute`.
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    #[account(token::mint = mint)]
+    pub source_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(token::mint = mint)]
+    pub destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Owner or delegate of the source account
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Validation account for this mint
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"counter", mint.key().as_ref()], bump)]
+    pub counter: Account<'info, Counter>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Counter {
+    pub transfers: u64,
+}
@@ -1,15 +1,24 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { MojoContract } from "../target/types/mojo_contract"; // Replace with your actual program name
+import { TestTransferHook } from "../target/types/test_transfer_hook";
 import { assert } from "chai";
 
 import {
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  NATIVE_MINT,
   createMint,
   getAccount,
   createAssociatedTokenAccountIdempotentInstruction,
   createInitializeMint2Instruction,
   createMintToInstruction,
+  createTransferInstruction,
+  createSyncNativeInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeTransferHookInstruction,
+  ExtensionType,
+  getMintLen,
   getAssociatedTokenAddressSync,
   getMinimumBalanceForRentExemptMint,
   MINT_SIZE,
@@ -24,6 +33,7 @@ describe("Platform Program", () => {
 
   // Get the Anchor program instance
   const program = anchor.workspace.MojoContract as Program<MojoContract>; // Replace with your actual program name
+  const hookProgram = anchor.workspace.TestTransferHook as Program<TestTransferHook>;
 
   // Admin Keypair
   const admin = anchor.web3.Keypair.generate();
@@ -82,7 +92,9 @@ describe("Platform Program", () => {
   let userLpTokenAccount;
 
   // Constants
-  const PROTOCOL_FEE_RATE = 250; // 2.5% represented as basis points (250/10000)
+  const PROTOCOL_FEE_RATE = 200; // 2%, the highest rate allowed, as basis points (200/10000)
+  const LP_FEE_RATE = 30; // 0.3% kept in the pool for LPs
+  const MINIMUM_LIQUIDITY = 1_000; // LP locked by the first deposit
   const INITIAL_LIQUIDITY_BASE = 1_000_000_000; // 1000 tokens with 6 decimals
   const INITIAL_LIQUIDITY_PAIRED = 1_000_000_000; // 1000 tokens with 6 decimals
 
@@ -100,6 +112,174 @@ describe("Platform Program", () => {
 
 
 
+  // Observations are zero-copy: the header is followed by `capacity`
+  // borsh-encoded samples, which the IDL does not describe.
+  const OBSERVATIONS_HEADER_SIZE = 8 + 40;
+  const OBSERVATION_SIZE = 48;
+
+  async function fetchObservations(address: anchor.web3.PublicKey) {
+    const header = await program.account.observations.fetch(address);
+    const { data } = await provider.connection.getAccountInfo(address);
+    const samples = [];
+    for (let slot = 0; slot < header.count; slot++) {
+      const sample = data.subarray(OBSERVATIONS_HEADER_SIZE + slot * OBSERVATION_SIZE);
+      samples.push({
+        timestamp: new BN(sample.subarray(0, 8), "le"),
+        basePriceCumulative: new BN(sample.subarray(8, 24), "le"),
+        pairedPriceCumulative: new BN(sample.subarray(24, 40), "le"),
+        liquidity: new BN(sample.subarray(40, 48), "le"),
+      });
+    }
+    return { ...header, samples };
+  }
+
+  // Creates a fresh player token mint, a MOJO/player pair for it and funds
+  // the admin with both sides. Used by tests that need an isolated pool.
+  // A non-zero `transferFeeBps` or a `transferHookProgramId` creates a
+  // Token-2022 mint with the matching extension.
+  async function setupPair(
+    pairedAmount: number,
+    lpFeeRate = 0,
+    tokenProgram = TOKEN_PROGRAM_ID,
+    transferFeeBps = 0,
+    transferHookProgramId: anchor.web3.PublicKey | null = null
+  ) {
+    const mint = anchor.web3.Keypair.generate();
+    const extensions = [
+      ...(transferFeeBps > 0 ? [ExtensionType.TransferFeeConfig] : []),
+      ...(transferHookProgramId ? [ExtensionType.TransferHook] : []),
+    ];
+    const mintSpace = getMintLen(extensions);
+    const mintLamports =
+      await provider.connection.getMinimumBalanceForRentExemption(mintSpace);
+
+    let tx = new anchor.web3.Transaction();
+    tx.instructions = [
+      SystemProgram.createAccount({
+        fromPubkey: provider.publicKey,
+        newAccountPubkey: mint.publicKey,
+        lamports: mintLamports,
+        space: mintSpace,
+        programId: tokenProgram,
+      }),
+      ...(transferFeeBps > 0
+        ? [
+            createInitializeTransferFeeConfigInstruction(
+              mint.publicKey,
+              admin.publicKey,
+              admin.publicKey,
+              transferFeeBps,
+              BigInt(Number.MAX_SAFE_INTEGER),
+              tokenProgram
+            ),
+          ]
+        : []),
+      ...(transferHookProgramId
+        ? [
+            createInitializeTransferHookInstruction(
+              mint.publicKey,
+              admin.publicKey,
+              transferHookProgramId,
+              tokenProgram
+            ),
+          ]
+        : []),
+      createInitializeMint2Instruction(
+        mint.publicKey,
+        6, // 6 decimals
+        admin.publicKey,
+        null,
+        tokenProgram
+      ),
+    ];
+    await provider.sendAndConfirm(tx, [mint]);
+
+    const [pair] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("pair"),
+        baseTokenMint.publicKey.toBuffer(),
+        mint.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const [lpMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), pair.toBuffer()],
+      program.programId
+    );
+    const baseVault = getAssociatedTokenAddressSync(
+      baseTokenMint.publicKey,
+      pair,
+      true,
+      TOKEN_PROGRAM_ID
+    );
+    const pairedVault = getAssociatedTokenAddressSync(
+      mint.publicKey,
+      pair,
+      true,
+      tokenProgram
+    );
+    const userPairedAta = getAssociatedTokenAddressSync(
+      mint.publicKey,
+      admin.publicKey,
+      false,
+      tokenProgram
+    );
+    const userLpAta = getAssociatedTokenAddressSync(
+      lpMint,
+      admin.publicKey,
+      false,
+      TOKEN_PROGRAM_ID
+    );
+
+    await program.methods
+      .createPair(lpFeeRate)
+      .accountsPartial({
+        creator: admin.publicKey,
+        pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: mint.publicKey,
+        lpMint,
+        baseVault,
+        pairedVault,
+        platformState: platformStatePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: tokenProgram,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    tx = new anchor.web3.Transaction();
+    tx.instructions = [
+      createAssociatedTokenAccountIdempotentInstruction(
+        admin.publicKey,
+        userPairedAta,
+        admin.publicKey,
+        mint.publicKey,
+        tokenProgram
+      ),
+      createAssociatedTokenAccountIdempotentInstruction(
+        admin.publicKey,
+        userLpAta,
+        admin.publicKey,
+        lpMint,
+        TOKEN_PROGRAM_ID
+      ),
+      createMintToInstruction(
+        mint.publicKey,
+        userPairedAta,
+        admin.publicKey,
+        pairedAmount,
+        [],
+        tokenProgram
+      ),
+    ];
+    await provider.sendAndConfirm(tx, [admin]);
+
+    return { mint, pair, lpMint, baseVault, pairedVault, userPairedAta, userLpAta };
+  }
+
   before(async () => {
     console.log("🔄 Setting up test environment...");
 
@@ -260,6 +440,7 @@ describe("Platform Program", () => {
     assert.ok(platformState.feeCollector.equals(platformTreasury));
     assert.equal(platformState.protocolFeeRate, PROTOCOL_FEE_RATE);
     assert.equal(platformState.isPaused, false);
+    assert.equal(platformState.feeChangeDelaySeconds.toNumber(), 86_400);
 
     console.log("✅ Platform initialized successfully!");
   });
@@ -410,7 +591,7 @@ describe("Platform Program", () => {
         "🚨 Initialization with excessive fee rate should have failed!"
       );
     } catch (error) {
-      // Expected to fail if you've implemented fee rate validation
+      assert.include(error.toString(), "ProtocolFeeTooHigh");
       console.log("✅ Excessive fee rate was correctly rejected.");
     }
   });
@@ -432,7 +613,7 @@ describe("Platform Program", () => {
 
     // Create the pair
     await program.methods
-      .createPair()
+      .createPair(feeRate)
       .accountsPartial({
         creator: admin.publicKey,
         pair: pairPda,
@@ -443,6 +624,7 @@ describe("Platform Program", () => {
         pairedVault: pairedVault,
         platformState: platformStatePda,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -484,6 +666,7 @@ describe("Platform Program", () => {
       pairAccount.pairedVault.equals(pairedVault),
       "Paired vault doesn't match"
     );
+    assert.equal(pairAccount.lpFeeRate, feeRate, "LP fee rate doesn't match");
 
     console.log("✅ Pair account data verified");
   });
@@ -555,7 +738,7 @@ describe("Platform Program", () => {
 
     try {
       await program.methods
-        .createPair()
+        .createPair(excessiveFeeRate)
         .accountsPartial({
           creator: admin.publicKey,
           pair: testPairPda,
@@ -566,6 +749,7 @@ describe("Platform Program", () => {
           pairedVault: testPairedVault,
           platformState: platformStatePda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -576,6 +760,7 @@ describe("Platform Program", () => {
         "🚨 Creating pair with excessive fee rate should have failed!"
       );
     } catch (error) {
+      assert.include(error.toString(), "FeeTooHigh");
       console.log("✅ Excessive fee rate was correctly rejected");
     }
   });
@@ -647,7 +832,7 @@ describe("Platform Program", () => {
 
     try {
       await program.methods
-        .createPair()
+        .createPair(feeRate)
         .accountsPartial({
           creator: admin.publicKey,
           pair: testPairPda,
@@ -658,6 +843,7 @@ describe("Platform Program", () => {
           pairedVault: testPairedVault,
           platformState: platformStatePda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -761,7 +947,7 @@ describe("Platform Program", () => {
 
     try {
       await program.methods
-        .createPair()
+        .createPair(feeRate)
         .accountsPartial({
           creator: admin.publicKey,
           pair: testPairPda,
@@ -772,6 +958,7 @@ describe("Platform Program", () => {
           pairedVault: testPairedVault,
           platformState: platformStatePda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -847,7 +1034,7 @@ describe("Platform Program", () => {
 
     // Create the pair first time
     await program.methods
-      .createPair()
+      .createPair(LP_FEE_RATE)
       .accountsPartial({
         creator: admin.publicKey,
         pair: pairPda,
@@ -858,6 +1045,7 @@ describe("Platform Program", () => {
         pairedVault: pairedVault,
         platformState: platformStatePda,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -869,7 +1057,7 @@ describe("Platform Program", () => {
     // Try to create the same pair again
     try {
       await program.methods
-        .createPair()
+        .createPair(LP_FEE_RATE)
         .accountsPartial({
           creator: admin.publicKey,
           pair: pairPda,
@@ -880,6 +1068,7 @@ describe("Platform Program", () => {
           pairedVault: pairedVault,
           platformState: platformStatePda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -977,7 +1166,7 @@ describe("Platform Program", () => {
 
     // Create pair 1
     await program.methods
-      .createPair()
+      .createPair(LP_FEE_RATE)
       .accountsPartial({
         creator: admin.publicKey,
         pair: pair1Pda,
@@ -988,6 +1177,7 @@ describe("Platform Program", () => {
         pairedVault: pairedVault1,
         platformState: platformStatePda,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -1029,7 +1219,7 @@ describe("Platform Program", () => {
 
     // Create pair 2
     await program.methods
-      .createPair()
+      .createPair(LP_FEE_RATE)
       .accountsPartial({
         creator: admin.publicKey,
         pair: pair2Pda,
@@ -1040,6 +1230,7 @@ describe("Platform Program", () => {
         pairedVault: pairedVault2,
         platformState: platformStatePda,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -1100,11 +1291,17 @@ describe("Platform Program", () => {
     await program.methods
       .addLiquidity(
         new BN(INITIAL_LIQUIDITY_BASE),
-        new BN(INITIAL_LIQUIDITY_PAIRED)
+        new BN(INITIAL_LIQUIDITY_PAIRED),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
       )
       .accountsPartial({
         user: admin.publicKey,
         pair: pairPda,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pairedTokenMint.publicKey,
         baseVault: baseVault,
         userBaseAta: userBaseTokenAccount,
         pairedVault: pairedVault,
@@ -1112,6 +1309,7 @@ describe("Platform Program", () => {
         lpMint: lpMintPda,
         userLpAta: userLpTokenAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -1131,13 +1329,15 @@ describe("Platform Program", () => {
       "Paired reserve incorrect"
     );
 
-    // Expected LP should be sqrt(base * paired) for initial liquidity
-    const expectedLp = Math.floor(
+    // Expected LP should be sqrt(base * paired) for initial liquidity, of
+    // which MINIMUM_LIQUIDITY stays locked in the pool
+    const totalLp = Math.floor(
       Math.sqrt(INITIAL_LIQUIDITY_BASE * INITIAL_LIQUIDITY_PAIRED)
     );
+    const expectedLp = totalLp - MINIMUM_LIQUIDITY;
     assert.ok(
-      pairAccount.totalLiquidity.eq(new BN(expectedLp)),
-      `Total liquidity incorrect. Expected: ${expectedLp}, Got: ${pairAccount.totalLiquidity.toString()}`
+      pairAccount.totalLiquidity.eq(new BN(totalLp)),
+      `Total liquidity incorrect. Expected: ${totalLp}, Got: ${pairAccount.totalLiquidity.toString()}`
     );
 
     // Verify LP tokens were minted to user
@@ -1198,11 +1398,17 @@ describe("Platform Program", () => {
     await program.methods
       .addLiquidity(
         new BN(additionalBaseAmount),
-        new BN(additionalPairedAmount)
+        new BN(additionalPairedAmount),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
       )
       .accountsPartial({
         user: admin.publicKey,
         pair: pairPda,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pairedTokenMint.publicKey,
         baseVault: baseVault,
         userBaseAta: userBaseTokenAccount,
         pairedVault: pairedVault,
@@ -1210,6 +1416,7 @@ describe("Platform Program", () => {
         lpMint: lpMintPda,
         userLpAta: userLpTokenAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -1351,15 +1558,21 @@ describe("Platform Program", () => {
 
     console.log(`Current pool ratio: ${poolRatio}`);
 
-    // Try to add very unbalanced liquidity (should work but mint minimal LP tokens)
+    // Try to add very unbalanced liquidity (should only take the matching amounts)
     await program.methods
       .addLiquidity(
         new BN(100_000_000), // 100 base tokens
-        new BN(1_000_000) // 1 paired token - very unbalanced
+        new BN(1_000_000), // 1 paired token - very unbalanced
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
       )
       .accountsPartial({
         user: testUser.publicKey,
         pair: pairPda,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pairedTokenMint.publicKey,
         baseVault: baseVault,
         userBaseAta: testUserBaseTokenAccount,
         pairedVault: pairedVault,
@@ -1367,6 +1580,7 @@ describe("Platform Program", () => {
         lpMint: lpMintPda,
         userLpAta: testUserLpTokenAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -1394,6 +1608,17 @@ describe("Platform Program", () => {
       `LP tokens minted incorrectly. Expected ~${expectedLp}, Got: ${userLpBalance.value.amount}`
     );
 
+    // Only the base matching 1 paired token at the pool ratio should be taken
+    const baseTaken = new BN(1_000_000)
+      .mul(pairAccount.baseReserve)
+      .div(pairAccount.pairedReserve);
+    const testUserBase = await getAccount(provider.connection, testUserBaseTokenAccount);
+    assert.equal(
+      testUserBase.amount.toString(),
+      new BN(100_000_000).sub(baseTaken).toString(),
+      "Excess base tokens should stay with the user"
+    );
+
     console.log("✅ Unbalanced liquidity properly handled");
   });
 
@@ -1404,11 +1629,17 @@ describe("Platform Program", () => {
       await program.methods
         .addLiquidity(
           new BN(0),
-          new BN(INITIAL_LIQUIDITY_PAIRED)
+          new BN(INITIAL_LIQUIDITY_PAIRED),
+          new BN(0),
+          new BN(0),
+          new BN(0),
+          null
         )
         .accountsPartial({
           user: admin.publicKey,
           pair: pairPda,
+          baseTokenMint: baseTokenMint.publicKey,
+          pairedTokenMint: pairedTokenMint.publicKey,
           baseVault: baseVault,
           userBaseAta: userBaseTokenAccount,
           pairedVault: pairedVault,
@@ -1416,6 +1647,7 @@ describe("Platform Program", () => {
           lpMint: lpMintPda,
           userLpAta: userLpTokenAccount,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -1431,11 +1663,17 @@ describe("Platform Program", () => {
       await program.methods
         .addLiquidity(
           new BN(INITIAL_LIQUIDITY_BASE),
-          new BN(0)
+          new BN(0),
+          new BN(0),
+          new BN(0),
+          new BN(0),
+          null
         )
         .accountsPartial({
           user: admin.publicKey,
           pair: pairPda,
+          baseTokenMint: baseTokenMint.publicKey,
+          pairedTokenMint: pairedTokenMint.publicKey,
           baseVault: baseVault,
           userBaseAta: userBaseTokenAccount,
           pairedVault: pairedVault,
@@ -1443,6 +1681,7 @@ describe("Platform Program", () => {
           lpMint: lpMintPda,
           userLpAta: userLpTokenAccount,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -1521,11 +1760,17 @@ describe("Platform Program", () => {
       await program.methods
         .addLiquidity(
           new BN(1000_000_000), // 1000 tokens
-          new BN(1000_000_000)  // 1000 tokens
+          new BN(1000_000_000), // 1000 tokens
+          new BN(0),
+          new BN(0),
+          new BN(0),
+          null
         )
         .accountsPartial({
           user: poorUser.publicKey,
           pair: pairPda,
+          baseTokenMint: baseTokenMint.publicKey,
+          pairedTokenMint: pairedTokenMint.publicKey,
           baseVault: baseVault,
           userBaseAta: poorUserBaseTokenAccount,
           pairedVault: pairedVault,
@@ -1533,6 +1778,7 @@ describe("Platform Program", () => {
           lpMint: lpMintPda,
           userLpAta: poorUserLpTokenAccount,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
@@ -1583,7 +1829,8 @@ describe("Platform Program", () => {
       .swap(
         new BN(swapAmount),
         new BN(minAmountOut),
-        isBaseInput
+        isBaseInput,
+        null
       )
       .accountsPartial({
         user: admin.publicKey,
@@ -1597,6 +1844,7 @@ describe("Platform Program", () => {
         userPairedAta: userPairedTokenAccount,
         feeCollector: platformTreasury,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([admin])
       .rpc();
@@ -1717,7 +1965,8 @@ describe("Platform Program", () => {
       .swap(
         new BN(swapAmount),
         new BN(minAmountOut),
-        isBaseInput
+        isBaseInput,
+        null
       )
       .accountsPartial({
         user: admin.publicKey,
@@ -1731,6 +1980,7 @@ describe("Platform Program", () => {
         userPairedAta: userPairedTokenAccount,
         feeCollector: platformTreasury,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([admin])
       .rpc();
@@ -1850,7 +2100,8 @@ describe("Platform Program", () => {
       .swap(
         new BN(swapAmount),
         new BN(minAmountOut),
-        isBaseInput
+        isBaseInput,
+        null
       )
       .accountsPartial({
         user: admin.publicKey,
@@ -1864,6 +2115,7 @@ describe("Platform Program", () => {
         userPairedAta: userPairedTokenAccount,
         feeCollector: platformTreasury,
         tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([admin])
       .rpc();
@@ -1891,6 +2143,1954 @@ describe("Platform Program", () => {
     `);
   });
 
+  it("Swaps for an exact amount of paired token", async () => {
+    console.log("🔄 Testing exact-output base to paired token swap...");
+
+    const amountOut = 5_000_000; // exactly 5 paired tokens
+    const isBaseInput = true;
+
+    const pairBefore = await program.account.pair.fetch(pairPda);
+    const baseReserve = BigInt(pairBefore.baseReserve.toString());
+    const pairedReserve = BigInt(pairBefore.pairedReserve.toString());
+
+    // Input needed by the curve, rounded up, then grossed up for each fee
+    const grossUp = (net: bigint, rate: number) =>
+      (net * 10_000n + BigInt(10_000 - rate) - 1n) / BigInt(10_000 - rate);
+    const k = baseReserve * pairedReserve;
+    const newPairedReserve = pairedReserve - BigInt(amountOut);
+    const newBaseReserve = (k + newPairedReserve - 1n) / newPairedReserve;
+    const curveAmountIn = newBaseReserve - baseReserve;
+    const expectedAmountIn = grossUp(
+      grossUp(curveAmountIn, pairBefore.lpFeeRate),
+      PROTOCOL_FEE_RATE
+    );
+
+    const userBaseTokenBefore = await getAccount(provider.connection, userBaseTokenAccount);
+    const userPairedTokenBefore = await getAccount(provider.connection, userPairedTokenAccount);
+
+    await program.methods
+      .swapExactOut(
+        new BN(amountOut),
+        new BN(expectedAmountIn.toString()),
+        isBaseInput,
+        null
+      )
+      .accountsPartial({
+        user: admin.publicKey,
+        pair: pairPda,
+        platformState: platformStatePda,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pairedTokenMint.publicKey,
+        baseVault: baseVault,
+        pairedVault: pairedVault,
+        userBaseAta: userBaseTokenAccount,
+        userPairedAta: userPairedTokenAccount,
+        feeCollector: platformTreasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
+    const userBaseTokenAfter = await getAccount(provider.connection, userBaseTokenAccount);
+    const userPairedTokenAfter = await getAccount(provider.connection, userPairedTokenAccount);
+
+    const baseTokenDiff = userBaseTokenBefore.amount - userBaseTokenAfter.amount;
+    const pairedTokenDiff = userPairedTokenAfter.amount - userPairedTokenBefore.amount;
+
+    assert.equal(Number(pairedTokenDiff), amountOut, "User should receive exactly the requested amount");
+    assert.equal(baseTokenDiff, expectedAmountIn, "User should pay the rounded-up input amount");
+
+    // The pool must never end up with a smaller k
+    const pairAfter = await program.account.pair.fetch(pairPda);
+    const kAfter =
+      BigInt(pairAfter.baseReserve.toString()) * BigInt(pairAfter.pairedReserve.toString());
+    assert.ok(kAfter >= k, "Constant product should not decrease");
+  });
+
+  it("Swaps paired token for an exact amount of base token", async () => {
+    console.log("🔄 Testing exact-output paired to base token swap...");
+
+    const amountOut = 5_000_000; // exactly 5 MOJO after fee
+    const isBaseInput = false;
+
+    const feeCollectorBefore = await getAccount(provider.connection, platformTreasury);
+    const userBaseTokenBefore = await getAccount(provider.connection, userBaseTokenAccount);
+
+    await program.methods
+      .swapExactOut(new BN(amountOut), new BN(10_000_000), isBaseInput, null)
+      .accountsPartial({
+        user: admin.publicKey,
+        pair: pairPda,
+        platformState: platformStatePda,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pairedTokenMint.publicKey,
+        baseVault: baseVault,
+        pairedVault: pairedVault,
+        userBaseAta: userBaseTokenAccount,
+        userPairedAta: userPairedTokenAccount,
+        feeCollector: platformTreasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
+    const feeCollectorAfter = await getAccount(provider.connection, platformTreasury);
+    const userBaseTokenAfter = await getAccount(provider.connection, userBaseTokenAccount);
+
+    // Fee is charged on the gross MOJO leaving the pool
+    const grossOut = Math.ceil((amountOut * 10_000) / (10_000 - PROTOCOL_FEE_RATE));
+    assert.equal(
+      Number(userBaseTokenAfter.amount - userBaseTokenBefore.amount),
+      amountOut,
+      "User should receive exactly the requested MOJO"
+    );
+    assert.equal(
+      Number(feeCollectorAfter.amount - feeCollectorBefore.amount),
+      grossOut - amountOut,
+      "Fee collector should receive the fee on the gross output"
+    );
+  });
+
+  it("Fails exact-output swap when input exceeds the maximum", async () => {
+    console.log("❌ Testing exact-output swap with too low max input...");
+
+    try {
+      await program.methods
+        .swapExactOut(new BN(5_000_000), new BN(1_000_000), true, null)
+        .accountsPartial({
+          user: admin.publicKey,
+          pair: pairPda,
+          platformState: platformStatePda,
+          baseTokenMint: baseTokenMint.publicKey,
+          pairedTokenMint: pairedTokenMint.publicKey,
+          baseVault: baseVault,
+          pairedVault: pairedVault,
+          userBaseAta: userBaseTokenAccount,
+          userPairedAta: userPairedTokenAccount,
+          feeCollector: platformTreasury,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+      assert.fail("Transaction should have failed due to excessive input amount");
+    } catch (error) {
+      assert.include(error.toString(), "ExcessiveInputAmount");
+      console.log("✅ Excessive input correctly rejected");
+    }
+  });
+
+  it("Routes one player token into another through MOJO", async () => {
+    console.log("🔄 Testing multi-hop route swap...");
+
+    // Second pool to route into
+    const target = await setupPair(INITIAL_LIQUIDITY_PAIRED, LP_FEE_RATE);
+    await program.methods
+      .addLiquidity(
+        new BN(100_000_000),
+        new BN(INITIAL_LIQUIDITY_PAIRED),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial({
+        user: admin.publicKey,
+        pair: target.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: target.mint.publicKey,
+        baseVault: target.baseVault,
+        userBaseAta: userBaseTokenAccount,
+        pairedVault: target.pairedVault,
+        userPairedAta: target.userPairedAta,
+        lpMint: target.lpMint,
+        userLpAta: target.userLpAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const amountIn = 10_000_000;
+    const userBaseBefore = await getAccount(provider.connection, userBaseTokenAccount);
+    const userInputBefore = await getAccount(provider.connection, userPairedTokenAccount);
+    const userOutputBefore = await getAccount(provider.connection, target.userPairedAta);
+    const feeCollectorBefore = await getAccount(provider.connection, platformTreasury);
+    const pairInBefore = await program.account.pair.fetch(pairPda);
+    const pairOutBefore = await program.account.pair.fetch(target.pair);
+
+    // Expected output, hop by hop
+    const fee = (amount: bigint, rate = PROTOCOL_FEE_RATE) =>
+      (amount * BigInt(rate)) / 10_000n;
+    const hop = (reserveIn: bigint, reserveOut: bigint, amount: bigint, lpFeeRate: number) => {
+      const curveIn = amount - fee(amount, lpFeeRate);
+      return (reserveOut * curveIn) / (reserveIn + curveIn);
+    };
+
+    const grossMojo = hop(
+      BigInt(pairInBefore.pairedReserve.toString()),
+      BigInt(pairInBefore.baseReserve.toString()),
+      BigInt(amountIn),
+      pairInBefore.lpFeeRate
+    );
+    const mojo = grossMojo - fee(grossMojo);
+    const expectedOut = hop(
+      BigInt(pairOutBefore.baseReserve.toString()),
+      BigInt(pairOutBefore.pairedReserve.toString()),
+      mojo - fee(mojo),
+      pairOutBefore.lpFeeRate
+    );
+
+    await program.methods
+      .swapRoute(new BN(amountIn), new BN(expectedOut.toString()), null)
+      .accountsPartial({
+        user: admin.publicKey,
+        platformState: platformStatePda,
+        pairIn: pairPda,
+        pairOut: target.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        inputTokenMint: pairedTokenMint.publicKey,
+        outputTokenMint: target.mint.publicKey,
+        pairInBaseVault: baseVault,
+        pairInPairedVault: pairedVault,
+        pairOutBaseVault: target.baseVault,
+        pairOutPairedVault: target.pairedVault,
+        userInputAta: userPairedTokenAccount,
+        userOutputAta: target.userPairedAta,
+        feeCollector: platformTreasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        inputTokenProgram: TOKEN_PROGRAM_ID,
+        outputTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
+    const userBaseAfter = await getAccount(provider.connection, userBaseTokenAccount);
+    const userInputAfter = await getAccount(provider.connection, userPairedTokenAccount);
+    const userOutputAfter = await getAccount(provider.connection, target.userPairedAta);
+    const feeCollectorAfter = await getAccount(provider.connection, platformTreasury);
+
+    assert.equal(
+      Number(userInputBefore.amount - userInputAfter.amount),
+      amountIn,
+      "User should have sent the exact input amount"
+    );
+    assert.equal(
+      userOutputAfter.amount - userOutputBefore.amount,
+      expectedOut,
+      "User should receive the routed output"
+    );
+    assert.equal(
+      userBaseAfter.amount,
+      userBaseBefore.amount,
+      "Intermediate MOJO should never touch the user's account"
+    );
+    assert.equal(
+      feeCollectorAfter.amount - feeCollectorBefore.amount,
+      fee(grossMojo) + fee(mojo),
+      "Protocol fee should be charged once per hop"
+    );
+  });
+
+  it("Accrues the LP fee to liquidity providers", async () => {
+    console.log("🔄 Testing LP fee accrual...");
+
+    const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED, 100); // 1% LP fee
+    const depositBase = 100_000_000;
+    const depositPaired = 100_000_000;
+
+    await program.methods
+      .addLiquidity(
+        new BN(depositBase),
+        new BN(depositPaired),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial({
+        user: admin.publicKey,
+        pair: pool.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pool.mint.publicKey,
+        baseVault: pool.baseVault,
+        userBaseAta: userBaseTokenAccount,
+        pairedVault: pool.pairedVault,
+        userPairedAta: pool.userPairedAta,
+        lpMint: pool.lpMint,
+        userLpAta: pool.userLpAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const kBefore = await program.account.pair.fetch(pool.pair);
+
+    // Trade back and forth so fees pile up on both sides
+    for (const isBaseInput of [true, false]) {
+      await program.methods
+        .swap(new BN(10_000_000), new BN(0), isBaseInput, null)
+        .accountsPartial({
+          user: admin.publicKey,
+          pair: pool.pair,
+          platformState: platformStatePda,
+          baseTokenMint: baseTokenMint.publicKey,
+          pairedTokenMint: pool.mint.publicKey,
+          baseVault: pool.baseVault,
+          pairedVault: pool.pairedVault,
+          userBaseAta: userBaseTokenAccount,
+          userPairedAta: pool.userPairedAta,
+          feeCollector: platformTreasury,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+    }
+
+    const pairAfterSwaps = await program.account.pair.fetch(pool.pair);
+    assert.ok(
+      pairAfterSwaps.baseReserve
+        .mul(pairAfterSwaps.pairedReserve)
+        .gt(kBefore.baseReserve.mul(kBefore.pairedReserve)),
+      "LP fee should grow the constant product"
+    );
+
+    const userBaseBefore = await getAccount(provider.connection, userBaseTokenAccount);
+    const userPairedBefore = await getAccount(provider.connection, pool.userPairedAta);
+
+    await program.methods
+      .removeLiquidity(
+        pairAfterSwaps.totalLiquidity.subn(MINIMUM_LIQUIDITY),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial({
+        user: admin.publicKey,
+        pair: pool.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pool.mint.publicKey,
+        baseVault: pool.baseVault,
+        userBaseAta: userBaseTokenAccount,
+        pairedVault: pool.pairedVault,
+        userPairedAta: pool.userPairedAta,
+        lpMint: pool.lpMint,
+        userLpAta: pool.userLpAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
+    const userBaseAfter = await getAccount(provider.connection, userBaseTokenAccount);
+    const userPairedAfter = await getAccount(provider.connection, pool.userPairedAta);
+
+    // The sole LP gets all but the locked share back, fees included. Its
+    // value at the final price must beat what was deposited.
+    const baseOut = userBaseAfter.amount - userBaseBefore.amount;
+    const pairedOut = userPairedAfter.amount - userPairedBefore.amount;
+    assert.ok(
+      baseOut * pairedOut > BigInt(depositBase) * BigInt(depositPaired),
+      "Withdrawn liquidity should include accrued LP fees"
+    );
+  });
+
+  it("Enforces the swap cooldown per user and pair", async () => {
+    console.log("⏱️ Testing swap cooldown...");
+
+    const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED);
+    await program.methods
+      .addLiquidity(
+        new BN(50_000_000),
+        new BN(50_000_000),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial({
+        user: admin.publicKey,
+        pair: pool.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pool.mint.publicKey,
+        baseVault: pool.baseVault,
+        userBaseAta: userBaseTokenAccount,
+        pairedVault: pool.pairedVault,
+        userPairedAta: pool.userPairedAta,
+        lpMint: pool.lpMint,
+        userLpAta: pool.userLpAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const swapOnPool = () =>
+      program.methods
+        .swap(new BN(1_000_000), new BN(0), true, null)
+        .accountsPartial({
+          user: admin.publicKey,
+          pair: pool.pair,
+          platformState: platformStatePda,
+          baseTokenMint: baseTokenMint.publicKey,
+          pairedTokenMint: pool.mint.publicKey,
+          baseVault: pool.baseVault,
+          pairedVault: pool.pairedVault,
+          userBaseAta: userBaseTokenAccount,
+          userPairedAta: pool.userPairedAta,
+          feeCollector: platformTreasury,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+    // Turn on a one hour platform-wide cooldown
+    await program.methods
+      .updateSwapCooldown(new BN(3600))
+      .accountsPartial({ admin: admin.publicKey, platformState: platformStatePda })
+      .signers([admin])
+      .rpc();
+
+    await swapOnPool();
+
+    try {
+      await swapOnPool();
+      assert.fail("Second swap within the cooldown should have failed");
+    } catch (error) {
+      assert.include(error.toString(), "SwapCooldown");
+      console.log("✅ Swap within cooldown correctly rejected");
+    }
+
+    // A per-pair override of zero lifts the cooldown for this pool only
+    await program.methods
+      .updatePairSwapCooldown(new BN(0))
+      .accountsPartial({
+        admin: admin.publicKey,
+        platformState: platformStatePda,
+        pair: pool.pair,
+      })
+      .signers([admin])
+      .rpc();
+
+    await swapOnPool();
+
+    const [traderStatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader"), pool.pair.toBuffer(), admin.publicKey.toBuffer()],
+      program.programId
+    );
+    const traderState = await program.account.traderState.fetch(traderStatePda);
+    assert.ok(traderState.user.equals(admin.publicKey), "Trader state user mismatch");
+    assert.ok(traderState.pair.equals(pool.pair), "Trader state pair mismatch");
+
+    // Restore the default so later tests are unaffected
+    await program.methods
+      .updateSwapCooldown(new BN(0))
+      .accountsPartial({ admin: admin.publicKey, platformState: platformStatePda })
+      .signers([admin])
+      .rpc();
+  });
+
+  it("Announces protocol fee changes ahead of time", async () => {
+    console.log("🧪 Testing the protocol fee change delay...");
+
+    const [pendingFeeChangePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pending-fee-change")],
+      program.programId
+    );
+
+    await program.methods
+      .proposeFeeChange(100)
+      .accountsPartial({
+        admin: admin.publicKey,
+        platformState: platformStatePda,
+        pendingFeeChange: pendingFeeChangePda,
+      })
+      .signers([admin])
+      .rpc();
+
+    const pending = await program.account.pendingFeeChange.fetch(pendingFeeChangePda);
+    assert.equal(pending.newFeeRate, 100);
+    assert.equal(pending.effectiveAt.sub(pending.proposedAt).toNumber(), 86_400);
+    const platformState = await program.account.platformState.fetch(platformStatePda);
+    assert.equal(
+      platformState.protocolFeeRate,
+      PROTOCOL_FEE_RATE,
+      "Rate should not change before the delay"
+    );
+
+    try {
+      await program.methods
+        .executeFeeChange()
+        .accountsPartial({
+          admin: admin.publicKey,
+          platformState: platformStatePda,
+          pendingFeeChange: pendingFeeChangePda,
+        })
+        .signers([admin])
+        .rpc();
+      assert.fail("Executing before the delay should have failed");
+    } catch (error) {
+      assert.include(error.toString(), "FeeChangeNotReady");
+      console.log("✅ Early fee change correctly rejected");
+    }
+
+    // Cancel so later tests keep the current rate
+    await program.methods
+      .cancelFeeChange()
+      .accountsPartial({
+        admin: admin.publicKey,
+        platformState: platformStatePda,
+        pendingFeeChange: pendingFeeChangePda,
+      })
+      .signers([admin])
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(pendingFeeChangePda));
+  });
+
+  it("Accumulates prices and reports a TWAP", async () => {
+    console.log("📈 Testing TWAP accumulators...");
+
+    const pairBefore = await program.account.pair.fetch(pairPda);
+    const [observationsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("observations"), pairPda.toBuffer()],
+      program.programId
+    );
+
+    // Let some time pass at the current price, then trade
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    await program.methods
+      .swap(new BN(1_000_000), new BN(0), true, null)
+      .accountsPartial({
+        user: admin.publicKey,
+        pair: pairPda,
+        platformState: platformStatePda,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pairedTokenMint.publicKey,
+        baseVault: baseVault,
+        pairedVault: pairedVault,
+        userBaseAta: userBaseTokenAccount,
+        userPairedAta: userPairedTokenAccount,
+        feeCollector: platformTreasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
+    const pairAfter = await program.account.pair.fetch(pairPda);
+    assert.ok(
+      pairAfter.basePriceCumulative.gt(pairBefore.basePriceCumulative),
+      "Base price accumulator should grow"
+    );
+    assert.ok(
+      pairAfter.pairedPriceCumulative.gt(pairBefore.pairedPriceCumulative),
+      "Paired price accumulator should grow"
+    );
+
+    // The window starts at the oldest stored sample
+    const observations = await fetchObservations(observationsPda);
+    const startIndex =
+      observations.count < observations.capacity
+        ? 0
+        : (observations.index + 1) % observations.capacity;
+    const since = observations.samples[startIndex];
+    // Without an end index the window runs until now
+    const twap = await program.methods
+      .getTwap(startIndex, null)
+      .accountsPartial({ pair: pairPda, observations: observationsPda })
+      .view();
+
+    const twapPrice =
+      Number(BigInt(twap.pairedPriceX64.toString()) >> 32n) / 2 ** 32;
+    assert.isAtLeast(
+      twap.windowSeconds.toNumber(),
+      pairAfter.lastSwapTime.sub(since.timestamp).toNumber(),
+      "Window should start at the sample"
+    );
+    assert.isAbove(twapPrice, 0, "TWAP should be positive");
+  });
+
+  it("Samples prices into the observation ring buffer", async () => {
+    console.log("📈 Testing price observations...");
+
+    const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED);
+    await program.methods
+      .addLiquidity(
+        new BN(50_000_000),
+        new BN(50_000_000),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial({
+        user: admin.publicKey,
+        pair: pool.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pool.mint.publicKey,
+        baseVault: pool.baseVault,
+        userBaseAta: userBaseTokenAccount,
+        pairedVault: pool.pairedVault,
+        userPairedAta: pool.userPairedAta,
+        lpMint: pool.lpMint,
+        userLpAta: pool.userLpAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const [observationsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("observations"), pool.pair.toBuffer()],
+      program.programId
+    );
+
+    const swapOnPool = (isBaseInput: boolean) =>
+      program.methods
+        .swap(new BN(1_000_000), new BN(0), isBaseInput, null)
+        .accountsPartial({
+          user: admin.publicKey,
+          pair: pool.pair,
+          platformState: platformStatePda,
+          baseTokenMint: baseTokenMint.publicKey,
+          pairedTokenMint: pool.mint.publicKey,
+          baseVault: pool.baseVault,
+          pairedVault: pool.pairedVault,
+          userBaseAta: userBaseTokenAccount,
+          userPairedAta: pool.userPairedAta,
+          feeCollector: platformTreasury,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+    // Two quick swaps fall inside the default interval: one sample
+    await swapOnPool(true);
+    await swapOnPool(false);
+    let observations = await fetchObservations(observationsPda);
+    assert.equal(observations.count, 1, "Only one sample per interval");
+
+    // Anyone can pay to enlarge the buffer
+    const payer = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(payer.publicKey, 1e9)
+    );
+    await program.methods
+      .growObservations(16)
+      .accountsPartial({ payer: payer.publicKey, pair: pool.pair })
+      .signers([payer])
+      .rpc();
+
+    observations = await fetchObservations(observationsPda);
+    assert.equal(observations.capacity, 16, "Buffer should have grown");
+    assert.equal(observations.count, 1, "Existing samples should be kept");
+
+    // With a one second interval the next swap is sampled again
+    await program.methods
+      .updateObservationInterval(new BN(1))
+      .accountsPartial({ admin: admin.publicKey, platformState: platformStatePda })
+      .signers([admin])
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    await swapOnPool(true);
+
+    observations = await fetchObservations(observationsPda);
+    assert.equal(observations.count, 2, "A second sample should be written");
+    const latest = observations.samples[observations.index];
+    const pairAccount = await program.account.pair.fetch(pool.pair);
+    assert.ok(latest.timestamp.eq(pairAccount.lastSwapTime), "Latest sample timestamp mismatch");
+    assert.ok(
+      latest.pairedPriceCumulative.eq(pairAccount.pairedPriceCumulative),
+      "Latest sample accumulator mismatch"
+    );
+
+    await program.methods
+      .updateObservationInterval(new BN(60))
+      .accountsPartial({ admin: admin.publicKey, platformState: platformStatePda })
+      .signers([admin])
+      .rpc();
+  });
+
+  it("Quotes a swap that matches execution exactly", async () => {
+    console.log("🔎 Testing swap quotes...");
+
+    for (const isBaseInput of [true, false]) {
+      const amountIn = 3_000_000;
+      const quote = await program.methods
+        .quoteSwap(new BN(amountIn), isBaseInput)
+        .accountsPartial({
+          platformState: platformStatePda,
+          pair: pairPda,
+          baseTokenMint: baseTokenMint.publicKey,
+          pairedTokenMint: pairedTokenMint.publicKey,
+        })
+        .view();
+
+      const feeCollectorBefore = await getAccount(provider.connection, platformTreasury);
+      const outputAccount = isBaseInput ? userPairedTokenAccount : userBaseTokenAccount;
+      const outputBefore = await getAccount(provider.connection, outputAccount);
+
+      await program.methods
+        .swap(new BN(amountIn), quote.amountOut, isBaseInput, null)
+        .accountsPartial({
+          user: admin.publicKey,
+          pair: pairPda,
+          platformState: platformStatePda,
+          baseTokenMint: baseTokenMint.publicKey,
+          pairedTokenMint: pairedTokenMint.publicKey,
+          baseVault: baseVault,
+          pairedVault: pairedVault,
+          userBaseAta: userBaseTokenAccount,
+          userPairedAta: userPairedTokenAccount,
+          feeCollector: platformTreasury,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+      const feeCollectorAfter = await getAccount(provider.connection, platformTreasury);
+      const outputAfter = await getAccount(provider.connection, outputAccount);
+      const pairAfter = await program.account.pair.fetch(pairPda);
+
+      assert.equal(
+        (outputAfter.amount - outputBefore.amount).toString(),
+        quote.amountOut.toString(),
+        "Quoted output should match the swap"
+      );
+      assert.equal(
+        (feeCollectorAfter.amount - feeCollectorBefore.amount).toString(),
+        quote.protocolFee.toString(),
+        "Quoted protocol fee should match the swap"
+      );
+      assert.ok(pairAfter.baseReserve.eq(quote.newBaseReserve), "Quoted base reserve mismatch");
+      assert.ok(pairAfter.pairedReserve.eq(quote.newPairedReserve), "Quoted paired reserve mismatch");
+      assert.isAbove(quote.priceImpactBps, 0, "A non-trivial trade should move the price");
+    }
+  });
+
+  for (const [label, pairedTokenProgram] of [
+    ["SPL Token", TOKEN_PROGRAM_ID],
+    ["Token-2022", TOKEN_2022_PROGRAM_ID],
+  ] as const) {
+    it(`Provides liquidity and swaps a ${label} player token`, async () => {
+      console.log(`🔄 Testing a ${label} player token pool...`);
+
+      const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED, 0, pairedTokenProgram);
+      const liquidityAccounts = {
+        user: admin.publicKey,
+        pair: pool.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pool.mint.publicKey,
+        baseVault: pool.baseVault,
+        userBaseAta: userBaseTokenAccount,
+        pairedVault: pool.pairedVault,
+        userPairedAta: pool.userPairedAta,
+        lpMint: pool.lpMint,
+        userLpAta: pool.userLpAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram,
+      };
+      const pairedBalance = async () =>
+        (
+          await getAccount(
+            provider.connection,
+            pool.userPairedAta,
+            undefined,
+            pairedTokenProgram
+          )
+        ).amount;
+
+      await program.methods
+        .addLiquidity(
+          new BN(100_000_000),
+          new BN(100_000_000),
+          new BN(0),
+          new BN(0),
+          new BN(0),
+          null
+        )
+        .accountsPartial({
+          ...liquidityAccounts,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      const pairedVaultAfterDeposit = await getAccount(
+        provider.connection,
+        pool.pairedVault,
+        undefined,
+        pairedTokenProgram
+      );
+      assert.equal(
+        Number(pairedVaultAfterDeposit.amount),
+        100_000_000,
+        "Paired vault should hold the deposit"
+      );
+
+      // One swap in each direction through the paired token program
+      for (const isBaseInput of [true, false]) {
+        const pairedBefore = await pairedBalance();
+
+        await program.methods
+          .swap(new BN(5_000_000), new BN(1), isBaseInput, null)
+          .accountsPartial({
+            user: admin.publicKey,
+            pair: pool.pair,
+            platformState: platformStatePda,
+            baseTokenMint: baseTokenMint.publicKey,
+            pairedTokenMint: pool.mint.publicKey,
+            baseVault: pool.baseVault,
+            pairedVault: pool.pairedVault,
+            userBaseAta: userBaseTokenAccount,
+            userPairedAta: pool.userPairedAta,
+            feeCollector: platformTreasury,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            pairedTokenProgram,
+          })
+          .signers([admin])
+          .rpc();
+
+        const pairedAfter = await pairedBalance();
+        if (isBaseInput) {
+          assert.isTrue(pairedAfter > pairedBefore, "User should receive the player token");
+        } else {
+          assert.equal(
+            Number(pairedBefore - pairedAfter),
+            5_000_000,
+            "User should send exactly the player token input"
+          );
+        }
+      }
+
+      const pairBeforeExit = await program.account.pair.fetch(pool.pair);
+      const pairedBeforeExit = await pairedBalance();
+      const userLp = pairBeforeExit.totalLiquidity.subn(MINIMUM_LIQUIDITY);
+
+      await program.methods
+        .removeLiquidity(userLp, new BN(0), new BN(0), null)
+        .accountsPartial(liquidityAccounts)
+        .signers([admin])
+        .rpc();
+
+      const pairAfterExit = await program.account.pair.fetch(pool.pair);
+      assert.equal(
+        (await pairedBalance()) - pairedBeforeExit,
+        BigInt(
+          pairBeforeExit.pairedReserve
+            .mul(userLp)
+            .div(pairBeforeExit.totalLiquidity)
+            .toString()
+        ),
+        "User should withdraw their share of the paired reserve"
+      );
+      assert.equal(
+        pairAfterExit.totalLiquidity.toNumber(),
+        MINIMUM_LIQUIDITY,
+        "Only the locked LP should remain"
+      );
+    });
+  }
+
+  it("Credits only what arrives for a transfer-fee player token", async () => {
+    console.log("🔄 Testing a Token-2022 player token with a transfer fee...");
+
+    const transferFeeBps = 100; // 1% withheld on every transfer
+    const pool = await setupPair(
+      INITIAL_LIQUIDITY_PAIRED,
+      0,
+      TOKEN_2022_PROGRAM_ID,
+      transferFeeBps
+    );
+    const withheld = (amount: number) => Math.floor((amount * transferFeeBps) / 10_000);
+    const pairedVaultBalance = async () =>
+      (
+        await getAccount(
+          provider.connection,
+          pool.pairedVault,
+          undefined,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ).amount;
+    const userPairedBalance = async () =>
+      (
+        await getAccount(
+          provider.connection,
+          pool.userPairedAta,
+          undefined,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ).amount;
+
+    const deposit = 100_000_000;
+    await program.methods
+      .addLiquidity(
+        new BN(deposit),
+        new BN(deposit),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial({
+        user: admin.publicKey,
+        pair: pool.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pool.mint.publicKey,
+        baseVault: pool.baseVault,
+        userBaseAta: userBaseTokenAccount,
+        pairedVault: pool.pairedVault,
+        userPairedAta: pool.userPairedAta,
+        lpMint: pool.lpMint,
+        userLpAta: pool.userLpAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    let pair = await program.account.pair.fetch(pool.pair);
+    assert.equal(
+      pair.pairedReserve.toNumber(),
+      deposit - withheld(deposit),
+      "Paired reserve should exclude the withheld transfer fee"
+    );
+    assert.equal(
+      Number(await pairedVaultBalance()),
+      pair.pairedReserve.toNumber(),
+      "Paired reserve should match the vault"
+    );
+
+    const swapAccounts = {
+      user: admin.publicKey,
+      pair: pool.pair,
+      platformState: platformStatePda,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: pool.mint.publicKey,
+      baseVault: pool.baseVault,
+      pairedVault: pool.pairedVault,
+      userBaseAta: userBaseTokenAccount,
+      userPairedAta: pool.userPairedAta,
+      feeCollector: platformTreasury,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_2022_PROGRAM_ID,
+    };
+    const amountIn = new BN(5_000_000);
+    const quote = await program.methods
+      .quoteSwap(amountIn, true)
+      .accountsPartial({
+        platformState: platformStatePda,
+        pair: pool.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pool.mint.publicKey,
+      })
+      .view();
+
+    assert.equal(
+      quote.amountReceived.toNumber(),
+      quote.amountOut.toNumber() - withheld(quote.amountOut.toNumber()),
+      "Quote should net out the transfer fee on the output"
+    );
+
+    // Slippage is measured on what the user receives, not what the pool sends
+    try {
+      await program.methods
+        .swap(amountIn, quote.amountOut, true, null)
+        .accountsPartial(swapAccounts)
+        .signers([admin])
+        .rpc();
+      assert.fail("Swap should fail when the transfer fee breaches the minimum");
+    } catch (error) {
+      assert.include(error.message, "SlippageExceeded");
+    }
+
+    const userPairedBefore = await userPairedBalance();
+    await program.methods
+      .swap(amountIn, quote.amountReceived, true, null)
+      .accountsPartial(swapAccounts)
+      .signers([admin])
+      .rpc();
+
+    assert.equal(
+      (await userPairedBalance()) - userPairedBefore,
+      BigInt(quote.amountReceived.toString()),
+      "User should receive the quoted amount after the transfer fee"
+    );
+
+    // Selling the player token credits the pool with the net input only
+    pair = await program.account.pair.fetch(pool.pair);
+    const pairedReserveBefore = pair.pairedReserve.toNumber();
+    await program.methods
+      .swap(amountIn, new BN(1), false, null)
+      .accountsPartial(swapAccounts)
+      .signers([admin])
+      .rpc();
+
+    pair = await program.account.pair.fetch(pool.pair);
+    assert.equal(
+      pair.pairedReserve.toNumber() - pairedReserveBefore,
+      amountIn.toNumber() - withheld(amountIn.toNumber()),
+      "Paired reserve should grow by the net input"
+    );
+    assert.equal(
+      Number(await pairedVaultBalance()),
+      pair.pairedReserve.toNumber(),
+      "Paired reserve should still match the vault"
+    );
+  });
+
+  it("Forwards transfer hook accounts for a hooked player token", async () => {
+    console.log("🔄 Testing a Token-2022 player token with a transfer hook...");
+
+    const pool = await setupPair(
+      INITIAL_LIQUIDITY_PAIRED,
+      0,
+      TOKEN_2022_PROGRAM_ID,
+      0,
+      hookProgram.programId
+    );
+
+    await hookProgram.methods
+      .initializeExtraAccountMetaList()
+      .accountsPartial({ payer: admin.publicKey, mint: pool.mint.publicKey })
+      .signers([admin])
+      .rpc();
+
+    const [counter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("counter"), pool.mint.publicKey.toBuffer()],
+      hookProgram.programId
+    );
+    const [extraAccountMetaList] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("extra-account-metas"), pool.mint.publicKey.toBuffer()],
+      hookProgram.programId
+    );
+    const hookAccounts = [
+      { pubkey: counter, isSigner: false, isWritable: true },
+      { pubkey: hookProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: extraAccountMetaList, isSigner: false, isWritable: false },
+    ];
+    const transfers = async () =>
+      (await hookProgram.account.counter.fetch(counter)).transfers.toNumber();
+
+    const liquidityAccounts = {
+      user: admin.publicKey,
+      pair: pool.pair,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: pool.mint.publicKey,
+      baseVault: pool.baseVault,
+      userBaseAta: userBaseTokenAccount,
+      pairedVault: pool.pairedVault,
+      userPairedAta: pool.userPairedAta,
+      lpMint: pool.lpMint,
+      userLpAta: pool.userLpAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_2022_PROGRAM_ID,
+    };
+    const swapAccounts = {
+      user: admin.publicKey,
+      pair: pool.pair,
+      platformState: platformStatePda,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: pool.mint.publicKey,
+      baseVault: pool.baseVault,
+      pairedVault: pool.pairedVault,
+      userBaseAta: userBaseTokenAccount,
+      userPairedAta: pool.userPairedAta,
+      feeCollector: platformTreasury,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_2022_PROGRAM_ID,
+    };
+
+    await program.methods
+      .addLiquidity(
+        new BN(100_000_000),
+        new BN(100_000_000),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial({
+        ...liquidityAccounts,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(hookAccounts)
+      .signers([admin])
+      .rpc();
+    assert.equal(await transfers(), 1, "Deposit should run the hook once");
+
+    // Without the hook accounts Token-2022 cannot resolve the hook
+    try {
+      await program.methods
+        .swap(new BN(5_000_000), new BN(1), true, null)
+        .accountsPartial(swapAccounts)
+        .signers([admin])
+        .rpc();
+      assert.fail("Swap should fail without the transfer hook accounts");
+    } catch (error) {
+      assert.notInclude(error.message, "Swap should fail");
+    }
+
+    for (const isBaseInput of [true, false]) {
+      await program.methods
+        .swap(new BN(5_000_000), new BN(1), isBaseInput, null)
+        .accountsPartial(swapAccounts)
+        .remainingAccounts(hookAccounts)
+        .signers([admin])
+        .rpc();
+    }
+    assert.equal(await transfers(), 3, "Each swap should run the hook once");
+
+    const pair = await program.account.pair.fetch(pool.pair);
+    await program.methods
+      .removeLiquidity(
+        pair.totalLiquidity.subn(MINIMUM_LIQUIDITY),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial(liquidityAccounts)
+      .remainingAccounts(hookAccounts)
+      .signers([admin])
+      .rpc();
+    assert.equal(await transfers(), 4, "Withdrawal should run the hook once");
+  });
+
+  it("Trades a wrapped SOL pool with native SOL", async () => {
+    console.log("🔄 Testing native SOL liquidity and swaps...");
+
+    const [solPair] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pair"), baseTokenMint.publicKey.toBuffer(), NATIVE_MINT.toBuffer()],
+      program.programId
+    );
+    const [solLpMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), solPair.toBuffer()],
+      program.programId
+    );
+    const baseVault = getAssociatedTokenAddressSync(baseTokenMint.publicKey, solPair, true);
+    const pairedVault = getAssociatedTokenAddressSync(NATIVE_MINT, solPair, true);
+    const userWsolAta = getAssociatedTokenAddressSync(NATIVE_MINT, admin.publicKey);
+    const userLpAta = getAssociatedTokenAddressSync(solLpMint, admin.publicKey);
+
+    await program.methods
+      .createPair(0)
+      .accountsPartial({
+        creator: admin.publicKey,
+        pair: solPair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: NATIVE_MINT,
+        lpMint: solLpMint,
+        baseVault,
+        pairedVault,
+        platformState: platformStatePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        pairedTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountIdempotentInstruction(
+          admin.publicKey,
+          userLpAta,
+          admin.publicKey,
+          solLpMint
+        )
+      ),
+      [admin]
+    );
+
+    // WSOL the user already holds should stay wrapped throughout
+    const heldWsol = 50_000_000;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountIdempotentInstruction(
+          admin.publicKey,
+          userWsolAta,
+          admin.publicKey,
+          NATIVE_MINT
+        ),
+        SystemProgram.transfer({
+          fromPubkey: admin.publicKey,
+          toPubkey: userWsolAta,
+          lamports: heldWsol,
+        }),
+        createSyncNativeInstruction(userWsolAta)
+      ),
+      [admin]
+    );
+    const [nativeSolAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("native-sol"), admin.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const liquidityAccounts = {
+      user: admin.publicKey,
+      pair: solPair,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: NATIVE_MINT,
+      baseVault,
+      userBaseAta: userBaseTokenAccount,
+      pairedVault,
+      userPairedAta: userWsolAta,
+      lpMint: solLpMint,
+      userLpAta,
+      nativeSolAccount,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const lamports = () => provider.connection.getBalance(admin.publicKey);
+    const assertWsolUntouched = async () => {
+      assert.equal(
+        Number((await getAccount(provider.connection, userWsolAta)).amount),
+        heldWsol,
+        "WSOL the user held should stay wrapped"
+      );
+      assert.isNull(
+        await provider.connection.getAccountInfo(nativeSolAccount),
+        "The temporary WSOL account should be closed"
+      );
+    };
+
+    // Deposit lamports straight from the user's wallet
+    const depositLamports = 1_000_000_000;
+    const lamportsBeforeDeposit = await lamports();
+    await program.methods
+      .addLiquiditySol(
+        new BN(100_000_000),
+        new BN(depositLamports),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial(liquidityAccounts)
+      .signers([admin])
+      .rpc();
+
+    assert.equal(
+      lamportsBeforeDeposit - (await lamports()),
+      depositLamports,
+      "Exactly the deposited lamports should leave the wallet"
+    );
+    await assertWsolUntouched();
+    assert.equal(
+      Number((await getAccount(provider.connection, pairedVault)).amount),
+      depositLamports,
+      "Paired vault should hold the wrapped deposit"
+    );
+
+    const swapAccounts = {
+      user: admin.publicKey,
+      pair: solPair,
+      platformState: platformStatePda,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: NATIVE_MINT,
+      baseVault,
+      pairedVault,
+      userBaseAta: userBaseTokenAccount,
+      userPairedAta: userWsolAta,
+      feeCollector: platformTreasury,
+      nativeSolAccount,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    // SOL in, MOJO out
+    const solIn = 100_000_000;
+    const lamportsBeforeBuy = await lamports();
+    const userBaseBeforeBuy = await getAccount(provider.connection, userBaseTokenAccount);
+    await program.methods
+      .swapSol(new BN(solIn), new BN(1), false, null)
+      .accountsPartial(swapAccounts)
+      .signers([admin])
+      .rpc();
+
+    // The first swap also pays the rent of the trader state
+    const [traderState] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("trader"), solPair.toBuffer(), admin.publicKey.toBuffer()],
+      program.programId
+    );
+    const traderStateRent = await provider.connection.getBalance(traderState);
+    assert.equal(
+      lamportsBeforeBuy - (await lamports()),
+      solIn + traderStateRent,
+      "SOL input should be spent"
+    );
+    const userBaseAfterBuy = await getAccount(provider.connection, userBaseTokenAccount);
+    assert.isTrue(userBaseAfterBuy.amount > userBaseBeforeBuy.amount, "User should receive MOJO");
+    await assertWsolUntouched();
+
+    // MOJO in, SOL out
+    const mojoIn = new BN(5_000_000);
+    const quote = await program.methods
+      .quoteSwap(mojoIn, true)
+      .accountsPartial({
+        platformState: platformStatePda,
+        pair: solPair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: NATIVE_MINT,
+      })
+      .view();
+    const lamportsBeforeSell = await lamports();
+    await program.methods
+      .swapSol(mojoIn, new BN(1), true, null)
+      .accountsPartial(swapAccounts)
+      .signers([admin])
+      .rpc();
+
+    assert.equal(
+      (await lamports()) - lamportsBeforeSell,
+      quote.amountReceived.toNumber(),
+      "SOL output should be unwrapped to the wallet"
+    );
+    await assertWsolUntouched();
+
+    // Withdraw everything but the locked liquidity back to native SOL
+    const pair = await program.account.pair.fetch(solPair);
+    const userLp = pair.totalLiquidity.subn(MINIMUM_LIQUIDITY);
+    const lamportsBeforeWithdraw = await lamports();
+    await program.methods
+      .removeLiquiditySol(userLp, new BN(0), new BN(0), null)
+      .accountsPartial(liquidityAccounts)
+      .signers([admin])
+      .rpc();
+
+    assert.equal(
+      (await lamports()) - lamportsBeforeWithdraw,
+      pair.pairedReserve.mul(userLp).div(pair.totalLiquidity).toNumber(),
+      "The user's share of the SOL reserve should be paid out as lamports"
+    );
+    await assertWsolUntouched();
+  });
+
+  it("Locks minimum liquidity against a first-depositor inflation attack", async () => {
+    console.log("🔄 Testing the first-depositor inflation attack...");
+
+    // The attacker opens the pool
+    const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED);
+    const accountsFor = (
+      user: anchor.web3.PublicKey,
+      userBaseAta: anchor.web3.PublicKey,
+      userPairedAta: anchor.web3.PublicKey,
+      userLpAta: anchor.web3.PublicKey
+    ) => ({
+      user,
+      pair: pool.pair,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: pool.mint.publicKey,
+      baseVault: pool.baseVault,
+      userBaseAta,
+      pairedVault: pool.pairedVault,
+      userPairedAta,
+      lpMint: pool.lpMint,
+      userLpAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_PROGRAM_ID,
+    });
+    const attackerAccounts = accountsFor(
+      admin.publicKey,
+      userBaseTokenAccount,
+      pool.userPairedAta,
+      pool.userLpAta
+    );
+
+    // A dust-sized first deposit can no longer mint any LP
+    try {
+      await program.methods
+        .addLiquidity(
+          new BN(1),
+          new BN(1),
+          new BN(0),
+          new BN(0),
+          new BN(0),
+          null
+        )
+        .accountsPartial(attackerAccounts)
+        .signers([admin])
+        .rpc();
+      assert.fail("Dust first deposit should be rejected");
+    } catch (error) {
+      assert.include(error.message, "InsufficientLiquidityMinted");
+    }
+
+    // The cheapest possible first deposit buys a single LP token
+    await program.methods
+      .addLiquidity(
+        new BN(MINIMUM_LIQUIDITY + 1),
+        new BN(MINIMUM_LIQUIDITY + 1),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial(attackerAccounts)
+      .signers([admin])
+      .rpc();
+    assert.equal(
+      (await getAccount(provider.connection, pool.userLpAta)).amount,
+      1n,
+      "Attacker should only own the unlocked LP"
+    );
+
+    // Donate straight to the vaults to try to inflate the LP share price
+    const donation = 100_000_000;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createTransferInstruction(userBaseTokenAccount, pool.baseVault, admin.publicKey, donation),
+        createTransferInstruction(pool.userPairedAta, pool.pairedVault, admin.publicKey, donation)
+      ),
+      [admin]
+    );
+
+    // The victim deposits after the donation
+    const victim = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(victim.publicKey, 2e9)
+    );
+    const victimBaseAta = getAssociatedTokenAddressSync(baseTokenMint.publicKey, victim.publicKey);
+    const victimPairedAta = getAssociatedTokenAddressSync(pool.mint.publicKey, victim.publicKey);
+    const victimLpAta = getAssociatedTokenAddressSync(pool.lpMint, victim.publicKey);
+    const victimDeposit = 1_000_000;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountIdempotentInstruction(
+          admin.publicKey,
+          victimBaseAta,
+          victim.publicKey,
+          baseTokenMint.publicKey
+        ),
+        createAssociatedTokenAccountIdempotentInstruction(
+          admin.publicKey,
+          victimPairedAta,
+          victim.publicKey,
+          pool.mint.publicKey
+        ),
+        createAssociatedTokenAccountIdempotentInstruction(
+          admin.publicKey,
+          victimLpAta,
+          victim.publicKey,
+          pool.lpMint
+        ),
+        createMintToInstruction(baseTokenMint.publicKey, victimBaseAta, admin.publicKey, victimDeposit),
+        createMintToInstruction(pool.mint.publicKey, victimPairedAta, admin.publicKey, victimDeposit)
+      ),
+      [admin]
+    );
+
+    const victimAccounts = accountsFor(victim.publicKey, victimBaseAta, victimPairedAta, victimLpAta);
+    await program.methods
+      .addLiquidity(
+        new BN(victimDeposit),
+        new BN(victimDeposit),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial(victimAccounts)
+      .signers([victim])
+      .rpc();
+
+    const victimLp = (await getAccount(provider.connection, victimLpAta)).amount;
+    assert.equal(
+      victimLp,
+      BigInt(victimDeposit),
+      "Donations must not dilute the victim's LP"
+    );
+
+    // The attacker exits with their single LP token
+    await program.methods
+      .removeLiquidity(new BN(1), new BN(0), new BN(0), null)
+      .accountsPartial(attackerAccounts)
+      .signers([admin])
+      .rpc();
+
+    // The victim still gets their deposit back, less rounding
+    await program.methods
+      .removeLiquidity(new BN(victimLp.toString()), new BN(0), new BN(0), null)
+      .accountsPartial(victimAccounts)
+      .signers([victim])
+      .rpc();
+
+    const victimBase = (await getAccount(provider.connection, victimBaseAta)).amount;
+    const victimPaired = (await getAccount(provider.connection, victimPairedAta)).amount;
+    assert.isTrue(victimBase >= BigInt(victimDeposit - 1), "Victim should recover their MOJO");
+    assert.isTrue(victimPaired >= BigInt(victimDeposit - 1), "Victim should recover their player token");
+
+    // The locked liquidity keeps the pool alive
+    const pair = await program.account.pair.fetch(pool.pair);
+    assert.equal(pair.totalLiquidity.toNumber(), MINIMUM_LIQUIDITY, "Locked LP should remain");
+    assert.isTrue(pair.baseReserve.gtn(0) && pair.pairedReserve.gtn(0), "Pool should never be empty");
+  });
+
+  it("Deposits only the optimal amounts and enforces the liquidity bounds", async () => {
+    console.log("🔄 Testing optimal-ratio deposits...");
+
+    const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED);
+    const liquidityAccounts = {
+      user: admin.publicKey,
+      pair: pool.pair,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: pool.mint.publicKey,
+      baseVault: pool.baseVault,
+      userBaseAta: userBaseTokenAccount,
+      pairedVault: pool.pairedVault,
+      userPairedAta: pool.userPairedAta,
+      lpMint: pool.lpMint,
+      userLpAta: pool.userLpAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    // Open the pool at 1 base : 2 paired
+    await program.methods
+      .addLiquidity(
+        new BN(100_000_000),
+        new BN(200_000_000),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial(liquidityAccounts)
+      .signers([admin])
+      .rpc();
+    const pairBefore = await program.account.pair.fetch(pool.pair);
+
+    const expectSlippage = async (args: number[]) => {
+      try {
+        await program.methods
+          .addLiquidity(
+            new BN(args[0]),
+            new BN(args[1]),
+            new BN(args[2]),
+            new BN(args[3]),
+            new BN(args[4]),
+            null
+          )
+          .accountsPartial(liquidityAccounts)
+          .signers([admin])
+          .rpc();
+        assert.fail("Deposit outside its bounds should be rejected");
+      } catch (error) {
+        assert.include(error.message, "SlippageExceeded");
+      }
+    };
+
+    // 10 base needs 20 paired, below the paired minimum of 25
+    await expectSlippage([10_000_000, 50_000_000, 0, 25_000_000, 0]);
+    // 20 paired needs 10 base, below the base minimum of 15
+    await expectSlippage([50_000_000, 20_000_000, 15_000_000, 0, 0]);
+    // Depositing 10% of the pool cannot mint more than 10% of the LP
+    const lpShare = pairBefore.totalLiquidity.divn(10).toNumber();
+    await expectSlippage([10_000_000, 20_000_000, 0, 0, lpShare + 1]);
+
+    // An oversized paired side is scaled down to the pool ratio
+    const baseBefore = await getAccount(provider.connection, userBaseTokenAccount);
+    const pairedBefore = await getAccount(provider.connection, pool.userPairedAta);
+    await program.methods
+      .addLiquidity(
+        new BN(10_000_000),
+        new BN(50_000_000),
+        new BN(10_000_000),
+        new BN(20_000_000),
+        new BN(lpShare),
+        null
+      )
+      .accountsPartial(liquidityAccounts)
+      .signers([admin])
+      .rpc();
+    const baseAfter = await getAccount(provider.connection, userBaseTokenAccount);
+    const pairedAfter = await getAccount(provider.connection, pool.userPairedAta);
+
+    assert.equal(baseBefore.amount - baseAfter.amount, 10_000_000n, "All base should be taken");
+    assert.equal(
+      pairedBefore.amount - pairedAfter.amount,
+      20_000_000n,
+      "Only the matching paired amount should be taken"
+    );
+    const pairAfter = await program.account.pair.fetch(pool.pair);
+    assert.equal(
+      pairAfter.totalLiquidity.sub(pairBefore.totalLiquidity).toNumber(),
+      lpShare,
+      "LP should be minted for the optimal amounts"
+    );
+  });
+
+  it("Zaps a single token into a pool", async () => {
+    console.log("🔄 Testing single-sided liquidity deposits...");
+
+    const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED, LP_FEE_RATE);
+    const liquidityAccounts = {
+      user: admin.publicKey,
+      pair: pool.pair,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: pool.mint.publicKey,
+      baseVault: pool.baseVault,
+      userBaseAta: userBaseTokenAccount,
+      pairedVault: pool.pairedVault,
+      userPairedAta: pool.userPairedAta,
+      lpMint: pool.lpMint,
+      userLpAta: pool.userLpAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const zapAccounts = {
+      ...liquidityAccounts,
+      platformState: platformStatePda,
+      feeCollector: platformTreasury,
+    };
+
+    await program.methods
+      .addLiquidity(
+        new BN(100_000_000),
+        new BN(100_000_000),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial(liquidityAccounts)
+      .signers([admin])
+      .rpc();
+
+    const balances = async () => ({
+      base: (await getAccount(provider.connection, userBaseTokenAccount)).amount,
+      paired: (await getAccount(provider.connection, pool.userPairedAta)).amount,
+      lp: (await getAccount(provider.connection, pool.userLpAta)).amount,
+    });
+    const zapIn = 20_000_000;
+
+    // 20 MOJO into a 100/100 pool can never be worth 10% of the LP
+    try {
+      await program.methods
+        .addLiquiditySingleSided(new BN(zapIn), true, new BN(10_000_000), null)
+        .accountsPartial(zapAccounts)
+        .signers([admin])
+        .rpc();
+      assert.fail("Zap below the minimum LP should be rejected");
+    } catch (error) {
+      assert.include(error.message, "SlippageExceeded");
+    }
+
+    for (const isBaseInput of [true, false]) {
+      const before = await balances();
+      await program.methods
+        .addLiquiditySingleSided(new BN(zapIn), isBaseInput, new BN(1), null)
+        .accountsPartial(zapAccounts)
+        .signers([admin])
+        .rpc();
+      const after = await balances();
+
+      const [inputSpent, otherChange] = isBaseInput
+        ? [before.base - after.base, after.paired - before.paired]
+        : [before.paired - after.paired, after.base - before.base];
+      assert.isTrue(after.lp > before.lp, "LP should be minted");
+      assert.isTrue(
+        inputSpent <= BigInt(zapIn) && inputSpent >= BigInt(zapIn - 5),
+        `Nearly all of the input should be deposited, spent ${inputSpent}`
+      );
+      assert.isTrue(
+        otherChange >= 0n && otherChange <= 5n,
+        `Only dust of the swapped side should be left over, got ${otherChange}`
+      );
+    }
+  });
+
+  it("Zaps liquidity out into a single token", async () => {
+    console.log("🔄 Testing single-sided liquidity withdrawals...");
+
+    const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED, LP_FEE_RATE);
+    const liquidityAccounts = {
+      user: admin.publicKey,
+      pair: pool.pair,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: pool.mint.publicKey,
+      baseVault: pool.baseVault,
+      userBaseAta: userBaseTokenAccount,
+      pairedVault: pool.pairedVault,
+      userPairedAta: pool.userPairedAta,
+      lpMint: pool.lpMint,
+      userLpAta: pool.userLpAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const zapAccounts = {
+      ...liquidityAccounts,
+      platformState: platformStatePda,
+      feeCollector: platformTreasury,
+    };
+
+    await program.methods
+      .addLiquidity(
+        new BN(100_000_000),
+        new BN(100_000_000),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial(liquidityAccounts)
+      .signers([admin])
+      .rpc();
+
+    const balances = async () => ({
+      base: (await getAccount(provider.connection, userBaseTokenAccount)).amount,
+      paired: (await getAccount(provider.connection, pool.userPairedAta)).amount,
+      lp: (await getAccount(provider.connection, pool.userLpAta)).amount,
+    });
+    const pair = await program.account.pair.fetch(pool.pair);
+    const lpAmount = pair.totalLiquidity.divn(4);
+    // Each side of a quarter of a 100/100 pool
+    const share = BigInt(pair.baseReserve.mul(lpAmount).div(pair.totalLiquidity).toString());
+
+    // Swapping the unwanted side back costs price impact and fees, so the
+    // result is always worth less than both sides at the pool price
+    try {
+      await program.methods
+        .removeLiquiditySingleSided(
+          lpAmount,
+          true,
+          new BN((2n * share).toString()),
+          null
+        )
+        .accountsPartial(zapAccounts)
+        .signers([admin])
+        .rpc();
+      assert.fail("Zap-out below the minimum should be rejected");
+    } catch (error) {
+      assert.include(error.message, "SlippageExceeded");
+    }
+
+    for (const wantBase of [true, false]) {
+      const before = await balances();
+      await program.methods
+        .removeLiquiditySingleSided(
+          lpAmount,
+          wantBase,
+          new BN(share.toString()),
+          null
+        )
+        .accountsPartial(zapAccounts)
+        .signers([admin])
+        .rpc();
+      const after = await balances();
+
+      const [wanted, unwanted] = wantBase
+        ? [after.base - before.base, after.paired - before.paired]
+        : [after.paired - before.paired, after.base - before.base];
+      assert.equal(before.lp - after.lp, BigInt(lpAmount.toString()), "LP should be burned");
+      assert.isTrue(wanted > share, `Swapped side should add to the output, got ${wanted}`);
+      assert.equal(unwanted, 0n, "None of the unwanted token should be left with the user");
+    }
+  });
+
+  it("Rejects trades that land after their deadline", async () => {
+    console.log("🔄 Testing transaction deadlines...");
+
+    const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED, LP_FEE_RATE);
+    const liquidityAccounts = {
+      user: admin.publicKey,
+      pair: pool.pair,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: pool.mint.publicKey,
+      baseVault: pool.baseVault,
+      userBaseAta: userBaseTokenAccount,
+      pairedVault: pool.pairedVault,
+      userPairedAta: pool.userPairedAta,
+      lpMint: pool.lpMint,
+      userLpAta: pool.userLpAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const swapAccounts = {
+      ...liquidityAccounts,
+      platformState: platformStatePda,
+      feeCollector: platformTreasury,
+    };
+    const clusterTime = await provider.connection.getBlockTime(
+      await provider.connection.getSlot()
+    );
+    const future = new BN(clusterTime + 600);
+    const past = new BN(clusterTime - 600);
+
+    await program.methods
+      .addLiquidity(
+        new BN(100_000_000),
+        new BN(100_000_000),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        future
+      )
+      .accountsPartial(liquidityAccounts)
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .swap(new BN(1_000_000), new BN(0), true, future)
+      .accountsPartial(swapAccounts)
+      .signers([admin])
+      .rpc();
+
+    const expectExpired = async (name: string, call: Promise<string>) => {
+      try {
+        await call;
+        assert.fail(`${name} past its deadline should be rejected`);
+      } catch (error) {
+        assert.include(error.message, "TransactionExpired");
+      }
+    };
+    await expectExpired(
+      "Swap",
+      program.methods
+        .swap(new BN(1_000_000), new BN(0), true, past)
+        .accountsPartial(swapAccounts)
+        .signers([admin])
+        .rpc()
+    );
+    await expectExpired(
+      "Deposit",
+      program.methods
+        .addLiquidity(
+          new BN(1_000_000),
+          new BN(1_000_000),
+          new BN(0),
+          new BN(0),
+          new BN(0),
+          past
+        )
+        .accountsPartial(liquidityAccounts)
+        .signers([admin])
+        .rpc()
+    );
+    await expectExpired(
+      "Withdrawal",
+      program.methods
+        .removeLiquidity(new BN(1_000), new BN(0), new BN(0), past)
+        .accountsPartial(liquidityAccounts)
+        .signers([admin])
+        .rpc()
+    );
+  });
+
+  it("Emits events for liquidity and swaps", async () => {
+    console.log("🔄 Testing emitted events...");
+
+    const pool = await setupPair(INITIAL_LIQUIDITY_PAIRED, LP_FEE_RATE);
+    const liquidityAccounts = {
+      user: admin.publicKey,
+      pair: pool.pair,
+      baseTokenMint: baseTokenMint.publicKey,
+      pairedTokenMint: pool.mint.publicKey,
+      baseVault: pool.baseVault,
+      userBaseAta: userBaseTokenAccount,
+      pairedVault: pool.pairedVault,
+      userPairedAta: pool.userPairedAta,
+      lpMint: pool.lpMint,
+      userLpAta: pool.userLpAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      pairedTokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const eventParser = new anchor.EventParser(
+      program.programId,
+      new anchor.BorshCoder(program.idl)
+    );
+    const eventsOf = async (signature: string) => {
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return [...eventParser.parseLogs(tx.meta.logMessages)];
+    };
+
+    const deposit = await program.methods
+      .addLiquidity(
+        new BN(100_000_000),
+        new BN(100_000_000),
+        new BN(0),
+        new BN(0),
+        new BN(0),
+        null
+      )
+      .accountsPartial(liquidityAccounts)
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+    const [added] = await eventsOf(deposit);
+    assert.equal(added.name, "liquidityAdded");
+    assert.isTrue(added.data.pair.equals(pool.pair));
+    assert.isTrue(added.data.user.equals(admin.publicKey));
+    assert.equal(added.data.baseAmount.toNumber(), 100_000_000);
+    assert.equal(
+      added.data.lpMinted.toNumber(),
+      added.data.totalLiquidity.toNumber() - MINIMUM_LIQUIDITY
+    );
+
+    const amountIn = new BN(5_000_000);
+    const quote = await program.methods
+      .quoteSwap(amountIn, true)
+      .accountsPartial({
+        platformState: platformStatePda,
+        pair: pool.pair,
+        baseTokenMint: baseTokenMint.publicKey,
+        pairedTokenMint: pool.mint.publicKey,
+      })
+      .view();
+    const trade = await program.methods
+      .swap(amountIn, new BN(0), true, null)
+      .accountsPartial({
+        ...liquidityAccounts,
+        platformState: platformStatePda,
+        feeCollector: platformTreasury,
+      })
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+    const [swapped] = await eventsOf(trade);
+    const pair = await program.account.pair.fetch(pool.pair);
+    assert.equal(swapped.name, "swapped");
+    assert.isTrue(swapped.data.user.equals(admin.publicKey));
+    assert.isTrue(swapped.data.isBaseInput);
+    assert.isTrue(swapped.data.amountIn.eq(quote.amountIn));
+    assert.isTrue(swapped.data.amountOut.eq(quote.amountOut));
+    assert.isTrue(swapped.data.protocolFee.eq(quote.protocolFee));
+    assert.isTrue(swapped.data.baseReserve.eq(pair.baseReserve));
+    assert.isTrue(swapped.data.pairedReserve.eq(pair.pairedReserve));
+
+    const lpBurned = new BN(1_000_000);
+    const withdrawal = await program.methods
+      .removeLiquidity(lpBurned, new BN(0), new BN(0), null)
+      .accountsPartial(liquidityAccounts)
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+    const [removed] = await eventsOf(withdrawal);
+    assert.equal(removed.name, "liquidityRemoved");
+    assert.isTrue(removed.data.lpBurned.eq(lpBurned));
+    assert.isTrue(
+      removed.data.totalLiquidity.eq(pair.totalLiquidity.sub(lpBurned))
+    );
+  });
+
   // SAD PATH TESTS
 
   it("Fails when trying to swap with insufficient funds", async () => {
@@ -1908,7 +4108,8 @@ describe("Platform Program", () => {
         .swap(
           new BN(swapAmount),
           new BN(minAmountOut),
-          isBaseInput
+          isBaseInput,
+          null
         )
         .accountsPartial({
           user: admin.publicKey,
@@ -1922,6 +4123,7 @@ describe("Platform Program", () => {
           userPairedAta: userPairedTokenAccount,
           feeCollector: platformTreasury,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([admin])
         .rpc();
@@ -1946,7 +4148,8 @@ describe("Platform Program", () => {
         .swap(
           new BN(swapAmount),
           new BN(minAmountOut),
-          isBaseInput
+          isBaseInput,
+          null
         )
         .accountsPartial({
           user: admin.publicKey,
@@ -1960,6 +4163,7 @@ describe("Platform Program", () => {
           userPairedAta: userPairedTokenAccount,
           feeCollector: platformTreasury,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([admin])
         .rpc();
@@ -1996,7 +4200,8 @@ describe("Platform Program", () => {
         .swap(
           new BN(swapAmount),
           new BN(minAmountOut),
-          isBaseInput
+          isBaseInput,
+          null
         )
         .accountsPartial({
           user: admin.publicKey,
@@ -2010,6 +4215,7 @@ describe("Platform Program", () => {
           userPairedAta: userPairedTokenAccount,
           feeCollector: platformTreasury,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([admin])
         .rpc();
@@ -2046,7 +4252,8 @@ describe("Platform Program", () => {
         .swap(
           new BN(swapAmount),
           new BN(minAmountOut),
-          isBaseInput
+          isBaseInput,
+          null
         )
         .accountsPartial({
           user: admin.publicKey,
@@ -2060,6 +4267,7 @@ describe("Platform Program", () => {
           userPairedAta: userBaseTokenAccount, // Incorrect token account
           feeCollector: platformTreasury,
           tokenProgram: TOKEN_PROGRAM_ID,
+          pairedTokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([admin])
         .rpc();